@@ -1,13 +1,16 @@
-use std::collections::VecDeque;
 use std::io::Read;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
+use std::pin::pin;
 
 use futures::StreamExt;
+
 use pcloud::entry::Entry;
+use pcloud::error::ApiErrorCode;
 use pcloud::file::File;
+use pcloud::folder::walk::{WalkFilter, WalkOptions};
 use pcloud::folder::Folder;
 use pcloud::Client;
-use tokio::io::{AsyncWriteExt, BufWriter};
+use tokio::io::BufWriter;
 
 fn compute_sha1(path: &PathBuf) -> std::io::Result<String> {
     let mut file = std::fs::OpenOptions::new().read(true).open(path)?;
@@ -27,30 +30,6 @@ struct DownloadManager<'a> {
     client: &'a Client,
     dry_run: bool,
     skip_existing: bool,
-    queue: VecDeque<(Entry, PathBuf)>,
-}
-
-async fn download_file(url: String, path: impl AsRef<Path>) -> anyhow::Result<usize> {
-    let writer = tokio::fs::OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .open(path)
-        .await?;
-    let mut writer = BufWriter::new(writer);
-
-    let res = pcloud::reqwest::get(&url).await?;
-    res.error_for_status_ref()?;
-    let mut stream = res.bytes_stream();
-
-    let mut size: usize = 0;
-    while let Some(chunk) = stream.next().await {
-        let chunk = chunk?;
-        size += chunk.len();
-        writer.write_all(&chunk).await?;
-    }
-
-    Ok(size)
 }
 
 impl<'a> DownloadManager<'a> {
@@ -59,11 +38,10 @@ impl<'a> DownloadManager<'a> {
             client,
             dry_run,
             skip_existing,
-            queue: VecDeque::with_capacity(1),
         }
     }
 
-    async fn process_file(&mut self, file: File, target: PathBuf) -> anyhow::Result<usize> {
+    async fn process_file(&self, file: File, target: PathBuf) -> anyhow::Result<usize> {
         tracing::info!("downloading file {:?} to {target:?}", file.base.name);
         if self.dry_run {
             return Ok(0);
@@ -87,49 +65,39 @@ impl<'a> DownloadManager<'a> {
                 std::fs::create_dir_all(parent)?;
             }
         }
-        let file_links = self.client.get_file_link(file.file_id).await?;
-        for link in file_links.links() {
-            match download_file(link.to_string(), &target).await {
-                Ok(size) => return Ok(size),
-                Err(err) => {
-                    tracing::error!(message = "download failed", cause = %err);
-                }
-            }
-        }
-        anyhow::bail!("unable to download file")
+        let writer = tokio::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&target)
+            .await?;
+        let mut writer = BufWriter::new(writer);
+        let size = self.client.download(file.file_id, &mut writer).await?;
+        Ok(size as usize)
     }
 
-    async fn process_folder(&mut self, folder: Folder, target: PathBuf) -> anyhow::Result<()> {
+    async fn process_folder(&self, folder: Folder, target: PathBuf) -> anyhow::Result<usize> {
         tracing::info!("downloading folder {:?} to {target:?}", folder.base.name);
-        if let Some(children) = folder.contents {
-            for child in children {
-                let new_target = target.join(child.base().name.as_str());
-                self.queue.push_back((child, new_target));
-            }
-        } else {
-            let res = self.client.list_folder(folder.folder_id).await?;
-            for child in res.contents.into_iter().flatten() {
-                let new_target = target.join(child.base().name.as_str());
-                self.queue.push_back((child, new_target));
+        let mut count = 0;
+        let options = WalkOptions::default().with_filter(WalkFilter::FilesOnly);
+        let mut walk = pin!(self.client.walk(folder.folder_id, options));
+        while let Some(item) = walk.next().await {
+            let (path, entry) = item?;
+            if let Entry::File(file) = entry {
+                let target = path
+                    .segments()
+                    .fold(target.clone(), |acc, segment| acc.join(segment));
+                count += self.process_file(file, target).await?;
             }
         }
-        Ok(())
+        Ok(count)
     }
 
-    async fn run(mut self, entry: Entry, target: PathBuf) -> anyhow::Result<()> {
-        let mut count = 0;
-        self.queue.push_front((entry, target));
-        while let Some((entry, target)) = self.queue.pop_front() {
-            match entry {
-                Entry::File(file) => {
-                    count += self.process_file(file, target).await?;
-                }
-                Entry::Folder(folder) => {
-                    self.process_folder(folder, target).await?;
-                }
-            }
-            tracing::info!("{} elements left in the queue", self.queue.len());
-        }
+    async fn run(self, entry: Entry, target: PathBuf) -> anyhow::Result<()> {
+        let count = match entry {
+            Entry::File(file) => self.process_file(file, target).await?,
+            Entry::Folder(folder) => self.process_folder(folder, target).await?,
+        };
         let formatter = human_number::Formatter::binary()
             .with_decimals(2)
             .with_unit("B");
@@ -159,7 +127,7 @@ impl Command {
         // assuming it's doing a `ls` on a folder at first
         match client.list_folder(&self.remote_path).await {
             Ok(folder) => Ok(Entry::Folder(folder)),
-            Err(pcloud::Error::Protocol(2005, _)) => {
+            Err(pcloud::Error::Protocol(ApiErrorCode::DirectoryNotFound, _)) => {
                 // try with a file if a folder is not found
                 client
                     .get_file_checksum(&self.remote_path)
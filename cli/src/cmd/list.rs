@@ -1,4 +1,5 @@
 use pcloud::entry::Entry;
+use pcloud::error::ApiErrorCode;
 use pcloud::file::File;
 
 #[derive(Default)]
@@ -111,7 +112,7 @@ impl Command {
         let folder_res = client.list_folder(&self.path).await;
         match folder_res {
             Ok(folder) => Ok(folder.contents.unwrap_or_default()),
-            Err(pcloud::Error::Protocol(2005, _)) => {
+            Err(pcloud::Error::Protocol(ApiErrorCode::DirectoryNotFound, _)) => {
                 // try with a file if a folder is not found
                 client
                     .get_file_checksum(&self.path)
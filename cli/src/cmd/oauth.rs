@@ -1,7 +1,7 @@
-use std::collections::HashMap;
 use std::process::Stdio;
 
 use anyhow::Context;
+use pcloud::oauth2::OAuth2Flow;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpListener;
 
@@ -10,7 +10,6 @@ const RCLONE_CLIENT_SECRET: &str = "RUqH9GooHqkC4IFkifsD1haRu9fy";
 const BIND_HOST: &str = "127.0.0.1";
 const REDIRECT_HOST: &str = "localhost";
 const REDIRECT_PORT: u16 = 53682;
-const AUTHORIZE_URL: &str = "https://my.pcloud.com/oauth2/authorize";
 
 const SUCCESS_BODY: &[u8] = b"<!doctype html><html><head><meta charset=\"utf-8\"><title>pcloud-cli</title></head><body><h1>Authorization complete</h1><p>You can close this window.</p></body></html>";
 
@@ -37,10 +36,9 @@ impl Command {
             .await
             .with_context(|| format!("unable to bind {BIND_HOST}:{REDIRECT_PORT}"))?;
 
-        let auth_url = format!(
-            "{AUTHORIZE_URL}?client_id={client_id}&response_type=code&redirect_uri=http%3A%2F%2F{REDIRECT_HOST}%3A{REDIRECT_PORT}%2F",
-            client_id = self.client_id,
-        );
+        let flow = OAuth2Flow::new(&self.client_id, &self.client_secret)
+            .with_redirect_uri(format!("http://{REDIRECT_HOST}:{REDIRECT_PORT}/"));
+        let auth_url = flow.authorize_url();
 
         eprintln!("Open the following URL in your browser to authorize pcloud-cli:");
         eprintln!();
@@ -53,25 +51,12 @@ impl Command {
         }
         eprintln!("Waiting for authorization on http://{REDIRECT_HOST}:{REDIRECT_PORT}/ ...");
 
-        let params = wait_for_callback(&listener).await?;
-
-        let code = params
-            .get("code")
-            .ok_or_else(|| match params.get("error") {
-                Some(err) => anyhow::anyhow!("authorization failed: {err}"),
-                None => anyhow::anyhow!("missing 'code' parameter in OAuth callback"),
-            })?
-            .to_owned();
-
-        let exchange_base_url = params
-            .get("hostname")
-            .map(|host| format!("https://{host}"))
-            .unwrap_or_else(|| pcloud::US_REGION.to_string());
-
-        tracing::debug!("exchanging code at {exchange_base_url}");
-        let exchange = pcloud::Client::new(exchange_base_url, pcloud::Credentials::anonymous())?;
-        let token = exchange
-            .oauth2_token(&self.client_id, &self.client_secret, &code)
+        let target = wait_for_callback(&listener).await?;
+        let callback = flow.parse_callback(&target)?;
+
+        tracing::debug!("exchanging code at {}", callback.base_url());
+        let token = flow
+            .exchange(&callback)
             .await
             .context("OAuth token exchange failed")?;
 
@@ -82,7 +67,8 @@ impl Command {
     }
 }
 
-async fn wait_for_callback(listener: &TcpListener) -> anyhow::Result<HashMap<String, String>> {
+/// Accepts the redirection of the browser and returns the request target, with its query.
+async fn wait_for_callback(listener: &TcpListener) -> anyhow::Result<String> {
     let (mut stream, _) = listener.accept().await?;
     let mut buffer = Vec::with_capacity(2048);
     let mut chunk = [0u8; 1024];
@@ -105,12 +91,11 @@ async fn wait_for_callback(listener: &TcpListener) -> anyhow::Result<HashMap<Str
         .lines()
         .next()
         .ok_or_else(|| anyhow::anyhow!("empty OAuth callback request"))?;
-    let path = request_line
+    let target = request_line
         .split_whitespace()
         .nth(1)
-        .ok_or_else(|| anyhow::anyhow!("malformed OAuth callback request line"))?;
-    let query = path.split_once('?').map(|(_, q)| q).unwrap_or("");
-    let params = parse_query(query);
+        .ok_or_else(|| anyhow::anyhow!("malformed OAuth callback request line"))?
+        .to_owned();
 
     let response = format!(
         "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
@@ -120,52 +105,7 @@ async fn wait_for_callback(listener: &TcpListener) -> anyhow::Result<HashMap<Str
     stream.write_all(SUCCESS_BODY).await?;
     stream.shutdown().await.ok();
 
-    Ok(params)
-}
-
-fn parse_query(query: &str) -> HashMap<String, String> {
-    let mut out = HashMap::new();
-    if query.is_empty() {
-        return out;
-    }
-    for pair in query.split('&') {
-        let (key, value) = match pair.split_once('=') {
-            Some((k, v)) => (k, v),
-            None => (pair, ""),
-        };
-        out.insert(percent_decode(key), percent_decode(value));
-    }
-    out
-}
-
-fn percent_decode(input: &str) -> String {
-    let bytes = input.as_bytes();
-    let mut out = Vec::with_capacity(bytes.len());
-    let mut i = 0;
-    while i < bytes.len() {
-        match bytes[i] {
-            b'+' => {
-                out.push(b' ');
-                i += 1;
-            }
-            b'%' if i + 2 < bytes.len() => {
-                let hi = (bytes[i + 1] as char).to_digit(16);
-                let lo = (bytes[i + 2] as char).to_digit(16);
-                if let (Some(hi), Some(lo)) = (hi, lo) {
-                    out.push((hi * 16 + lo) as u8);
-                    i += 3;
-                } else {
-                    out.push(bytes[i]);
-                    i += 1;
-                }
-            }
-            other => {
-                out.push(other);
-                i += 1;
-            }
-        }
-    }
-    String::from_utf8_lossy(&out).into_owned()
+    Ok(target)
 }
 
 #[cfg(target_os = "macos")]
@@ -192,26 +132,3 @@ fn spawn_detached(program: &str, args: &[&str]) -> std::io::Result<()> {
         .spawn()
         .map(|_| ())
 }
-
-#[cfg(test)]
-mod tests {
-    use super::{parse_query, percent_decode};
-
-    #[test]
-    fn parses_query_string() {
-        let params = parse_query("code=abc&hostname=eapi.pcloud.com&extra=hello%20world");
-        assert_eq!(params.get("code").map(String::as_str), Some("abc"));
-        assert_eq!(
-            params.get("hostname").map(String::as_str),
-            Some("eapi.pcloud.com")
-        );
-        assert_eq!(params.get("extra").map(String::as_str), Some("hello world"));
-    }
-
-    #[test]
-    fn decodes_percent_encoding() {
-        assert_eq!(percent_decode("a%2Bb"), "a+b");
-        assert_eq!(percent_decode("a+b"), "a b");
-        assert_eq!(percent_decode("plain"), "plain");
-    }
-}
@@ -1,6 +1,5 @@
 use pcloud::builder::Error as ClientBuilderError;
 use pcloud::Client;
-use pcloud::Credentials;
 use pcloud::Region;
 use serde::Deserialize;
 use std::path::Path;
@@ -12,12 +11,6 @@ pub struct CredentialsConfig {
     password: String,
 }
 
-impl CredentialsConfig {
-    fn build(self) -> Credentials {
-        Credentials::username_password(self.username, self.password)
-    }
-}
-
 #[derive(Default, Deserialize)]
 pub struct Config {
     credentials: Option<CredentialsConfig>,
@@ -39,8 +32,8 @@ impl Config {
         if let Some(timeout) = self.timeout.map(Duration::from_secs) {
             reqwest_builder = reqwest_builder.timeout(timeout);
         }
-        if let Some(creds) = self.credentials.map(|c| c.build()) {
-            builder.set_credentials(creds);
+        if let Some(creds) = self.credentials {
+            builder.set_login(creds.username, creds.password);
         }
         if let Some(region) = self.region {
             builder.set_region(region);
@@ -2,6 +2,7 @@ mod render;
 mod router;
 
 use clap::Parser;
+use pcloud::stream::provider::LinkProvider;
 use pcloud::Client;
 use std::{fmt::Write, net::IpAddr, str::FromStr, string::FromUtf8Error, sync::Arc};
 use tower_http::trace::TraceLayer;
@@ -155,21 +156,26 @@ impl FolderCloudPath {
         self.inner
     }
 
-    pub fn raw(&self) -> RawFolderCloudPath {
+    pub fn raw(&self) -> RawFolderCloudPath<'_> {
         RawFolderCloudPath(self)
     }
 
-    pub fn encoded(&self) -> EncodedFolderCloudPath {
+    pub fn encoded(&self) -> EncodedFolderCloudPath<'_> {
         EncodedFolderCloudPath(self)
     }
 }
 
 #[derive(Clone)]
-struct Storage(Arc<Client>);
+struct Storage(Arc<Client>, LinkProvider);
 
 impl Storage {
     fn new(client: Client) -> Self {
-        Self(Arc::new(client))
+        let links = LinkProvider::new(client.clone());
+        Self(Arc::new(client), links)
+    }
+
+    fn links(&self) -> &LinkProvider {
+        &self.1
     }
 }
 
@@ -2,6 +2,7 @@ use crate::{RootPrefix, Storage};
 use axum::extract::{Path, Query};
 use axum::response::{Html, IntoResponse};
 use axum::Extension;
+use pcloud::error::ApiErrorCode;
 use pcloud::file::File;
 use std::str::FromStr;
 use std::string::FromUtf8Error;
@@ -29,27 +30,15 @@ pub enum Error {
 impl Error {
     fn status_code(&self) -> axum::http::StatusCode {
         match self {
-            // Bad request
-            Self::UnableGetFile(pcloud::Error::Protocol(2010, _))
-            | Self::UnableListFolder(pcloud::Error::Protocol(2010, _))
-            | Self::InvalidPath(_) => axum::http::StatusCode::BAD_REQUEST,
-            // Unauthorized
-            Self::UnableGetFile(pcloud::Error::Protocol(1000, _))
-            | Self::UnableListFolder(pcloud::Error::Protocol(1000, _))
-            | Self::UnableGetFile(pcloud::Error::Protocol(2000, _))
-            | Self::UnableListFolder(pcloud::Error::Protocol(2000, _)) => {
-                axum::http::StatusCode::UNAUTHORIZED
-            }
-            // Forbidden
-            Self::UnableGetFile(pcloud::Error::Protocol(2003, _))
-            | Self::UnableListFolder(pcloud::Error::Protocol(2003, _)) => {
-                axum::http::StatusCode::FORBIDDEN
-            }
-            // Not found
-            Self::UnableGetFile(pcloud::Error::Protocol(2009, _))
-            | Self::UnableListFolder(pcloud::Error::Protocol(2005, _)) => {
-                axum::http::StatusCode::NOT_FOUND
-            }
+            Self::InvalidPath(_) => axum::http::StatusCode::BAD_REQUEST,
+            Self::UnableGetFile(inner) | Self::UnableListFolder(inner) => match inner.api_code() {
+                Some(ApiErrorCode::InvalidPath) => axum::http::StatusCode::BAD_REQUEST,
+                Some(ApiErrorCode::AccessDenied) => axum::http::StatusCode::FORBIDDEN,
+                _ if inner.is_auth_error() => axum::http::StatusCode::UNAUTHORIZED,
+                _ if inner.is_not_found() => axum::http::StatusCode::NOT_FOUND,
+                _ if inner.is_rate_limited() => axum::http::StatusCode::TOO_MANY_REQUESTS,
+                _ => axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+            },
             _ => axum::http::StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -137,16 +126,20 @@ async fn handle(
                 .get_file_checksum(&identifier)
                 .await
                 .map_err(Error::UnableGetFile)?;
+            // the path may point to another file since its links were cached
+            if let Some(hash) = file.metadata.hash {
+                engine.links().evict_stale(&identifier, hash);
+            }
 
             if is_video(&file.metadata) {
-                engine.as_ref().get_video_link(identifier).await
+                engine.links().video_link(identifier).await
             } else if is_audio(&file.metadata) {
-                engine.as_ref().get_audio_link(identifier).await
+                engine.links().audio_link(identifier).await
             } else {
-                engine.as_ref().get_file_link(identifier).await
+                engine.links().file_link(identifier).await
             }
         } else {
-            engine.as_ref().get_file_link(identifier).await
+            engine.links().file_link(identifier).await
         };
 
         let list = list.map_err(Error::UnableGetFile)?;
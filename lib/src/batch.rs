@@ -0,0 +1,490 @@
+//! Operations applied to many files and folders at once.
+//!
+//! The operations are sent with a bounded concurrency and a failure doesn't stop the batch:
+//! the [`BatchReport`] tells, for every operation, whether it succeeded or why it failed.
+//! With [`BatchOptions::with_dry_run`], the planned operations are returned without being sent.
+//!
+//! ```rust,no_run
+//! use pcloud::batch::{BatchOptions, Target};
+//!
+//! # async fn example(client: &pcloud::Client) {
+//! let targets = vec![Target::file(42), Target::folder("/old/folder")];
+//! let report = client
+//!     .delete_many(targets, &BatchOptions::default().with_recursive(true))
+//!     .await;
+//! for (operation, error) in report.failed() {
+//!     eprintln!("{operation:?} failed: {error}");
+//! }
+//! # }
+//! ```
+
+use std::borrow::Cow;
+
+use futures_util::StreamExt;
+
+use crate::entry::Entry;
+use crate::file::FileIdentifier;
+use crate::folder::delete::RecursivePayload;
+use crate::folder::FolderIdentifier;
+
+/// A file or a folder targeted by an operation.
+#[derive(Clone, Debug)]
+pub enum Target<'a> {
+    /// A file, by ID or by path.
+    File(FileIdentifier<'a>),
+    /// A folder, by ID or by path.
+    Folder(FolderIdentifier<'a>),
+}
+
+impl<'a> Target<'a> {
+    /// Targets a file, by ID or by path.
+    pub fn file(identifier: impl Into<FileIdentifier<'a>>) -> Self {
+        Self::File(identifier.into())
+    }
+
+    /// Targets a folder, by ID or by path.
+    pub fn folder(identifier: impl Into<FolderIdentifier<'a>>) -> Self {
+        Self::Folder(identifier.into())
+    }
+}
+
+impl From<&Entry> for Target<'static> {
+    /// Targets an entry by its ID.
+    fn from(value: &Entry) -> Self {
+        match value {
+            Entry::File(file) => Self::File(FileIdentifier::FileId(file.file_id)),
+            Entry::Folder(folder) => Self::Folder(FolderIdentifier::FolderId(folder.folder_id)),
+        }
+    }
+}
+
+/// An operation of a batch.
+#[derive(Clone, Debug)]
+pub enum Operation<'a> {
+    /// Deletes a file or a folder, see [`BatchOptions::with_recursive`] for non empty folders.
+    Delete(Target<'a>),
+    /// Moves a file or a folder into another folder.
+    Move {
+        /// The file or the folder to move.
+        target: Target<'a>,
+        /// The folder to move it into.
+        to_folder: FolderIdentifier<'a>,
+    },
+    /// Copies a file or a folder, with its content, into another folder.
+    Copy {
+        /// The file or the folder to copy.
+        target: Target<'a>,
+        /// The folder to copy it into.
+        to_folder: FolderIdentifier<'a>,
+    },
+    /// Renames a file or a folder, keeping it in the same folder.
+    Rename {
+        /// The file or the folder to rename.
+        target: Target<'a>,
+        /// The new name.
+        name: Cow<'a, str>,
+    },
+}
+
+/// What a successful operation returned.
+#[derive(Debug)]
+pub enum Outcome {
+    /// The metadata of the file or the folder, after the operation.
+    Entry(Box<Entry>),
+    /// The number of entries removed by a recursive deletion.
+    Deleted(RecursivePayload),
+}
+
+/// The state of an operation of a batch.
+#[derive(Debug)]
+pub enum Status {
+    /// The operation was planned but not sent, in a dry run.
+    Planned,
+    /// The operation succeeded.
+    Succeeded(Outcome),
+    /// The operation failed.
+    Failed(crate::Error),
+}
+
+/// An operation and its outcome.
+#[derive(Debug)]
+pub struct BatchItem<'a> {
+    /// The operation, as given to the batch.
+    pub operation: Operation<'a>,
+    /// Whether the operation was planned, succeeded or failed.
+    pub status: Status,
+}
+
+/// Report returned by the batch operations, with an item per operation in the order given.
+#[derive(Debug, Default)]
+pub struct BatchReport<'a> {
+    /// The operations with their status, in the order they were given.
+    pub items: Vec<BatchItem<'a>>,
+}
+
+impl<'a> BatchReport<'a> {
+    /// Iterates over the operations planned in a dry run.
+    pub fn planned(&self) -> impl Iterator<Item = &Operation<'a>> {
+        self.items
+            .iter()
+            .filter(|item| matches!(item.status, Status::Planned))
+            .map(|item| &item.operation)
+    }
+
+    /// Iterates over the successful operations, with their outcome.
+    pub fn succeeded(&self) -> impl Iterator<Item = (&Operation<'a>, &Outcome)> {
+        self.items.iter().filter_map(|item| match item.status {
+            Status::Succeeded(ref outcome) => Some((&item.operation, outcome)),
+            _ => None,
+        })
+    }
+
+    /// Iterates over the failed operations, with their error.
+    pub fn failed(&self) -> impl Iterator<Item = (&Operation<'a>, &crate::Error)> {
+        self.items.iter().filter_map(|item| match item.status {
+            Status::Failed(ref error) => Some((&item.operation, error)),
+            _ => None,
+        })
+    }
+
+    /// Returns `true` when no operation failed.
+    pub fn is_success(&self) -> bool {
+        self.failed().next().is_none()
+    }
+}
+
+/// Options for the batch operations.
+#[derive(Clone, Debug)]
+pub struct BatchOptions {
+    concurrency: usize,
+    dry_run: bool,
+    recursive: bool,
+}
+
+impl Default for BatchOptions {
+    /// Sends 4 operations at a time, doesn't delete the content of the folders.
+    fn default() -> Self {
+        Self {
+            concurrency: 4,
+            dry_run: false,
+            recursive: false,
+        }
+    }
+}
+
+impl BatchOptions {
+    /// Sets the maximum number of operations sent at the same time.
+    pub fn set_concurrency(&mut self, value: usize) {
+        self.concurrency = value.max(1);
+    }
+
+    /// Sets the maximum number of operations sent at the same time and returns the updated options.
+    pub fn with_concurrency(mut self, value: usize) -> Self {
+        self.set_concurrency(value);
+        self
+    }
+
+    /// When enabled, the operations are returned as planned without being sent.
+    pub fn set_dry_run(&mut self, value: bool) {
+        self.dry_run = value;
+    }
+
+    /// Enables or disables the dry run and returns the updated options.
+    pub fn with_dry_run(mut self, value: bool) -> Self {
+        self.set_dry_run(value);
+        self
+    }
+
+    /// When enabled, the folders are deleted with their content, otherwise only empty
+    /// folders can be deleted.
+    pub fn set_recursive(&mut self, value: bool) {
+        self.recursive = value;
+    }
+
+    /// Enables or disables the recursive deletion and returns the updated options.
+    pub fn with_recursive(mut self, value: bool) -> Self {
+        self.set_recursive(value);
+        self
+    }
+}
+
+impl crate::Client {
+    /// Sends a single operation of a batch.
+    async fn run_operation(
+        &self,
+        operation: &Operation<'_>,
+        options: &BatchOptions,
+    ) -> crate::Result<Outcome> {
+        let entry = match operation {
+            Operation::Delete(Target::File(file)) => self.delete_file(file.clone()).await?.into(),
+            Operation::Delete(Target::Folder(folder)) if options.recursive => {
+                return self
+                    .delete_folder_recursive(folder.clone())
+                    .await
+                    .map(Outcome::Deleted);
+            }
+            Operation::Delete(Target::Folder(folder)) => {
+                self.delete_folder(folder.clone()).await?.into()
+            }
+            Operation::Move {
+                target: Target::File(file),
+                to_folder,
+            } => self
+                .move_file(file.clone(), to_folder.clone())
+                .await?
+                .into(),
+            Operation::Move {
+                target: Target::Folder(folder),
+                to_folder,
+            } => self
+                .move_folder(folder.clone(), to_folder.clone())
+                .await?
+                .into(),
+            Operation::Copy {
+                target: Target::File(file),
+                to_folder,
+            } => self
+                .copy_file(file.clone(), to_folder.clone())
+                .await?
+                .into(),
+            Operation::Copy {
+                target: Target::Folder(folder),
+                to_folder,
+            } => self
+                .copy_folder(folder.clone(), to_folder.clone())
+                .await?
+                .into(),
+            Operation::Rename {
+                target: Target::File(file),
+                name,
+            } => self.rename_file(file.clone(), name.clone()).await?.into(),
+            Operation::Rename {
+                target: Target::Folder(folder),
+                name,
+            } => self
+                .rename_folder(folder.clone(), name.clone())
+                .await?
+                .into(),
+        };
+        Ok(Outcome::Entry(Box::new(entry)))
+    }
+
+    /// Runs many operations, a failure not stopping the others.
+    ///
+    /// # Arguments
+    ///
+    /// * `operations` - The operations to run.
+    /// * `options` - The concurrency, dry run and recursive deletion options.
+    ///
+    /// # Returns
+    ///
+    /// A [`BatchReport`] with the status of every operation, in the order given.
+    pub async fn run_batch<'a>(
+        &self,
+        operations: impl IntoIterator<Item = Operation<'a>>,
+        options: &BatchOptions,
+    ) -> BatchReport<'a> {
+        if options.dry_run {
+            let items = operations
+                .into_iter()
+                .map(|operation| BatchItem {
+                    operation,
+                    status: Status::Planned,
+                })
+                .collect();
+            return BatchReport { items };
+        }
+        let items = futures_util::stream::iter(operations)
+            .map(|operation| async move {
+                let status = match self.run_operation(&operation, options).await {
+                    Ok(outcome) => Status::Succeeded(outcome),
+                    Err(error) => {
+                        tracing::warn!(?operation, "batch operation failed: {error}");
+                        Status::Failed(error)
+                    }
+                };
+                BatchItem { operation, status }
+            })
+            .buffered(options.concurrency)
+            .collect()
+            .await;
+        BatchReport { items }
+    }
+
+    /// Deletes many files and folders.
+    ///
+    /// Folders must be empty unless [`BatchOptions::with_recursive`] is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pcloud::batch::{BatchOptions, Target};
+    ///
+    /// # async fn example(client: &pcloud::Client) {
+    /// let report = client
+    ///     .delete_many([Target::file(1), Target::file(2)], &BatchOptions::default())
+    ///     .await;
+    /// println!("all deleted: {}", report.is_success());
+    /// # }
+    /// ```
+    pub async fn delete_many<'a>(
+        &self,
+        targets: impl IntoIterator<Item = Target<'a>>,
+        options: &BatchOptions,
+    ) -> BatchReport<'a> {
+        self.run_batch(targets.into_iter().map(Operation::Delete), options)
+            .await
+    }
+
+    /// Moves many files and folders into the same folder.
+    pub async fn move_many<'a>(
+        &self,
+        targets: impl IntoIterator<Item = Target<'a>>,
+        to_folder: impl Into<FolderIdentifier<'a>>,
+        options: &BatchOptions,
+    ) -> BatchReport<'a> {
+        let to_folder = to_folder.into();
+        let operations = targets.into_iter().map(|target| Operation::Move {
+            target,
+            to_folder: to_folder.clone(),
+        });
+        self.run_batch(operations, options).await
+    }
+
+    /// Copies many files and folders into the same folder.
+    pub async fn copy_many<'a>(
+        &self,
+        targets: impl IntoIterator<Item = Target<'a>>,
+        to_folder: impl Into<FolderIdentifier<'a>>,
+        options: &BatchOptions,
+    ) -> BatchReport<'a> {
+        let to_folder = to_folder.into();
+        let operations = targets.into_iter().map(|target| Operation::Copy {
+            target,
+            to_folder: to_folder.clone(),
+        });
+        self.run_batch(operations, options).await
+    }
+
+    /// Renames many files and folders, each with its new name.
+    pub async fn rename_many<'a, N>(
+        &self,
+        renames: impl IntoIterator<Item = (Target<'a>, N)>,
+        options: &BatchOptions,
+    ) -> BatchReport<'a>
+    where
+        N: Into<Cow<'a, str>>,
+    {
+        let operations = renames.into_iter().map(|(target, name)| Operation::Rename {
+            target,
+            name: name.into(),
+        });
+        self.run_batch(operations, options).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BatchOptions, Operation, Outcome, Target};
+    use crate::entry::Entry;
+    use crate::testing::{file, folder, response};
+    use crate::{Client, Credentials};
+    use mockito::Matcher;
+
+    #[tokio::test]
+    async fn should_report_each_operation() {
+        let mut server = mockito::Server::new_async().await;
+        let deleted = server
+            .mock("GET", "/deletefile")
+            .match_query(Matcher::UrlEncoded("fileid".into(), "1".into()))
+            .with_status(200)
+            .with_body(response(file(1, "file.txt")))
+            .create_async()
+            .await;
+        let missing = server
+            .mock("GET", "/deletefile")
+            .match_query(Matcher::UrlEncoded("fileid".into(), "2".into()))
+            .with_status(200)
+            .with_body(r#"{"result": 2009, "error": "File not found."}"#)
+            .create_async()
+            .await;
+        let client = Client::new(server.url(), Credentials::access_token("access-token")).unwrap();
+        let report = client
+            .delete_many(
+                [Target::file(1), Target::file(2)],
+                &BatchOptions::default().with_concurrency(2),
+            )
+            .await;
+        assert_eq!(report.items.len(), 2);
+        assert!(!report.is_success());
+        assert_eq!(report.succeeded().count(), 1);
+        let failed: Vec<_> = report.failed().collect();
+        assert!(matches!(
+            failed[0].0,
+            Operation::Delete(Target::File(crate::file::FileIdentifier::FileId(2)))
+        ));
+        assert!(failed[0].1.is_not_found());
+        deleted.assert_async().await;
+        missing.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn should_copy_files_and_folders() {
+        let mut server = mockito::Server::new_async().await;
+        let file = server
+            .mock("GET", "/copyfile")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("fileid".into(), "1".into()),
+                Matcher::UrlEncoded("tofolderid".into(), "3".into()),
+            ]))
+            .with_status(200)
+            .with_body(response(file(1, "file.txt")))
+            .create_async()
+            .await;
+        let folder = server
+            .mock("GET", "/copyfolder")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("folderid".into(), "2".into()),
+                Matcher::UrlEncoded("tofolderid".into(), "3".into()),
+            ]))
+            .with_status(200)
+            .with_body(response(folder(4, "copy", None)))
+            .create_async()
+            .await;
+        let client = Client::new(server.url(), Credentials::access_token("access-token")).unwrap();
+        let report = client
+            .copy_many(
+                [Target::file(1), Target::folder(2)],
+                3,
+                &BatchOptions::default(),
+            )
+            .await;
+        assert!(report.is_success());
+        let outcomes: Vec<_> = report.succeeded().map(|(_, outcome)| outcome).collect();
+        assert!(
+            matches!(outcomes[1], Outcome::Entry(entry) if matches!(**entry, Entry::Folder(_)))
+        );
+        file.assert_async().await;
+        folder.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn should_only_plan_in_dry_run() {
+        let mut server = mockito::Server::new_async().await;
+        let m = server
+            .mock("GET", Matcher::Any)
+            .expect(0)
+            .create_async()
+            .await;
+        let client = Client::new(server.url(), Credentials::access_token("access-token")).unwrap();
+        let report = client
+            .rename_many(
+                [(Target::file(1), "a.txt"), (Target::folder(2), "b")],
+                &BatchOptions::default().with_dry_run(true),
+            )
+            .await;
+        assert_eq!(report.planned().count(), 2);
+        assert!(report.is_success());
+        m.assert_async().await;
+    }
+}
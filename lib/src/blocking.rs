@@ -0,0 +1,463 @@
+//! A synchronous client for the pCloud API.
+//!
+//! [`Client`] mirrors the methods of the asynchronous [`crate::Client`] and runs them
+//! on a runtime it owns, so callers don't have to manage one.
+//!
+//! ```rust,no_run
+//! use pcloud::blocking::Client;
+//! use pcloud::Credentials;
+//!
+//! # fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = Client::new(pcloud::EU_REGION, Credentials::from_env())?;
+//! let folder = client.list_folder("/")?;
+//! println!("{} entries", folder.contents.map(|c| c.len()).unwrap_or(0));
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The methods must not be called from within an asynchronous runtime, doing so panics.
+
+use std::borrow::Cow;
+use std::future::Future;
+use std::ops::RangeBounds;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use tokio::io::AsyncWrite;
+
+use crate::file::checksum::FileChecksum;
+use crate::file::copy::CopyFileOptions;
+use crate::file::upload::MultiFileUpload;
+use crate::file::{File, FileIdentifier};
+use crate::folder::delete::RecursivePayload;
+use crate::folder::list::ListFolderOptions;
+use crate::folder::upload::{UploadDirOptions, UploadDirReport};
+use crate::folder::{Folder, FolderIdentifier};
+use crate::general::getdigest::Digest;
+use crate::general::userinfo::UserInfo;
+use crate::stream::audio::GetAudioLinkParams;
+use crate::stream::file::GetFileLinkParams;
+use crate::stream::video::GetVideoLinkParams;
+use crate::stream::StreamingLinkList;
+
+/// Adapts a synchronous writer to the writer expected by the downloads.
+///
+/// Writing blocks the current thread, which is what the blocking client does anyway.
+struct SyncWriter<'a, W: ?Sized>(&'a mut W);
+
+impl<W: std::io::Write + ?Sized> AsyncWrite for SyncWriter<'_, W> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Poll::Ready(self.0.write(buf))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Poll::Ready(self.0.flush())
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.poll_flush(cx)
+    }
+}
+
+/// Synchronous HTTP client used to interact with the pCloud API.
+///
+/// Cloning the client is cheap and the clones share the same runtime and connection pool.
+#[derive(Clone, Debug)]
+pub struct Client {
+    inner: crate::Client,
+    runtime: Arc<tokio::runtime::Runtime>,
+}
+
+impl Client {
+    /// Creates a new `Client` with the specified base URL and credentials.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`crate::builder::Error`] if the HTTP client or the runtime fails to build.
+    pub fn new(
+        base_url: impl Into<Cow<'static, str>>,
+        credentials: crate::Credentials,
+    ) -> Result<Self, crate::builder::Error> {
+        Self::from_async(crate::Client::new(base_url, credentials)?)
+    }
+
+    /// Wraps an asynchronous client, keeping its configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::builder::Error::Runtime`] if the runtime fails to build.
+    pub fn from_async(inner: crate::Client) -> Result<Self, crate::builder::Error> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(crate::builder::Error::Runtime)?;
+        Ok(Self {
+            inner,
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    /// Returns the asynchronous client used under the hood.
+    pub fn as_async(&self) -> &crate::Client {
+        &self.inner
+    }
+
+    /// Runs a future of the asynchronous client to completion.
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+}
+
+impl Client {
+    /// Blocking version of [`crate::Client::user_info`].
+    pub fn user_info(&self) -> crate::Result<UserInfo> {
+        self.block_on(self.inner.user_info())
+    }
+
+    /// Blocking version of [`crate::Client::get_digest`].
+    pub fn get_digest(&self) -> crate::Result<Digest> {
+        self.block_on(self.inner.get_digest())
+    }
+
+    /// Blocking version of [`crate::Client::get_token`].
+    pub fn get_token(&self) -> crate::Result<String> {
+        self.block_on(self.inner.get_token())
+    }
+}
+
+impl Client {
+    /// Blocking version of [`crate::Client::list_folder`].
+    pub fn list_folder<'a>(
+        &self,
+        identifier: impl Into<FolderIdentifier<'a>>,
+    ) -> crate::Result<Folder> {
+        self.block_on(self.inner.list_folder(identifier))
+    }
+
+    /// Blocking version of [`crate::Client::list_folder_with_options`].
+    pub fn list_folder_with_options<'a>(
+        &self,
+        identifier: impl Into<FolderIdentifier<'a>>,
+        options: ListFolderOptions,
+    ) -> crate::Result<Folder> {
+        self.block_on(self.inner.list_folder_with_options(identifier, options))
+    }
+
+    /// Blocking version of [`crate::Client::create_folder`].
+    pub fn create_folder<'a>(
+        &self,
+        parent: impl Into<FolderIdentifier<'a>>,
+        name: impl Into<Cow<'a, str>>,
+    ) -> crate::Result<Folder> {
+        self.block_on(self.inner.create_folder(parent, name))
+    }
+
+    /// Blocking version of [`crate::Client::create_folder_if_not_exists`].
+    pub fn create_folder_if_not_exists<'a>(
+        &self,
+        parent: impl Into<FolderIdentifier<'a>>,
+        name: impl Into<Cow<'a, str>>,
+    ) -> crate::Result<Folder> {
+        self.block_on(self.inner.create_folder_if_not_exists(parent, name))
+    }
+
+    /// Blocking version of [`crate::Client::create_folder_all`].
+    pub fn create_folder_all(&self, path: &str) -> crate::Result<Folder> {
+        self.block_on(self.inner.create_folder_all(path))
+    }
+
+    /// Blocking version of [`crate::Client::rename_folder`].
+    pub fn rename_folder<'a>(
+        &self,
+        identifier: impl Into<FolderIdentifier<'a>>,
+        name: impl Into<Cow<'a, str>>,
+    ) -> crate::Result<Folder> {
+        self.block_on(self.inner.rename_folder(identifier, name))
+    }
+
+    /// Blocking version of [`crate::Client::move_folder`].
+    pub fn move_folder<'a, 'b>(
+        &self,
+        folder: impl Into<FolderIdentifier<'a>>,
+        to_folder: impl Into<FolderIdentifier<'b>>,
+    ) -> crate::Result<Folder> {
+        self.block_on(self.inner.move_folder(folder, to_folder))
+    }
+
+    /// Blocking version of [`crate::Client::delete_folder`].
+    pub fn delete_folder<'a>(
+        &self,
+        identifier: impl Into<FolderIdentifier<'a>>,
+    ) -> crate::Result<Folder> {
+        self.block_on(self.inner.delete_folder(identifier))
+    }
+
+    /// Blocking version of [`crate::Client::delete_folder_recursive`].
+    pub fn delete_folder_recursive<'a>(
+        &self,
+        identifier: impl Into<FolderIdentifier<'a>>,
+    ) -> crate::Result<RecursivePayload> {
+        self.block_on(self.inner.delete_folder_recursive(identifier))
+    }
+
+    /// Blocking version of [`crate::Client::upload_dir`].
+    pub fn upload_dir<'a>(
+        &self,
+        local_path: impl AsRef<std::path::Path>,
+        parent: impl Into<FolderIdentifier<'a>>,
+        options: UploadDirOptions,
+    ) -> crate::Result<UploadDirReport> {
+        self.block_on(self.inner.upload_dir(local_path, parent, options))
+    }
+}
+
+impl Client {
+    /// Blocking version of [`crate::Client::get_file_checksum`].
+    pub fn get_file_checksum<'a>(
+        &self,
+        identifier: impl Into<FileIdentifier<'a>>,
+    ) -> crate::Result<FileChecksum> {
+        self.block_on(self.inner.get_file_checksum(identifier))
+    }
+
+    /// Blocking version of [`crate::Client::rename_file`].
+    pub fn rename_file<'a>(
+        &self,
+        identifier: impl Into<FileIdentifier<'a>>,
+        name: impl Into<Cow<'a, str>>,
+    ) -> crate::Result<File> {
+        self.block_on(self.inner.rename_file(identifier, name))
+    }
+
+    /// Blocking version of [`crate::Client::move_file`].
+    pub fn move_file<'a, 'b>(
+        &self,
+        file: impl Into<FileIdentifier<'a>>,
+        to_folder: impl Into<FolderIdentifier<'b>>,
+    ) -> crate::Result<File> {
+        self.block_on(self.inner.move_file(file, to_folder))
+    }
+
+    /// Blocking version of [`crate::Client::copy_file`].
+    pub fn copy_file<'a, 'b>(
+        &self,
+        file: impl Into<FileIdentifier<'a>>,
+        to_folder: impl Into<FolderIdentifier<'b>>,
+    ) -> crate::Result<File> {
+        self.block_on(self.inner.copy_file(file, to_folder))
+    }
+
+    /// Blocking version of [`crate::Client::copy_file_with_options`].
+    pub fn copy_file_with_options<'a, 'b>(
+        &self,
+        file: impl Into<FileIdentifier<'a>>,
+        to_folder: impl Into<FolderIdentifier<'b>>,
+        options: CopyFileOptions<'_>,
+    ) -> crate::Result<File> {
+        self.block_on(self.inner.copy_file_with_options(file, to_folder, options))
+    }
+
+    /// Blocking version of [`crate::Client::delete_file`].
+    pub fn delete_file<'a>(
+        &self,
+        identifier: impl Into<FileIdentifier<'a>>,
+    ) -> crate::Result<File> {
+        self.block_on(self.inner.delete_file(identifier))
+    }
+
+    /// Blocking version of [`crate::Client::upload_files`].
+    ///
+    /// Stream entries are polled on the runtime of the client.
+    pub fn upload_files<'a>(
+        &self,
+        parent: impl Into<FolderIdentifier<'a>>,
+        files: MultiFileUpload,
+    ) -> crate::Result<Vec<File>> {
+        self.block_on(self.inner.upload_files(parent, files))
+    }
+
+    /// Blocking version of [`crate::Client::upload_files_verified`].
+    pub fn upload_files_verified<'a>(
+        &self,
+        parent: impl Into<FolderIdentifier<'a>>,
+        files: MultiFileUpload,
+    ) -> crate::Result<Vec<File>> {
+        self.block_on(self.inner.upload_files_verified(parent, files))
+    }
+
+    /// Blocking version of [`crate::Client::download`], writing in a synchronous writer.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # fn example(client: &pcloud::blocking::Client) -> Result<(), pcloud::Error> {
+    /// let mut output = std::fs::File::create("report.pdf").unwrap();
+    /// client.download("/documents/report.pdf", &mut output)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn download<'a, W>(
+        &self,
+        identifier: impl Into<FileIdentifier<'a>>,
+        writer: &mut W,
+    ) -> crate::Result<u64>
+    where
+        W: std::io::Write + ?Sized,
+    {
+        let mut writer = SyncWriter(writer);
+        self.block_on(self.inner.download(identifier, &mut writer))
+    }
+
+    /// Blocking version of [`crate::Client::download_range`], writing in a synchronous writer.
+    pub fn download_range<'a, W>(
+        &self,
+        identifier: impl Into<FileIdentifier<'a>>,
+        range: impl RangeBounds<u64>,
+        writer: &mut W,
+    ) -> crate::Result<u64>
+    where
+        W: std::io::Write + ?Sized,
+    {
+        let mut writer = SyncWriter(writer);
+        self.block_on(self.inner.download_range(identifier, range, &mut writer))
+    }
+
+    /// Blocking version of [`crate::Client::download_verified`], writing in a synchronous writer.
+    pub fn download_verified<'a, W>(
+        &self,
+        identifier: impl Into<FileIdentifier<'a>>,
+        writer: &mut W,
+    ) -> crate::Result<u64>
+    where
+        W: std::io::Write + ?Sized,
+    {
+        let mut writer = SyncWriter(writer);
+        self.block_on(self.inner.download_verified(identifier, &mut writer))
+    }
+}
+
+impl Client {
+    /// Blocking version of [`crate::Client::get_file_link`].
+    pub fn get_file_link<'a>(
+        &self,
+        identifier: impl Into<FileIdentifier<'a>>,
+    ) -> crate::Result<StreamingLinkList> {
+        self.block_on(self.inner.get_file_link(identifier))
+    }
+
+    /// Blocking version of [`crate::Client::get_file_link_with_params`].
+    pub fn get_file_link_with_params<'a>(
+        &self,
+        identifier: impl Into<FileIdentifier<'a>>,
+        params: GetFileLinkParams<'_>,
+    ) -> crate::Result<StreamingLinkList> {
+        self.block_on(self.inner.get_file_link_with_params(identifier, params))
+    }
+
+    /// Blocking version of [`crate::Client::get_audio_link`].
+    pub fn get_audio_link<'a>(
+        &self,
+        identifier: impl Into<FileIdentifier<'a>>,
+    ) -> crate::Result<StreamingLinkList> {
+        self.block_on(self.inner.get_audio_link(identifier))
+    }
+
+    /// Blocking version of [`crate::Client::get_audio_link_with_params`].
+    pub fn get_audio_link_with_params<'a>(
+        &self,
+        identifier: impl Into<FileIdentifier<'a>>,
+        params: GetAudioLinkParams,
+    ) -> crate::Result<StreamingLinkList> {
+        self.block_on(self.inner.get_audio_link_with_params(identifier, params))
+    }
+
+    /// Blocking version of [`crate::Client::get_video_link`].
+    pub fn get_video_link<'a>(
+        &self,
+        identifier: impl Into<FileIdentifier<'a>>,
+    ) -> crate::Result<StreamingLinkList> {
+        self.block_on(self.inner.get_video_link(identifier))
+    }
+
+    /// Blocking version of [`crate::Client::get_video_link_with_params`].
+    pub fn get_video_link_with_params<'a>(
+        &self,
+        identifier: impl Into<FileIdentifier<'a>>,
+        params: GetVideoLinkParams<'_>,
+    ) -> crate::Result<StreamingLinkList> {
+        self.block_on(self.inner.get_video_link_with_params(identifier, params))
+    }
+}
+
+impl crate::builder::ClientBuilder {
+    /// Builds a blocking [`Client`] with the configured options.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::builder::Error::Reqwest`] if the HTTP client could not be built.
+    /// Returns [`crate::builder::Error::Runtime`] if the runtime could not be built.
+    pub fn build_blocking(self) -> Result<Client, crate::builder::Error> {
+        Client::from_async(self.build()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Client;
+    use crate::Credentials;
+    use mockito::Matcher;
+
+    #[test]
+    fn should_list_folder_without_runtime() {
+        let mut server = mockito::Server::new();
+        let m = server
+            .mock("GET", "/listfolder")
+            .match_query(Matcher::UrlEncoded("folderid".into(), "0".into()))
+            .with_status(200)
+            .with_body(
+                r#"{
+    "result": 0,
+    "metadata": {
+        "name": "/",
+        "created": "Fri, 23 Jul 2021 19:39:09 +0000",
+        "modified": "Fri, 23 Jul 2021 19:39:09 +0000",
+        "ismine": true,
+        "thumb": false,
+        "id": "d0",
+        "isshared": false,
+        "icon": "folder",
+        "isfolder": true,
+        "folderid": 0,
+        "contents": []
+    }
+}"#,
+            )
+            .create();
+        let client = Client::new(server.url(), Credentials::anonymous()).unwrap();
+        let folder = client.list_folder(0).unwrap();
+        assert_eq!(folder.folder_id, 0);
+        m.assert();
+    }
+
+    #[test]
+    fn should_return_errors() {
+        let mut server = mockito::Server::new();
+        server
+            .mock("GET", "/checksumfile")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_body(r#"{ "result": 2009, "error": "File not found." }"#)
+            .create();
+        let client = Client::new(server.url(), Credentials::anonymous()).unwrap();
+        let error = client.get_file_checksum(42).unwrap_err();
+        assert_eq!(
+            error.api_code(),
+            Some(crate::error::ApiErrorCode::FileNotFound)
+        );
+    }
+}
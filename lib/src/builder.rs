@@ -6,6 +6,10 @@ pub enum Error {
     /// Returned when the underlying HTTP client could not be built.
     #[error("unable to build reqwest client")]
     Reqwest(#[from] reqwest::Error),
+    /// Returned when the runtime of the blocking client could not be built.
+    #[cfg(feature = "blocking")]
+    #[error("unable to build runtime")]
+    Runtime(#[source] std::io::Error),
 }
 
 /// Builder for constructing a [`Client`](crate::Client) with custom configuration.
@@ -17,6 +21,14 @@ pub struct ClientBuilder {
     base_url: Cow<'static, str>,
     client_builder: Option<reqwest::ClientBuilder>,
     credentials: crate::Credentials,
+    retry_policy: Option<crate::retry::RetryPolicy>,
+    max_concurrent_requests: Option<usize>,
+    requests_per_second: Option<u32>,
+    metadata_cache_ttl: Option<std::time::Duration>,
+    login: Option<crate::session::Session>,
+    hooks: crate::hook::Hooks,
+    credentials_mode: crate::CredentialsMode,
+    time_format: crate::TimeFormat,
 }
 
 impl Default for ClientBuilder {
@@ -25,11 +37,23 @@ impl Default for ClientBuilder {
     /// - Base URL is set to the EU region.
     /// - No credentials are set.
     /// - No custom `reqwest::ClientBuilder` is used.
+    /// - Failed requests are not retried.
+    /// - The number of requests is not limited.
+    /// - The metadata are not cached.
+    /// - The credentials are kept out of the URLs.
     fn default() -> Self {
         Self {
             base_url: Cow::Borrowed(crate::EU_REGION),
             client_builder: None,
             credentials: crate::Credentials::Anonymous,
+            retry_policy: None,
+            max_concurrent_requests: None,
+            requests_per_second: None,
+            metadata_cache_ttl: None,
+            login: None,
+            hooks: Default::default(),
+            credentials_mode: crate::CredentialsMode::Hidden,
+            time_format: crate::TimeFormat::Rfc2822,
         }
     }
 }
@@ -52,6 +76,14 @@ impl ClientBuilder {
             base_url,
             client_builder: None,
             credentials,
+            retry_policy: None,
+            max_concurrent_requests: None,
+            requests_per_second: None,
+            metadata_cache_ttl: None,
+            login: None,
+            hooks: Default::default(),
+            credentials_mode: crate::CredentialsMode::Hidden,
+            time_format: crate::TimeFormat::Rfc2822,
         }
     }
 }
@@ -101,6 +133,112 @@ impl ClientBuilder {
         self
     }
 
+    /// Sets the policy used to retry requests failing with a transient error.
+    pub fn set_retry_policy(&mut self, retry_policy: crate::retry::RetryPolicy) {
+        self.retry_policy = Some(retry_policy);
+    }
+
+    /// Sets the retry policy and returns the modified builder.
+    pub fn with_retry_policy(mut self, retry_policy: crate::retry::RetryPolicy) -> Self {
+        self.set_retry_policy(retry_policy);
+        self
+    }
+
+    /// Limits the number of requests being sent at the same time by the client and its clones.
+    pub fn set_max_concurrent_requests(&mut self, value: usize) {
+        self.max_concurrent_requests = Some(value);
+    }
+
+    /// Limits the number of concurrent requests and returns the modified builder.
+    pub fn with_max_concurrent_requests(mut self, value: usize) -> Self {
+        self.set_max_concurrent_requests(value);
+        self
+    }
+
+    /// Limits the number of requests sent per second by the client and its clones.
+    ///
+    /// Short bursts up to that number of requests are allowed.
+    pub fn set_requests_per_second(&mut self, value: u32) {
+        self.requests_per_second = Some(value);
+    }
+
+    /// Limits the number of requests per second and returns the modified builder.
+    pub fn with_requests_per_second(mut self, value: u32) -> Self {
+        self.set_requests_per_second(value);
+        self
+    }
+
+    /// Enables the cache of the file and folder metadata, entries expiring after `ttl`.
+    ///
+    /// See [`crate::cache`] for what gets cached and invalidated.
+    pub fn set_metadata_cache(&mut self, ttl: std::time::Duration) {
+        self.metadata_cache_ttl = Some(ttl);
+    }
+
+    /// Enables the metadata cache and returns the modified builder.
+    pub fn with_metadata_cache(mut self, ttl: std::time::Duration) -> Self {
+        self.set_metadata_cache(ttl);
+        self
+    }
+
+    /// Logs in automatically with a username and a password.
+    ///
+    /// The password is never sent to the API: it's hashed with a digest and exchanged for an
+    /// authorization token on the first request. The token is reused by the client and its
+    /// clones, and a new one is requested when the API rejects it. The credentials set with
+    /// [`ClientBuilder::set_credentials`] are ignored.
+    pub fn set_login(&mut self, username: impl Into<String>, password: impl Into<String>) {
+        self.login = Some(crate::session::Session::new(
+            username.into(),
+            password.into(),
+        ));
+    }
+
+    /// Logs in automatically with a username and a password and returns the modified builder.
+    pub fn with_login(mut self, username: impl Into<String>, password: impl Into<String>) -> Self {
+        self.set_login(username, password);
+        self
+    }
+
+    /// Sets where the credentials are placed in the requests.
+    ///
+    /// By default, they're kept out of the URLs, see [`crate::CredentialsMode`].
+    pub fn set_credentials_mode(&mut self, mode: crate::CredentialsMode) {
+        self.credentials_mode = mode;
+    }
+
+    /// Sets where the credentials are placed and returns the modified builder.
+    pub fn with_credentials_mode(mut self, mode: crate::CredentialsMode) -> Self {
+        self.set_credentials_mode(mode);
+        self
+    }
+
+    /// Sets the format of the dates requested to the API.
+    ///
+    /// [`crate::TimeFormat::Timestamp`] makes large listings faster to decode.
+    pub fn set_time_format(&mut self, time_format: crate::TimeFormat) {
+        self.time_format = time_format;
+    }
+
+    /// Sets the format of the dates and returns the modified builder.
+    pub fn with_time_format(mut self, time_format: crate::TimeFormat) -> Self {
+        self.set_time_format(time_format);
+        self
+    }
+
+    /// Adds a hook called before every request and after every response.
+    ///
+    /// The hooks are called in the order they were added. See [`crate::hook`].
+    pub fn add_hook(&mut self, hook: impl crate::hook::Hook) {
+        self.hooks.push(std::sync::Arc::new(hook));
+    }
+
+    /// Adds a hook and returns the modified builder.
+    pub fn with_hook(mut self, hook: impl crate::hook::Hook) -> Self {
+        self.add_hook(hook);
+        self
+    }
+
     /// Builds the [`Client`](crate::Client) with the configured options.
     ///
     /// # Errors
@@ -116,6 +254,19 @@ impl ClientBuilder {
             base_url: self.base_url,
             credentials: self.credentials,
             inner: builder.build()?,
+            retry_policy: self.retry_policy,
+            throttle: crate::throttle::Throttle::new(
+                self.max_concurrent_requests,
+                self.requests_per_second,
+            )
+            .map(std::sync::Arc::new),
+            cache: self
+                .metadata_cache_ttl
+                .map(|ttl| std::sync::Arc::new(crate::cache::MetadataCache::new(ttl))),
+            session: self.login.map(std::sync::Arc::new),
+            hooks: self.hooks,
+            credentials_mode: self.credentials_mode,
+            time_format: self.time_format,
         })
     }
 }
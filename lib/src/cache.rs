@@ -0,0 +1,710 @@
+//! In-memory cache of the file and folder metadata returned by the API.
+//!
+//! The cache is enabled with [`ClientBuilder::with_metadata_cache`](crate::builder::ClientBuilder::with_metadata_cache).
+//! Folder listings made with the default options are then served from the cache until
+//! they expire, and the entries touched by the mutating calls of the client (rename, move,
+//! delete, upload...) are invalidated automatically. Changes made by other clients can be
+//! applied with [`MetadataCache::apply_diff`].
+
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use crate::entry::Entry;
+use crate::file::{File, FileIdentifier};
+use crate::folder::{Folder, FolderIdentifier};
+
+/// Kind of change reported by the `diff` endpoint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffEventKind {
+    /// The state should be reloaded from scratch.
+    Reset,
+    CreateFolder,
+    DeleteFolder,
+    ModifyFolder,
+    CreateFile,
+    ModifyFile,
+    DeleteFile,
+    /// Any other event, not affecting the cached metadata (shares, user info...).
+    #[serde(other)]
+    Other,
+}
+
+/// A change reported by the `diff` endpoint.
+#[derive(Clone, Debug, serde::Deserialize)]
+pub struct DiffEvent {
+    /// The kind of change.
+    pub event: DiffEventKind,
+    /// The metadata of the entry after the change.
+    pub metadata: Option<Entry>,
+}
+
+/// Owned version of a file or folder identifier.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum CacheKey {
+    Id(u64),
+    Path(String),
+}
+
+/// Removes the trailing slashes so that `/foo/` and `/foo` share the same entry.
+fn normalize(path: &str) -> String {
+    let trimmed = path.trim_end_matches('/');
+    if trimmed.is_empty() {
+        "/".to_string()
+    } else if trimmed.starts_with('/') {
+        trimmed.to_string()
+    } else {
+        format!("/{trimmed}")
+    }
+}
+
+fn parent_path(path: &str) -> Option<String> {
+    path.rsplit_once('/')
+        .map(|(parent, _)| normalize(parent))
+        .filter(|parent| parent != path)
+}
+
+impl From<&FolderIdentifier<'_>> for CacheKey {
+    fn from(value: &FolderIdentifier<'_>) -> Self {
+        match value {
+            FolderIdentifier::FolderId(id) => Self::Id(*id),
+            FolderIdentifier::Path(path) => Self::Path(normalize(path)),
+        }
+    }
+}
+
+impl From<&FileIdentifier<'_>> for CacheKey {
+    fn from(value: &FileIdentifier<'_>) -> Self {
+        match value {
+            FileIdentifier::FileId(id) => Self::Id(*id),
+            FileIdentifier::Path(path) => Self::Path(normalize(path)),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Cached<T> {
+    value: T,
+    expires_at: Instant,
+}
+
+#[derive(Debug, Default)]
+struct Store {
+    folders: HashMap<u64, Cached<Folder>>,
+    folder_paths: HashMap<String, u64>,
+    files: HashMap<u64, Cached<File>>,
+    file_paths: HashMap<String, u64>,
+    /// The parent of the folders seen in a cached listing, to find it when they change.
+    folder_parents: HashMap<u64, u64>,
+    last_purge: Option<Instant>,
+}
+
+impl Store {
+    fn folder_id(&self, key: &CacheKey) -> Option<u64> {
+        match key {
+            CacheKey::Id(id) => Some(*id),
+            CacheKey::Path(path) => self.folder_paths.get(path).copied(),
+        }
+    }
+
+    fn file_id(&self, key: &CacheKey) -> Option<u64> {
+        match key {
+            CacheKey::Id(id) => Some(*id),
+            CacheKey::Path(path) => self.file_paths.get(path).copied(),
+        }
+    }
+
+    fn purge_expired(&mut self, now: Instant) {
+        self.folders.retain(|_, item| item.expires_at > now);
+        self.files.retain(|_, item| item.expires_at > now);
+        let Self {
+            folders,
+            folder_paths,
+            files,
+            file_paths,
+            folder_parents,
+            ..
+        } = self;
+        folder_paths.retain(|_, id| folders.contains_key(id));
+        file_paths.retain(|_, id| files.contains_key(id));
+        folder_parents.retain(|_, parent| folders.contains_key(parent));
+        self.last_purge = Some(now);
+    }
+
+    fn remove_folder_listing(&mut self, key: &CacheKey) {
+        if let Some(id) = self.folder_id(key) {
+            self.folders.remove(&id);
+        }
+        if let CacheKey::Path(path) = key {
+            self.folder_paths.remove(path);
+        }
+    }
+
+    /// Removes a file and the listings of the folder containing it.
+    fn remove_file(&mut self, key: &CacheKey) {
+        if let Some(file) = self
+            .file_id(key)
+            .and_then(|id| self.files.remove(&id))
+            .map(|item| item.value)
+        {
+            if let Some(parent) = file.base.parent_folder_id {
+                self.remove_folder_listing(&CacheKey::Id(parent));
+            }
+            if let Some(path) = file.base.path.as_deref() {
+                self.file_paths.remove(&normalize(path));
+            }
+        }
+        if let CacheKey::Path(path) = key {
+            self.file_paths.remove(path);
+            if let Some(parent) = parent_path(path) {
+                self.remove_folder_listing(&CacheKey::Path(parent));
+            }
+        }
+    }
+
+    /// Removes the cached listings and files below a folder, their paths changing with it.
+    fn remove_descendants(&mut self, folder_id: u64, listing: Option<Folder>) {
+        let mut pending = vec![(folder_id, listing)];
+        while let Some((id, listing)) = pending.pop() {
+            // the children may be known from the listing, or only from their own entries
+            self.files
+                .retain(|_, item| item.value.base.parent_folder_id != Some(id));
+            let mut children: Vec<u64> = self
+                .folder_parents
+                .iter()
+                .filter(|(_, parent)| **parent == id)
+                .map(|(child, _)| *child)
+                .collect();
+            for entry in listing
+                .into_iter()
+                .flat_map(|f| f.contents.unwrap_or_default())
+            {
+                match entry {
+                    Entry::File(file) => {
+                        self.files.remove(&file.file_id);
+                    }
+                    Entry::Folder(child) => children.push(child.folder_id),
+                }
+            }
+            for child in children {
+                self.folder_parents.remove(&child);
+                let listing = self.folders.remove(&child).map(|item| item.value);
+                pending.push((child, listing));
+            }
+        }
+    }
+
+    /// Removes a folder whose path may have changed, with its parent listing and
+    /// every path and listing below it.
+    fn remove_folder_tree(&mut self, key: &CacheKey) {
+        let id = self.folder_id(key);
+        let folder = id
+            .and_then(|id| self.folders.remove(&id))
+            .map(|item| item.value);
+        // the folder may only be known as an entry of its parent listing
+        let parent = folder
+            .as_ref()
+            .and_then(|f| f.base.parent_folder_id)
+            .or_else(|| id.and_then(|id| self.folder_parents.get(&id).copied()));
+        let listed_path = id.zip(parent).and_then(|(id, parent)| {
+            let listing = self.folders.get(&parent)?;
+            listing
+                .value
+                .contents
+                .iter()
+                .flatten()
+                .filter_map(Entry::as_folder)
+                .find(|child| child.folder_id == id)
+                .and_then(|child| child.base.path.as_deref())
+                .map(normalize)
+        });
+        if let Some(id) = id {
+            self.folder_parents.remove(&id);
+        }
+        if let Some(parent) = parent {
+            self.remove_folder_listing(&CacheKey::Id(parent));
+        }
+        let path = match key {
+            CacheKey::Path(path) => Some(path.clone()),
+            CacheKey::Id(_) => folder
+                .as_ref()
+                .and_then(|f| f.base.path.as_deref())
+                .map(normalize)
+                .or(listed_path),
+        };
+        match path {
+            Some(path) => {
+                if let Some(parent) = parent_path(&path) {
+                    self.remove_folder_listing(&CacheKey::Path(parent));
+                }
+                let prefix = format!("{}/", path.trim_end_matches('/'));
+                let below = |item: &String| item == &path || item.starts_with(&prefix);
+                self.folder_paths.retain(|item, _| !below(item));
+                self.file_paths.retain(|item, _| !below(item));
+            }
+            None => {
+                // without the previous path, the paths below the folder can't be found
+                self.folder_paths.clear();
+                self.file_paths.clear();
+            }
+        }
+        if let Some(id) = id {
+            self.remove_descendants(id, folder);
+        }
+    }
+}
+
+/// Cache of the file and folder metadata, indexed by ID and by path.
+///
+/// Every entry expires after the configured time to live.
+#[derive(Debug)]
+pub struct MetadataCache {
+    ttl: Duration,
+    store: Mutex<Store>,
+}
+
+impl MetadataCache {
+    /// Creates an empty cache whose entries expire after `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            store: Mutex::default(),
+        }
+    }
+
+    fn store(&self) -> MutexGuard<'_, Store> {
+        self.store.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the listing of a folder, if cached and not expired.
+    pub fn folder(&self, identifier: &FolderIdentifier<'_>) -> Option<Folder> {
+        self.get_folder(&identifier.into())
+    }
+
+    /// Returns the metadata of a file, if cached and not expired.
+    ///
+    /// Files are cached when the listing of their parent folder is cached.
+    pub fn file(&self, identifier: &FileIdentifier<'_>) -> Option<File> {
+        let store = self.store();
+        let id = store.file_id(&identifier.into())?;
+        store
+            .files
+            .get(&id)
+            .filter(|item| item.expires_at > Instant::now())
+            .map(|item| item.value.clone())
+    }
+
+    pub(crate) fn get_folder(&self, key: &CacheKey) -> Option<Folder> {
+        let store = self.store();
+        let id = store.folder_id(key)?;
+        store
+            .folders
+            .get(&id)
+            .filter(|item| item.expires_at > Instant::now())
+            .map(|item| item.value.clone())
+    }
+
+    /// Stores the listing of a folder, and the metadata of the files it contains.
+    pub(crate) fn insert_folder(&self, key: &CacheKey, folder: &Folder) {
+        let now = Instant::now();
+        let expires_at = now + self.ttl;
+        let mut store = self.store();
+        if store
+            .last_purge
+            .is_none_or(|last| now.duration_since(last) >= self.ttl)
+        {
+            store.purge_expired(now);
+        }
+        if let CacheKey::Path(path) = key {
+            store.folder_paths.insert(path.clone(), folder.folder_id);
+        }
+        if let Some(path) = folder.base.path.as_deref() {
+            store.folder_paths.insert(normalize(path), folder.folder_id);
+        }
+        if let Some(parent) = folder.base.parent_folder_id {
+            store.folder_parents.insert(folder.folder_id, parent);
+        }
+        for child in folder
+            .contents
+            .iter()
+            .flatten()
+            .filter_map(Entry::as_folder)
+        {
+            store
+                .folder_parents
+                .insert(child.folder_id, folder.folder_id);
+        }
+        for file in folder.contents.iter().flatten().filter_map(Entry::as_file) {
+            if let Some(path) = file.base.path.as_deref() {
+                store.file_paths.insert(normalize(path), file.file_id);
+            }
+            store.files.insert(
+                file.file_id,
+                Cached {
+                    value: file.clone(),
+                    expires_at,
+                },
+            );
+        }
+        store.folders.insert(
+            folder.folder_id,
+            Cached {
+                value: folder.clone(),
+                expires_at,
+            },
+        );
+    }
+
+    /// Removes the listing of a folder, after its content changed.
+    pub fn invalidate_folder(&self, identifier: &FolderIdentifier<'_>) {
+        self.store().remove_folder_listing(&identifier.into());
+    }
+
+    /// Removes a file and the listing of the folder containing it.
+    pub fn invalidate_file(&self, identifier: &FileIdentifier<'_>) {
+        self.store().remove_file(&identifier.into());
+    }
+
+    /// Removes every entry.
+    pub fn clear(&self) {
+        let mut store = self.store();
+        *store = Store::default();
+    }
+
+    /// Applies the changes reported by the `diff` endpoint.
+    pub fn apply_diff<'a, I>(&self, events: I)
+    where
+        I: IntoIterator<Item = &'a DiffEvent>,
+    {
+        let mut store = self.store();
+        for event in events {
+            match (event.event, event.metadata.as_ref()) {
+                (DiffEventKind::Reset, _) => *store = Store::default(),
+                (DiffEventKind::CreateFile, Some(Entry::File(file)))
+                | (DiffEventKind::ModifyFile, Some(Entry::File(file)))
+                | (DiffEventKind::DeleteFile, Some(Entry::File(file))) => {
+                    store.remove_file(&CacheKey::Id(file.file_id));
+                    if let Some(parent) = file.base.parent_folder_id {
+                        store.remove_folder_listing(&CacheKey::Id(parent));
+                    }
+                }
+                (DiffEventKind::CreateFolder, Some(Entry::Folder(folder))) => {
+                    if let Some(parent) = folder.base.parent_folder_id {
+                        store.remove_folder_listing(&CacheKey::Id(parent));
+                    }
+                }
+                (DiffEventKind::ModifyFolder, Some(Entry::Folder(folder)))
+                | (DiffEventKind::DeleteFolder, Some(Entry::Folder(folder))) => {
+                    store.remove_folder_tree(&CacheKey::Id(folder.folder_id));
+                    if let Some(parent) = folder.base.parent_folder_id {
+                        store.remove_folder_listing(&CacheKey::Id(parent));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Called after a file has been changed by the client.
+    ///
+    /// `file` is the metadata returned by the API, used to invalidate its new parent.
+    pub(crate) fn file_changed(&self, key: &CacheKey, file: Option<&File>) {
+        let mut store = self.store();
+        store.remove_file(key);
+        if let Some(file) = file {
+            store.remove_file(&CacheKey::Id(file.file_id));
+            if let Some(parent) = file.base.parent_folder_id {
+                store.remove_folder_listing(&CacheKey::Id(parent));
+            }
+        }
+    }
+
+    /// Called after a folder has been renamed, moved or deleted by the client.
+    ///
+    /// `folder` is the metadata returned by the API, used to invalidate its new parent.
+    pub(crate) fn folder_changed(&self, key: &CacheKey, folder: Option<&Folder>) {
+        let mut store = self.store();
+        store.remove_folder_tree(key);
+        if let Some(folder) = folder {
+            store.remove_folder_listing(&CacheKey::Id(folder.folder_id));
+            if let Some(parent) = folder.base.parent_folder_id {
+                store.remove_folder_listing(&CacheKey::Id(parent));
+            }
+        }
+    }
+
+    /// Called after the content of a folder has been changed by the client.
+    pub(crate) fn content_changed(&self, key: &CacheKey) {
+        self.store().remove_folder_listing(key);
+    }
+}
+
+impl crate::Client {
+    /// Returns the metadata cache, when enabled.
+    pub fn metadata_cache(&self) -> Option<&MetadataCache> {
+        self.cache.as_deref()
+    }
+
+    /// Returns the cache with the key of an identifier, when the cache is enabled.
+    ///
+    /// Must be called before the identifier is consumed by the request.
+    pub(crate) fn cache_key<'i, I>(&self, identifier: &'i I) -> Option<(&MetadataCache, CacheKey)>
+    where
+        CacheKey: From<&'i I>,
+    {
+        self.cache
+            .as_deref()
+            .map(|cache| (cache, CacheKey::from(identifier)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use mockito::Matcher;
+
+    use super::{DiffEvent, MetadataCache};
+    use crate::file::FileIdentifier;
+    use crate::folder::FolderIdentifier;
+    use crate::testing::{file, folder, response, with_fields};
+    use crate::Client;
+
+    /// The listing of `/foo`, holding the file `/foo/bar.txt`.
+    fn listing() -> String {
+        let file = with_fields(
+            file(42, "bar.txt"),
+            serde_json::json!({ "path": "/foo/bar.txt", "parentfolderid": 10, "size": 12 }),
+        );
+        response(with_fields(
+            folder(10, "foo", Some(&file)),
+            serde_json::json!({ "path": "/foo", "parentfolderid": 0 }),
+        ))
+    }
+
+    /// The file `/foo/bar.txt` once renamed.
+    fn renamed_file() -> String {
+        response(with_fields(
+            file(42, "baz.txt"),
+            serde_json::json!({ "parentfolderid": 10, "size": 12 }),
+        ))
+    }
+
+    /// The listing of `/foo`, holding the folder `/foo/sub`.
+    fn parent_listing() -> String {
+        let sub = with_fields(
+            folder(11, "sub", None),
+            serde_json::json!({ "path": "/foo/sub", "parentfolderid": 10 }),
+        );
+        response(with_fields(
+            folder(10, "foo", Some(&sub)),
+            serde_json::json!({ "path": "/foo", "parentfolderid": 0 }),
+        ))
+    }
+
+    /// The metadata of `/foo/sub` once moved to `/other/sub`.
+    fn moved_folder() -> String {
+        with_fields(
+            folder(11, "sub", None),
+            serde_json::json!({ "path": "/other/sub", "parentfolderid": 20 }),
+        )
+    }
+
+    async fn client(server: &mockito::Server) -> Client {
+        Client::builder()
+            .with_base_url(server.url())
+            .with_metadata_cache(Duration::from_secs(60))
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_serve_listing_from_cache() {
+        let mut server = mockito::Server::new_async().await;
+        let m = server
+            .mock("GET", "/listfolder")
+            .match_query(Matcher::UrlEncoded("path".into(), "/foo".into()))
+            .with_status(200)
+            .with_body(listing())
+            .expect(1)
+            .create_async()
+            .await;
+        let client = client(&server).await;
+        client.list_folder("/foo").await.unwrap();
+        let folder = client.list_folder("/foo/").await.unwrap();
+        assert_eq!(folder.folder_id, 10);
+        let cache = client.metadata_cache().unwrap();
+        assert!(cache.folder(&FolderIdentifier::FolderId(10)).is_some());
+        assert!(cache.file(&FileIdentifier::path("/foo/bar.txt")).is_some());
+        m.assert_async().await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_expire_entries() {
+        let mut server = mockito::Server::new_async().await;
+        let m = server
+            .mock("GET", "/listfolder")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_body(listing())
+            .expect(2)
+            .create_async()
+            .await;
+        let client = client(&server).await;
+        client.list_folder(10).await.unwrap();
+        tokio::time::advance(Duration::from_secs(61)).await;
+        client.list_folder(10).await.unwrap();
+        m.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn should_invalidate_after_rename() {
+        let mut server = mockito::Server::new_async().await;
+        let m_list = server
+            .mock("GET", "/listfolder")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_body(listing())
+            .expect(2)
+            .create_async()
+            .await;
+        let m_rename = server
+            .mock("GET", "/renamefile")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_body(renamed_file())
+            .create_async()
+            .await;
+        let client = client(&server).await;
+        client.list_folder("/foo").await.unwrap();
+        client.rename_file(42, "baz.txt").await.unwrap();
+        let cache = client.metadata_cache().unwrap();
+        assert!(cache.file(&FileIdentifier::FileId(42)).is_none());
+        assert!(cache.folder(&FolderIdentifier::FolderId(10)).is_none());
+        client.list_folder("/foo").await.unwrap();
+        m_list.assert_async().await;
+        m_rename.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn should_invalidate_previous_parent_after_move() {
+        let mut server = mockito::Server::new_async().await;
+        let m_list = server
+            .mock("GET", "/listfolder")
+            .match_query(Matcher::UrlEncoded("path".into(), "/foo".into()))
+            .with_status(200)
+            .with_body(parent_listing())
+            .expect(2)
+            .create_async()
+            .await;
+        let m_move = server
+            .mock("GET", "/renamefolder")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("folderid".into(), "11".into()),
+                Matcher::UrlEncoded("tofolderid".into(), "20".into()),
+            ]))
+            .with_status(200)
+            .with_body(response(moved_folder()))
+            .create_async()
+            .await;
+        let client = client(&server).await;
+        client.list_folder("/foo").await.unwrap();
+        client.move_folder(11, 20).await.unwrap();
+        let cache = client.metadata_cache().unwrap();
+        assert!(cache.folder(&FolderIdentifier::FolderId(10)).is_none());
+        client.list_folder("/foo").await.unwrap();
+        m_list.assert_async().await;
+        m_move.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn should_invalidate_descendants_after_move() {
+        let sub = with_fields(
+            folder(
+                11,
+                "sub",
+                Some(&[folder(12, "deep", None), file(13, "a.txt")].join(",")),
+            ),
+            serde_json::json!({ "path": "/foo/sub", "parentfolderid": 10 }),
+        );
+        let deep = with_fields(
+            folder(12, "deep", Some(&file(14, "b.txt"))),
+            serde_json::json!({ "path": "/foo/sub/deep", "parentfolderid": 11 }),
+        );
+        let mut server = mockito::Server::new_async().await;
+        let m_sub = server
+            .mock("GET", "/listfolder")
+            .match_query(Matcher::UrlEncoded("folderid".into(), "11".into()))
+            .with_status(200)
+            .with_body(response(sub))
+            .create_async()
+            .await;
+        let m_deep = server
+            .mock("GET", "/listfolder")
+            .match_query(Matcher::UrlEncoded("folderid".into(), "12".into()))
+            .with_status(200)
+            .with_body(response(deep))
+            .expect(2)
+            .create_async()
+            .await;
+        let m_move = server
+            .mock("GET", "/renamefolder")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_body(response(moved_folder()))
+            .create_async()
+            .await;
+        let client = client(&server).await;
+        client.list_folder(11).await.unwrap();
+        client.list_folder(12).await.unwrap();
+        client.move_folder(11, 20).await.unwrap();
+        let cache = client.metadata_cache().unwrap();
+        assert!(cache.folder(&FolderIdentifier::FolderId(12)).is_none());
+        assert!(cache.file(&FileIdentifier::FileId(13)).is_none());
+        assert!(cache.file(&FileIdentifier::FileId(14)).is_none());
+        // the listing of the child is requested again, with its new path
+        client.list_folder(12).await.unwrap();
+        m_sub.assert_async().await;
+        m_deep.assert_async().await;
+        m_move.assert_async().await;
+    }
+
+    #[test]
+    fn should_invalidate_previous_parent_on_modified_folder() {
+        let cache = MetadataCache::new(Duration::from_secs(60));
+        let folder: crate::folder::FolderResponse =
+            serde_json::from_str(&parent_listing()).unwrap();
+        cache.insert_folder(&super::CacheKey::Path("/foo".into()), &folder.metadata);
+
+        let events: Vec<DiffEvent> = serde_json::from_str(&format!(
+            r#"[{{ "event": "modifyfolder", "metadata": {} }}]"#,
+            moved_folder()
+        ))
+        .unwrap();
+        cache.apply_diff(&events);
+        assert!(cache.folder(&FolderIdentifier::path("/foo")).is_none());
+    }
+
+    #[test]
+    fn should_apply_diff_events() {
+        let cache = MetadataCache::new(Duration::from_secs(60));
+        let folder: crate::folder::FolderResponse = serde_json::from_str(&listing()).unwrap();
+        cache.insert_folder(&super::CacheKey::Path("/foo".into()), &folder.metadata);
+        assert!(cache.folder(&FolderIdentifier::path("/foo")).is_some());
+
+        let deleted = with_fields(
+            file(42, "bar.txt"),
+            serde_json::json!({ "parentfolderid": 10 }),
+        );
+        let events: Vec<DiffEvent> = serde_json::from_str(&format!(
+            r#"[{{ "event": "requestsharein" }}, {{ "event": "deletefile", "metadata": {deleted} }}]"#
+        ))
+        .unwrap();
+        cache.apply_diff(&events);
+        assert!(cache.file(&FileIdentifier::FileId(42)).is_none());
+        assert!(cache.folder(&FolderIdentifier::path("/foo")).is_none());
+    }
+}
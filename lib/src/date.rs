@@ -1,8 +1,11 @@
 // pCloud is using RFC2822
 // Sat, 24 Jul 2021 07:38:41 +0000
+// or, with the timeformat=timestamp parameter, the number of seconds since the epoch
+// 1627111121
 
 use chrono::{DateTime, Utc};
-use serde::{self, Deserialize, Deserializer, Serializer};
+use serde::de::{self, Visitor};
+use serde::{Deserializer, Serializer};
 
 // The signature of a serialize_with function must follow the pattern:
 //
@@ -19,6 +22,42 @@ where
     serializer.serialize_str(&value)
 }
 
+/// Accepts the dates as RFC 2822 strings, or as a number of seconds since the Unix epoch
+/// when requested with `timeformat=timestamp`.
+struct DateVisitor;
+
+impl<'de> Visitor<'de> for DateVisitor {
+    type Value = DateTime<Utc>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("an RFC 2822 date or a unix timestamp")
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+        DateTime::from_timestamp(value, 0)
+            .ok_or_else(|| E::custom(format!("timestamp out of range: {value}")))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+        let value = std::convert::TryFrom::try_from(value).map_err(E::custom)?;
+        self.visit_i64(value)
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Self::Value, E> {
+        // the API sends whole seconds, the fraction is dropped
+        self.visit_i64(value as i64)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        match value.parse::<i64>() {
+            Ok(timestamp) => self.visit_i64(timestamp),
+            Err(_) => DateTime::parse_from_rfc2822(value)
+                .map(|fixed| fixed.into())
+                .map_err(E::custom),
+        }
+    }
+}
+
 // The signature of a deserialize_with function must follow the pattern:
 //
 //    fn deserialize<'de, D>(D) -> Result<T, D::Error>
@@ -30,10 +69,7 @@ pub fn deserialize<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
 where
     D: Deserializer<'de>,
 {
-    let value = String::deserialize(deserializer)?;
-    DateTime::parse_from_rfc2822(&value)
-        .map(|fixed| fixed.into())
-        .map_err(serde::de::Error::custom)
+    deserializer.deserialize_any(DateVisitor)
 }
 
 pub mod optional {
@@ -50,18 +86,68 @@ pub mod optional {
         }
     }
 
+    struct OptionalDateVisitor;
+
+    impl<'de> Visitor<'de> for OptionalDateVisitor {
+        type Value = Option<DateTime<Utc>>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("an optional RFC 2822 date or unix timestamp")
+        }
+
+        fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_some<D: Deserializer<'de>>(
+            self,
+            deserializer: D,
+        ) -> Result<Self::Value, D::Error> {
+            super::deserialize(deserializer).map(Some)
+        }
+    }
+
     pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
     where
         D: Deserializer<'de>,
     {
-        let value = Option::<String>::deserialize(deserializer)?;
-        if let Some(value) = value {
-            DateTime::parse_from_rfc2822(&value)
-                .map(|fixed| fixed.into())
-                .map(Some)
-                .map_err(serde::de::Error::custom)
-        } else {
-            Ok(None)
+        deserializer.deserialize_option(OptionalDateVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Utc};
+
+    #[derive(Debug, serde::Deserialize)]
+    struct Dated {
+        #[serde(with = "super")]
+        date: DateTime<Utc>,
+        #[serde(default, with = "super::optional")]
+        other: Option<DateTime<Utc>>,
+    }
+
+    #[test]
+    fn should_accept_timestamps_and_rfc2822() {
+        let expected = DateTime::from_timestamp(1627111121, 0).unwrap();
+        for input in [
+            r#"{"date": "Sat, 24 Jul 2021 07:18:41 +0000"}"#,
+            r#"{"date": 1627111121}"#,
+            r#"{"date": "1627111121"}"#,
+        ] {
+            let value: Dated = serde_json::from_str(input).unwrap();
+            assert_eq!(value.date, expected);
+            assert_eq!(value.other, None);
         }
+        let value: Dated =
+            serde_json::from_str(r#"{"date": 1627111121, "other": 1627111121}"#).unwrap();
+        assert_eq!(value.other, Some(expected));
+        let value: Dated = serde_json::from_str(r#"{"date": 1627111121, "other": null}"#).unwrap();
+        assert_eq!(value.other, None);
+        assert!(serde_json::from_str::<Dated>(r#"{"date": "yesterday"}"#).is_err());
     }
 }
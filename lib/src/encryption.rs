@@ -0,0 +1,869 @@
+//! Client-side encryption of the files stored on pCloud.
+//!
+//! The content is encrypted before being uploaded and decrypted while being downloaded, pCloud
+//! only ever sees the encrypted bytes. The key is provided by the user and never leaves the client,
+//! losing it means losing the files.
+//!
+//! # Format
+//!
+//! An encrypted file starts with a header made of the `PCE1` magic and a random salt of 32
+//! bytes, from which a key unique to the file is derived with HKDF-SHA256. The content follows,
+//! split in chunks of 64 KiB, each sealed with AES-256-GCM. The nonce of a chunk is its index
+//! and a flag set on the last chunk, so that reordering, removing or truncating chunks is
+//! detected when decrypting.
+//!
+//! The names are encrypted deterministically, the same name always giving the same encrypted
+//! name, so that files can still be found by path. Encrypted names are encoded in URL-safe
+//! base64 and are about a third longer than the original, plus 38 characters.
+//!
+//! ```rust,no_run
+//! use futures_util::StreamExt;
+//! use pcloud::encryption::{Encryption, EncryptionKey};
+//! use pcloud::file::upload::MultiFileUpload;
+//!
+//! # async fn example(client: &pcloud::Client) -> Result<(), pcloud::Error> {
+//! let encryption = Encryption::new(EncryptionKey::generate());
+//! let content = futures_util::stream::iter([Ok::<_, std::io::Error>(bytes::Bytes::from("secret"))]);
+//! let files = MultiFileUpload::default()
+//!     .with_encrypted_stream_entry(&encryption, "report.txt", Some(6), content);
+//! let uploaded = client.upload_files("/vault", files).await?;
+//!
+//! let stream = client
+//!     .download_decrypted_stream(uploaded[0].file_id, &encryption)
+//!     .await?;
+//! let mut stream = std::pin::pin!(stream);
+//! while let Some(chunk) = stream.next().await {
+//!     println!("decrypted {} bytes", chunk?.len());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use aws_lc_rs::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use aws_lc_rs::{hkdf, hmac};
+use base64::Engine;
+use bytes::{Bytes, BytesMut};
+use futures_core::Stream;
+
+use crate::file::upload::MultiFileUpload;
+use crate::file::FileIdentifier;
+use crate::stream::StreamingLinkList;
+
+/// Length of the keys, in bytes.
+pub const KEY_LEN: usize = 32;
+
+/// Magic bytes starting every encrypted content, including the version of the format.
+const MAGIC: &[u8; 4] = b"PCE1";
+/// Length of the random salt following the magic bytes.
+const SALT_LEN: usize = 32;
+/// Length of the header of an encrypted content.
+const HEADER_LEN: usize = MAGIC.len() + SALT_LEN;
+/// Size of the plaintext chunks.
+const CHUNK_SIZE: usize = 64 * 1024;
+/// Length of the authentication tag appended to every chunk.
+const TAG_LEN: usize = 16;
+
+const CONTENT_INFO: &[u8] = b"pcloud encryption content key";
+const NAME_KEY_INFO: &[u8] = b"pcloud encryption name key";
+const NAME_NONCE_INFO: &[u8] = b"pcloud encryption name nonce";
+
+/// Errors that can occur when encrypting or decrypting.
+#[derive(Debug, thiserror::Error)]
+pub enum EncryptionError {
+    /// The key doesn't have the expected length or encoding.
+    #[error("invalid encryption key")]
+    InvalidKey,
+    /// The content doesn't start with a supported header.
+    #[error("content not encrypted or encrypted with an unsupported format")]
+    InvalidHeader,
+    /// The content has been altered, or was encrypted with another key.
+    #[error("unable to authenticate encrypted content")]
+    Authentication,
+    /// The content ended before its last chunk.
+    #[error("truncated encrypted content")]
+    Truncated,
+    /// The name isn't an encrypted name, or was encrypted with another key.
+    #[error("invalid encrypted name")]
+    InvalidName,
+}
+
+impl From<EncryptionError> for std::io::Error {
+    fn from(value: EncryptionError) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, value)
+    }
+}
+
+/// A secret key of 32 bytes, used to encrypt the contents and the names.
+#[derive(Clone, PartialEq, Eq)]
+pub struct EncryptionKey([u8; KEY_LEN]);
+
+impl std::fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple(stringify!(EncryptionKey))
+            .field(&crate::hook::REDACTED)
+            .finish()
+    }
+}
+
+impl EncryptionKey {
+    /// Generates a new random key.
+    pub fn generate() -> Self {
+        Self(rand::random())
+    }
+
+    /// Creates a key from its bytes.
+    pub fn from_bytes(bytes: [u8; KEY_LEN]) -> Self {
+        Self(bytes)
+    }
+
+    /// Decodes a key encoded in standard base64, as returned by [`EncryptionKey::to_base64`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EncryptionError::InvalidKey`] if the value isn't a base64 encoded key of 32 bytes.
+    pub fn from_base64(value: &str) -> Result<Self, EncryptionError> {
+        base64::engine::general_purpose::STANDARD
+            .decode(value.trim())
+            .ok()
+            .and_then(|bytes| std::convert::TryFrom::try_from(bytes).ok())
+            .map(Self)
+            .ok_or(EncryptionError::InvalidKey)
+    }
+
+    /// Encodes the key in standard base64, to be stored.
+    pub fn to_base64(&self) -> String {
+        base64::engine::general_purpose::STANDARD.encode(self.0)
+    }
+
+    /// Derives a key of 32 bytes for the given salt and purpose.
+    fn derive(&self, salt: &[u8], info: &[u8]) -> [u8; KEY_LEN] {
+        struct Len;
+
+        impl hkdf::KeyType for Len {
+            fn len(&self) -> usize {
+                KEY_LEN
+            }
+        }
+
+        let mut output = [0; KEY_LEN];
+        hkdf::Salt::new(hkdf::HKDF_SHA256, salt)
+            .extract(&self.0)
+            .expand(&[info], Len)
+            .and_then(|okm| okm.fill(&mut output))
+            .expect("32 bytes is a valid HKDF-SHA256 output length");
+        output
+    }
+}
+
+fn aead_key(bytes: &[u8; KEY_LEN]) -> LessSafeKey {
+    LessSafeKey::new(UnboundKey::new(&AES_256_GCM, bytes).expect("32 bytes is a valid AES-256 key"))
+}
+
+/// Encrypts and decrypts contents and names with a user-supplied key.
+///
+/// Cloning is cheap, the derived keys being shared by the clones.
+#[derive(Clone)]
+pub struct Encryption {
+    key: Arc<EncryptionKey>,
+    name_key: Arc<LessSafeKey>,
+    name_nonce_key: hmac::Key,
+}
+
+impl std::fmt::Debug for Encryption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct(stringify!(Encryption))
+            .field("key", &self.key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Encryption {
+    /// Creates an encryption layer using the given key.
+    pub fn new(key: EncryptionKey) -> Self {
+        let name_key = aead_key(&key.derive(&[], NAME_KEY_INFO));
+        let name_nonce_key = hmac::Key::new(hmac::HMAC_SHA256, &key.derive(&[], NAME_NONCE_INFO));
+        Self {
+            key: Arc::new(key),
+            name_key: Arc::new(name_key),
+            name_nonce_key,
+        }
+    }
+
+    /// Returns the size of the encrypted content, for a content of `length` bytes.
+    pub fn encrypted_len(length: u64) -> u64 {
+        let chunks = length.div_ceil(CHUNK_SIZE as u64).max(1);
+        HEADER_LEN as u64 + length + chunks * TAG_LEN as u64
+    }
+
+    /// Returns the size of the decrypted content, for an encrypted content of `length` bytes,
+    /// or `None` when no valid encrypted content has this size.
+    pub fn decrypted_len(length: u64) -> Option<u64> {
+        let sealed = length.checked_sub(HEADER_LEN as u64)?;
+        let full_chunks = sealed / (CHUNK_SIZE + TAG_LEN) as u64;
+        let remaining = sealed % (CHUNK_SIZE + TAG_LEN) as u64;
+        let plain = match (full_chunks, remaining) {
+            (0, remaining) => remaining.checked_sub(TAG_LEN as u64)?,
+            (full, 0) => full * CHUNK_SIZE as u64,
+            (full, remaining) => {
+                full * CHUNK_SIZE as u64 + remaining.checked_sub(TAG_LEN as u64)?
+            }
+        };
+        (Self::encrypted_len(plain) == length).then_some(plain)
+    }
+
+    /// Encrypts a stream of bytes.
+    ///
+    /// The errors of the inner stream are returned as is, and end the encrypted stream.
+    pub fn encrypt_stream<S, B, E>(&self, stream: S) -> CryptStream<S>
+    where
+        S: Stream<Item = Result<B, E>>,
+        B: AsRef<[u8]>,
+    {
+        let salt: [u8; SALT_LEN] = rand::random();
+        let mut header = BytesMut::with_capacity(HEADER_LEN);
+        header.extend_from_slice(MAGIC);
+        header.extend_from_slice(&salt);
+        CryptStream {
+            inner: Some(Box::pin(stream)),
+            buffer: BytesMut::new(),
+            mode: Mode::Encrypt {
+                header: Some(header.freeze()),
+            },
+            cipher: Some(ChunkCipher::new(&self.key, &salt)),
+        }
+    }
+
+    /// Decrypts a stream of bytes encrypted with [`Encryption::encrypt_stream`].
+    ///
+    /// The decrypted bytes are only returned once authenticated. A content altered, truncated
+    /// or encrypted with another key returns an [`EncryptionError`], converted into the error
+    /// type of the inner stream.
+    pub fn decrypt_stream<S, B, E>(&self, stream: S) -> CryptStream<S>
+    where
+        S: Stream<Item = Result<B, E>>,
+        B: AsRef<[u8]>,
+        E: From<EncryptionError>,
+    {
+        CryptStream {
+            inner: Some(Box::pin(stream)),
+            buffer: BytesMut::new(),
+            mode: Mode::Decrypt {
+                key: self.key.clone(),
+            },
+            cipher: None,
+        }
+    }
+
+    /// Encrypts a file or folder name.
+    ///
+    /// The same name always gives the same encrypted name, which never contains a `/`.
+    pub fn encrypt_name(&self, name: &str) -> String {
+        let tag = hmac::sign(&self.name_nonce_key, name.as_bytes());
+        let mut nonce = [0; NONCE_LEN];
+        nonce.copy_from_slice(&tag.as_ref()[..NONCE_LEN]);
+        let mut output = nonce.to_vec();
+        output.extend_from_slice(name.as_bytes());
+        let mut sealed = output.split_off(NONCE_LEN);
+        self.name_key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::empty(),
+                &mut sealed,
+            )
+            .expect("names are shorter than the AES-GCM limit");
+        output.extend_from_slice(&sealed);
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(output)
+    }
+
+    /// Decrypts a name encrypted with [`Encryption::encrypt_name`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EncryptionError::InvalidName`] if the name isn't an encrypted name, or was
+    /// encrypted with another key.
+    pub fn decrypt_name(&self, name: &str) -> Result<String, EncryptionError> {
+        let mut decoded = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(name)
+            .map_err(|_| EncryptionError::InvalidName)?;
+        if decoded.len() < NONCE_LEN + TAG_LEN {
+            return Err(EncryptionError::InvalidName);
+        }
+        let mut sealed = decoded.split_off(NONCE_LEN);
+        let nonce =
+            Nonce::try_assume_unique_for_key(&decoded).map_err(|_| EncryptionError::InvalidName)?;
+        let plain = self
+            .name_key
+            .open_in_place(nonce, Aad::empty(), &mut sealed)
+            .map_err(|_| EncryptionError::InvalidName)?;
+        String::from_utf8(plain.to_vec()).map_err(|_| EncryptionError::InvalidName)
+    }
+
+    /// Encrypts every name of a path, like `/photos/beach.jpg`, keeping the separators.
+    pub fn encrypt_path(&self, path: &str) -> String {
+        path.split('/')
+            .map(|segment| {
+                if segment.is_empty() {
+                    String::new()
+                } else {
+                    self.encrypt_name(segment)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// Decrypts every name of a path encrypted with [`Encryption::encrypt_path`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EncryptionError::InvalidName`] if one of the names can't be decrypted.
+    pub fn decrypt_path(&self, path: &str) -> Result<String, EncryptionError> {
+        path.split('/')
+            .map(|segment| {
+                if segment.is_empty() {
+                    Ok(String::new())
+                } else {
+                    self.decrypt_name(segment)
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(|segments| segments.join("/"))
+    }
+}
+
+/// Seals or opens the successive chunks of a content.
+struct ChunkCipher {
+    key: LessSafeKey,
+    index: u64,
+}
+
+impl ChunkCipher {
+    fn new(key: &EncryptionKey, salt: &[u8]) -> Self {
+        Self {
+            key: aead_key(&key.derive(salt, CONTENT_INFO)),
+            index: 0,
+        }
+    }
+
+    /// Returns the nonce of the next chunk and moves to the following one.
+    fn next_nonce(&mut self, last: bool) -> Nonce {
+        let mut nonce = [0; NONCE_LEN];
+        nonce[3..11].copy_from_slice(&self.index.to_be_bytes());
+        nonce[11] = u8::from(last);
+        self.index += 1;
+        Nonce::assume_unique_for_key(nonce)
+    }
+
+    fn seal(&mut self, chunk: &[u8], last: bool) -> Bytes {
+        let nonce = self.next_nonce(last);
+        let mut output = Vec::with_capacity(chunk.len() + TAG_LEN);
+        output.extend_from_slice(chunk);
+        self.key
+            .seal_in_place_append_tag(nonce, Aad::empty(), &mut output)
+            .expect("chunks are shorter than the AES-GCM limit");
+        Bytes::from(output)
+    }
+
+    fn open(&mut self, mut chunk: BytesMut, last: bool) -> Result<Bytes, EncryptionError> {
+        let nonce = self.next_nonce(last);
+        let length = self
+            .key
+            .open_in_place(nonce, Aad::empty(), &mut chunk)
+            .map_err(|_| EncryptionError::Authentication)?
+            .len();
+        chunk.truncate(length);
+        Ok(chunk.freeze())
+    }
+}
+
+enum Mode {
+    Encrypt { header: Option<Bytes> },
+    Decrypt { key: Arc<EncryptionKey> },
+}
+
+/// Stream encrypting or decrypting the content of an inner stream, returned by
+/// [`Encryption::encrypt_stream`] and [`Encryption::decrypt_stream`].
+///
+/// The stream ends after the first error, nothing being returned from the remaining bytes.
+pub struct CryptStream<S> {
+    /// The inner stream, `None` once the stream ended or failed.
+    inner: Option<Pin<Box<S>>>,
+    buffer: BytesMut,
+    mode: Mode,
+    cipher: Option<ChunkCipher>,
+}
+
+impl<S> CryptStream<S> {
+    /// Size of the chunks read from the inner stream.
+    fn input_chunk_size(&self) -> usize {
+        match self.mode {
+            Mode::Encrypt { .. } => CHUNK_SIZE,
+            Mode::Decrypt { .. } => CHUNK_SIZE + TAG_LEN,
+        }
+    }
+
+    /// Seals or opens a chunk, `last` being `true` for the last chunk of the content.
+    fn process(&mut self, chunk: BytesMut, last: bool) -> Result<Bytes, EncryptionError> {
+        let cipher = self
+            .cipher
+            .as_mut()
+            .expect("the cipher is created with the header");
+        match self.mode {
+            Mode::Encrypt { .. } => Ok(cipher.seal(&chunk, last)),
+            Mode::Decrypt { .. } => cipher.open(chunk, last),
+        }
+    }
+
+    /// Ends the stream after an error, dropping what's left to process.
+    fn fail<E>(&mut self, error: E) -> Poll<Option<Result<Bytes, E>>> {
+        self.inner = None;
+        self.buffer.clear();
+        self.cipher = None;
+        Poll::Ready(Some(Err(error)))
+    }
+
+    /// Reads the header once enough bytes have been received, when decrypting.
+    fn read_header(&mut self) -> Result<(), EncryptionError> {
+        if let Mode::Decrypt { ref key } = self.mode {
+            if self.cipher.is_none() && self.buffer.len() >= HEADER_LEN {
+                let header = self.buffer.split_to(HEADER_LEN);
+                if !header.starts_with(MAGIC) {
+                    return Err(EncryptionError::InvalidHeader);
+                }
+                self.cipher = Some(ChunkCipher::new(key, &header[MAGIC.len()..]));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<S, B, E> Stream for CryptStream<S>
+where
+    S: Stream<Item = Result<B, E>>,
+    B: AsRef<[u8]>,
+    E: From<EncryptionError>,
+{
+    type Item = Result<Bytes, E>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if let Mode::Encrypt { ref mut header } = this.mode {
+            if let Some(header) = header.take() {
+                return Poll::Ready(Some(Ok(header)));
+            }
+        }
+        let chunk_size = this.input_chunk_size();
+        loop {
+            if let Err(err) = this.read_header() {
+                return this.fail(err.into());
+            }
+            // a full chunk is only processed once more data arrived, to know if it's the last one
+            if this.cipher.is_some() && this.buffer.len() > chunk_size {
+                let chunk = this.buffer.split_to(chunk_size);
+                return match this.process(chunk, false) {
+                    Ok(chunk) => Poll::Ready(Some(Ok(chunk))),
+                    Err(err) => this.fail(err.into()),
+                };
+            }
+            // the stream ended, or failed and its buffer was dropped with the error
+            let Some(inner) = this.inner.as_mut() else {
+                return Poll::Ready(None);
+            };
+            match inner.as_mut().poll_next(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Some(Ok(data))) => this.buffer.extend_from_slice(data.as_ref()),
+                Poll::Ready(Some(Err(err))) => return this.fail(err),
+                Poll::Ready(None) => {
+                    if this.cipher.is_none() {
+                        return this.fail(EncryptionError::Truncated.into());
+                    }
+                    if matches!(this.mode, Mode::Decrypt { .. }) && this.buffer.len() < TAG_LEN {
+                        return this.fail(EncryptionError::Truncated.into());
+                    }
+                    this.inner = None;
+                    let chunk = this.buffer.split();
+                    return match this.process(chunk, true) {
+                        Ok(chunk) => Poll::Ready(Some(Ok(chunk))),
+                        Err(err) => this.fail(err.into()),
+                    };
+                }
+            }
+        }
+    }
+}
+
+impl MultiFileUpload {
+    /// Adds a file stream encrypted with the given [`Encryption`] and returns the updated builder.
+    ///
+    /// This is a chainable version of [`MultiFileUpload::add_encrypted_stream_entry`].
+    pub fn with_encrypted_stream_entry<S, B>(
+        mut self,
+        encryption: &Encryption,
+        filename: &str,
+        length: Option<u64>,
+        stream: S,
+    ) -> Self
+    where
+        S: Stream<Item = std::io::Result<B>> + Send + Sync + 'static,
+        B: AsRef<[u8]> + Send + Sync + 'static,
+    {
+        self.add_encrypted_stream_entry(encryption, filename, length, stream);
+        self
+    }
+
+    /// Adds a file stream encrypted with the given [`Encryption`].
+    ///
+    /// Both the name and the content are encrypted, the length being the one of the
+    /// unencrypted content.
+    ///
+    /// # Arguments
+    ///
+    /// * `encryption` - The [`Encryption`] holding the key.
+    /// * `filename` - The name of the file, before encryption.
+    /// * `length` - The size of the file in bytes, before encryption.
+    /// * `stream` - A stream of bytes representing the file content.
+    pub fn add_encrypted_stream_entry<S, B>(
+        &mut self,
+        encryption: &Encryption,
+        filename: &str,
+        length: Option<u64>,
+        stream: S,
+    ) where
+        S: Stream<Item = std::io::Result<B>> + Send + Sync + 'static,
+        B: AsRef<[u8]> + Send + Sync + 'static,
+    {
+        self.add_stream_entry(
+            encryption.encrypt_name(filename),
+            length.map(Encryption::encrypted_len),
+            encryption.encrypt_stream(stream),
+        );
+    }
+}
+
+impl crate::Client {
+    /// Opens the first working link and returns its content decrypted with the given [`Encryption`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`crate::Error::Download`] if none of the hosts could be reached. The stream
+    /// returns a [`crate::Error::Encryption`] if the content can't be decrypted.
+    pub async fn download_links_decrypted_stream(
+        &self,
+        links: &StreamingLinkList,
+        encryption: &Encryption,
+    ) -> crate::Result<impl Stream<Item = crate::Result<Bytes>> + Send + 'static> {
+        let stream = self.download_links_stream(links).await?;
+        Ok(encryption.decrypt_stream(stream))
+    }
+
+    /// Downloads a file encrypted with the given [`Encryption`] and returns its decrypted content.
+    ///
+    /// The identifier is used as is, a path should be encrypted with [`Encryption::encrypt_path`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`crate::Error`] if the links cannot be resolved, or a
+    /// [`crate::Error::Download`] if none of the hosts could be reached. The stream
+    /// returns a [`crate::Error::Encryption`] if the content can't be decrypted.
+    pub async fn download_decrypted_stream(
+        &self,
+        identifier: impl Into<FileIdentifier<'_>>,
+        encryption: &Encryption,
+    ) -> crate::Result<impl Stream<Item = crate::Result<Bytes>> + Send + 'static> {
+        let links = self.get_file_link(identifier).await?;
+        self.download_links_decrypted_stream(&links, encryption)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::{Encryption, EncryptionError, EncryptionKey, CHUNK_SIZE};
+    use crate::file::upload::MultiFileUpload;
+    use crate::{Client, Credentials};
+    use bytes::Bytes;
+    use futures_util::{StreamExt, TryStreamExt};
+    use mockito::Matcher;
+
+    fn encryption() -> Encryption {
+        Encryption::new(EncryptionKey::from_bytes([7; 32]))
+    }
+
+    async fn encrypt(encryption: &Encryption, content: &[u8]) -> Vec<u8> {
+        // small pieces, to go through the buffering
+        let pieces = content
+            .chunks(1000)
+            .map(|piece| Ok::<_, std::io::Error>(Bytes::copy_from_slice(piece)))
+            .collect::<Vec<_>>();
+        let chunks: Vec<Bytes> = encryption
+            .encrypt_stream(futures_util::stream::iter(pieces))
+            .try_collect()
+            .await
+            .unwrap();
+        chunks.concat()
+    }
+
+    async fn decrypt(encryption: &Encryption, content: Vec<u8>) -> std::io::Result<Vec<u8>> {
+        let pieces = content
+            .chunks(777)
+            .map(|piece| Ok::<_, std::io::Error>(Bytes::copy_from_slice(piece)))
+            .collect::<Vec<_>>();
+        let chunks: Vec<Bytes> = encryption
+            .decrypt_stream(futures_util::stream::iter(pieces))
+            .try_collect()
+            .await?;
+        Ok(chunks.concat())
+    }
+
+    fn kind(err: std::io::Error) -> EncryptionError {
+        *err.into_inner()
+            .unwrap()
+            .downcast::<EncryptionError>()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_encrypt_and_decrypt_contents() {
+        let encryption = encryption();
+        for length in [
+            0,
+            1,
+            CHUNK_SIZE - 1,
+            CHUNK_SIZE,
+            CHUNK_SIZE + 1,
+            3 * CHUNK_SIZE,
+        ] {
+            let content: Vec<u8> = (0..length).map(|i| (i % 251) as u8).collect();
+            let encrypted = encrypt(&encryption, &content).await;
+            assert_eq!(
+                encrypted.len() as u64,
+                Encryption::encrypted_len(length as u64)
+            );
+            assert_eq!(
+                Encryption::decrypted_len(encrypted.len() as u64),
+                Some(length as u64)
+            );
+            assert_eq!(decrypt(&encryption, encrypted).await.unwrap(), content);
+        }
+    }
+
+    #[tokio::test]
+    async fn should_reject_altered_contents() {
+        let encryption = encryption();
+        let content = vec![42; 2 * CHUNK_SIZE + 10];
+        let encrypted = encrypt(&encryption, &content).await;
+
+        let mut altered = encrypted.clone();
+        altered[100] ^= 1;
+        let err = decrypt(&encryption, altered).await.unwrap_err();
+        assert!(matches!(kind(err), EncryptionError::Authentication));
+
+        // dropping the last chunk makes the previous one the last, with the wrong nonce
+        let truncated = encrypted[..encrypted.len() - 26].to_vec();
+        let err = decrypt(&encryption, truncated).await.unwrap_err();
+        assert!(matches!(kind(err), EncryptionError::Authentication));
+
+        let other = Encryption::new(EncryptionKey::from_bytes([8; 32]));
+        let err = decrypt(&other, encrypted).await.unwrap_err();
+        assert!(matches!(kind(err), EncryptionError::Authentication));
+
+        let err = decrypt(&encryption, b"hello world".repeat(10))
+            .await
+            .unwrap_err();
+        assert!(matches!(kind(err), EncryptionError::InvalidHeader));
+    }
+
+    #[test]
+    fn should_encrypt_names_deterministically() {
+        let encryption = encryption();
+        let name = encryption.encrypt_name("beach.jpg");
+        assert_eq!(name, encryption.encrypt_name("beach.jpg"));
+        assert_ne!(name, encryption.encrypt_name("beach.png"));
+        assert!(!name.contains('/'));
+        assert_eq!(encryption.decrypt_name(&name).unwrap(), "beach.jpg");
+        assert!(encryption.decrypt_name("beach.jpg").is_err());
+
+        let path = encryption.encrypt_path("/photos/beach.jpg");
+        assert!(path.starts_with('/'));
+        assert_eq!(path.matches('/').count(), 2);
+        assert_eq!(encryption.decrypt_path(&path).unwrap(), "/photos/beach.jpg");
+
+        let key = EncryptionKey::from_bytes([7; 32]);
+        assert_eq!(EncryptionKey::from_base64(&key.to_base64()).unwrap(), key);
+        assert!(format!("{key:?}").contains("redacted"));
+    }
+
+    #[tokio::test]
+    async fn should_stop_after_the_first_error() {
+        let encryption = encryption();
+        let mut encrypted = encrypt(&encryption, &vec![42; 3 * CHUNK_SIZE]).await;
+        encrypted[100] ^= 1;
+        let items: Vec<std::io::Result<Bytes>> = encryption
+            .decrypt_stream(futures_util::stream::iter([Ok(Bytes::from(encrypted))]))
+            .collect()
+            .await;
+        assert_eq!(items.len(), 1);
+        assert!(matches!(
+            kind(items.into_iter().next().unwrap().unwrap_err()),
+            EncryptionError::Authentication
+        ));
+
+        // the remaining bytes aren't read as another header
+        let garbage = Bytes::from(b"hello world".repeat(10));
+        let items: Vec<std::io::Result<Bytes>> = encryption
+            .decrypt_stream(futures_util::stream::iter([Ok(garbage)]))
+            .collect()
+            .await;
+        assert_eq!(items.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn should_upload_encrypted_files() {
+        let encryption = encryption();
+        let name = encryption.encrypt_name("report.txt");
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let mut server = mockito::Server::new_async().await;
+        let m = server
+            .mock("POST", "/uploadfile")
+            .match_query(Matcher::UrlEncoded("folderid".into(), "0".into()))
+            .match_header("content-length", Matcher::Regex("[0-9]+".to_string()))
+            .match_request({
+                let received = received.clone();
+                move |req| {
+                    *received.lock().unwrap() = req.body().unwrap().clone();
+                    true
+                }
+            })
+            .with_status(200)
+            .with_body(r#"{"result": 0, "metadata": [], "checksums": [], "fileids": []}"#)
+            .create_async()
+            .await;
+        let client = Client::new(server.url(), Credentials::access_token("access-token")).unwrap();
+        let content = futures_util::stream::iter([Ok(Bytes::from("hello world"))]);
+        let files = MultiFileUpload::default().with_encrypted_stream_entry(
+            &encryption,
+            "report.txt",
+            Some(11),
+            content,
+        );
+        client.upload_files(0, files).await.unwrap();
+        m.assert_async().await;
+
+        let body = received.lock().unwrap().clone();
+        let headers = format!("filename=\"{name}\"");
+        let start = find(&body, headers.as_bytes()).expect("encrypted filename");
+        let start = start + find(&body[start..], b"\r\n\r\n").unwrap() + 4;
+        let end = body.windows(4).rposition(|w| w == b"\r\n--").unwrap();
+        let uploaded = body[start..end].to_vec();
+        // the declared length matches the encrypted content
+        assert_eq!(uploaded.len() as u64, Encryption::encrypted_len(11));
+        assert!(find(&body, b"report.txt").is_none());
+        assert!(find(&body, b"hello world").is_none());
+        assert_eq!(
+            decrypt(&encryption, uploaded).await.unwrap(),
+            b"hello world"
+        );
+    }
+
+    fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+        haystack
+            .windows(needle.len())
+            .position(|window| window == needle)
+    }
+
+    /// Serves the mock server over TLS, the streaming links being https URLs.
+    async fn tls_proxy(target: String) -> std::net::SocketAddr {
+        use tokio_rustls::rustls::pki_types::pem::PemObject;
+        use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+
+        let cert =
+            CertificateDer::from_pem_slice(include_bytes!("../tests/fixtures/localhost.crt"))
+                .unwrap();
+        let key = PrivateKeyDer::from_pem_slice(include_bytes!("../tests/fixtures/localhost.key"))
+            .unwrap();
+        let config = tokio_rustls::rustls::ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(vec![cert], key)
+            .unwrap();
+        let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(config));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                let acceptor = acceptor.clone();
+                let target = target.clone();
+                tokio::spawn(async move {
+                    let mut tls = acceptor.accept(socket).await?;
+                    let mut upstream = tokio::net::TcpStream::connect(target).await?;
+                    tokio::io::copy_bidirectional(&mut tls, &mut upstream).await
+                });
+            }
+        });
+        address
+    }
+
+    #[tokio::test]
+    async fn should_download_decrypted_files() {
+        let encryption = encryption();
+        let encrypted = encrypt(&encryption, b"hello world").await;
+        let mut server = mockito::Server::new_async().await;
+        let host = tls_proxy(server.host_with_port()).await;
+        let m_link = server
+            .mock("GET", "/getfilelink")
+            .match_query(Matcher::UrlEncoded("fileid".into(), "42".into()))
+            .with_status(200)
+            .with_body(format!(
+                r#"{{
+    "result": 0,
+    "expires": "Sat, 24 Jul 2100 03:18:31 +0000",
+    "path": "/file.bin",
+    "hosts": ["{host}"]
+}}"#
+            ))
+            .expect(2)
+            .create_async()
+            .await;
+        let m_file = server
+            .mock("GET", "/file.bin")
+            .with_status(200)
+            .with_body(encrypted)
+            .expect(2)
+            .create_async()
+            .await;
+        let client = Client::builder()
+            .with_base_url(server.url())
+            .with_credentials(Credentials::access_token("access-token"))
+            .with_client_builder(
+                reqwest::ClientBuilder::new().tls_danger_accept_invalid_certs(true),
+            )
+            .build()
+            .unwrap();
+
+        let chunks: Vec<Bytes> = client
+            .download_decrypted_stream(42, &encryption)
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(chunks.concat(), b"hello world");
+
+        let links = client.get_file_link(42).await.unwrap();
+        let chunks: Vec<Bytes> = client
+            .download_links_decrypted_stream(&links, &encryption)
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(chunks.concat(), b"hello world");
+        m_link.assert_async().await;
+        m_file.assert_async().await;
+    }
+}
@@ -20,6 +20,9 @@ pub struct EntryBase {
     pub is_shared: bool,
     #[serde(rename = "ismine")]
     pub is_mine: bool,
+    /// Whether the entry is in the trash.
+    #[serde(default, rename = "isdeleted")]
+    pub is_deleted: bool,
 }
 
 /// The representation of what can be returned by the PCloud API, a file or a folder.
@@ -0,0 +1,223 @@
+//! Typed representation of the result codes returned by the pCloud API.
+//!
+//! See <https://docs.pcloud.com/errors/> for the documented list.
+
+/// A result code returned by the pCloud API alongside an error message.
+///
+/// Codes that are not documented (or not known by this library yet) are kept
+/// in [`ApiErrorCode::Other`] so that no information is lost. New variants may be added
+/// when codes get known, matches should keep a wildcard arm.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ApiErrorCode {
+    /// `1000`: log in required.
+    LoginRequired,
+    /// `1001`: no full path or name/folderid provided.
+    MissingPathOrName,
+    /// `1002`: no full path or folderid provided.
+    MissingPathOrFolderId,
+    /// `1004`: no fileid or path provided.
+    MissingFileIdOrPath,
+    /// `1005`: unknown content-type requested.
+    UnknownContentType,
+    /// `1006`: please provide flags.
+    MissingFlags,
+    /// `1007`: invalid or closed file descriptor.
+    InvalidFileDescriptor,
+    /// `1016`: no full topath or toname/tofolderid provided.
+    MissingDestination,
+    /// `1017`: invalid 'folderid' provided.
+    InvalidFolderId,
+    /// `2000`: log in failed.
+    LoginFailed,
+    /// `2001`: invalid file/folder name.
+    InvalidName,
+    /// `2002`: a component of parent directory does not exist.
+    ParentNotFound,
+    /// `2003`: access denied, you do not have permissions to perform this operation.
+    AccessDenied,
+    /// `2004`: file or folder already exists.
+    AlreadyExists,
+    /// `2005`: directory does not exist.
+    DirectoryNotFound,
+    /// `2006`: folder is not empty.
+    FolderNotEmpty,
+    /// `2007`: cannot delete the root folder.
+    CannotDeleteRoot,
+    /// `2008`: user is over quota.
+    OverQuota,
+    /// `2009`: file not found.
+    FileNotFound,
+    /// `2010`: invalid path.
+    InvalidPath,
+    /// `2011`: requested speed limit too low.
+    SpeedLimitTooLow,
+    /// `2012`: invalid OAuth2 code.
+    InvalidCode,
+    /// `2023`: trying to place a shared folder into another shared folder.
+    NestedSharedFolder,
+    /// `2028`: there are active shares or share requests for this folder.
+    ActiveShares,
+    /// `2041`: connection broken.
+    ConnectionBroken,
+    /// `2094`: invalid 'access_token' provided.
+    InvalidAccessToken,
+    /// `4000`: too many login tries from this IP address.
+    TooManyLogins,
+    /// `5000`: internal error, try again later.
+    InternalError,
+    /// `5001`: internal upload error.
+    InternalUploadError,
+    /// `5002`: internal error, no servers available, try again later.
+    NoServersAvailable,
+    /// Any other code not known by this library.
+    Other(u16),
+}
+
+impl From<u16> for ApiErrorCode {
+    fn from(value: u16) -> Self {
+        match value {
+            1000 => Self::LoginRequired,
+            1001 => Self::MissingPathOrName,
+            1002 => Self::MissingPathOrFolderId,
+            1004 => Self::MissingFileIdOrPath,
+            1005 => Self::UnknownContentType,
+            1006 => Self::MissingFlags,
+            1007 => Self::InvalidFileDescriptor,
+            1016 => Self::MissingDestination,
+            1017 => Self::InvalidFolderId,
+            2000 => Self::LoginFailed,
+            2001 => Self::InvalidName,
+            2002 => Self::ParentNotFound,
+            2003 => Self::AccessDenied,
+            2004 => Self::AlreadyExists,
+            2005 => Self::DirectoryNotFound,
+            2006 => Self::FolderNotEmpty,
+            2007 => Self::CannotDeleteRoot,
+            2008 => Self::OverQuota,
+            2009 => Self::FileNotFound,
+            2010 => Self::InvalidPath,
+            2011 => Self::SpeedLimitTooLow,
+            2012 => Self::InvalidCode,
+            2023 => Self::NestedSharedFolder,
+            2028 => Self::ActiveShares,
+            2041 => Self::ConnectionBroken,
+            2094 => Self::InvalidAccessToken,
+            4000 => Self::TooManyLogins,
+            5000 => Self::InternalError,
+            5001 => Self::InternalUploadError,
+            5002 => Self::NoServersAvailable,
+            other => Self::Other(other),
+        }
+    }
+}
+
+impl From<ApiErrorCode> for u16 {
+    fn from(value: ApiErrorCode) -> Self {
+        value.code()
+    }
+}
+
+impl std::fmt::Display for ApiErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.code().fmt(f)
+    }
+}
+
+impl ApiErrorCode {
+    /// Returns the numeric code as sent by the API.
+    pub const fn code(&self) -> u16 {
+        match self {
+            Self::LoginRequired => 1000,
+            Self::MissingPathOrName => 1001,
+            Self::MissingPathOrFolderId => 1002,
+            Self::MissingFileIdOrPath => 1004,
+            Self::UnknownContentType => 1005,
+            Self::MissingFlags => 1006,
+            Self::InvalidFileDescriptor => 1007,
+            Self::MissingDestination => 1016,
+            Self::InvalidFolderId => 1017,
+            Self::LoginFailed => 2000,
+            Self::InvalidName => 2001,
+            Self::ParentNotFound => 2002,
+            Self::AccessDenied => 2003,
+            Self::AlreadyExists => 2004,
+            Self::DirectoryNotFound => 2005,
+            Self::FolderNotEmpty => 2006,
+            Self::CannotDeleteRoot => 2007,
+            Self::OverQuota => 2008,
+            Self::FileNotFound => 2009,
+            Self::InvalidPath => 2010,
+            Self::SpeedLimitTooLow => 2011,
+            Self::InvalidCode => 2012,
+            Self::NestedSharedFolder => 2023,
+            Self::ActiveShares => 2028,
+            Self::ConnectionBroken => 2041,
+            Self::InvalidAccessToken => 2094,
+            Self::TooManyLogins => 4000,
+            Self::InternalError => 5000,
+            Self::InternalUploadError => 5001,
+            Self::NoServersAvailable => 5002,
+            Self::Other(code) => *code,
+        }
+    }
+
+    /// Returns `true` when the requested file, folder or one of its parents doesn't exist.
+    pub const fn is_not_found(&self) -> bool {
+        matches!(
+            self,
+            Self::ParentNotFound | Self::DirectoryNotFound | Self::FileNotFound
+        )
+    }
+
+    /// Returns `true` when the request was rejected because of missing or invalid credentials.
+    pub const fn is_auth_error(&self) -> bool {
+        matches!(
+            self,
+            Self::LoginRequired | Self::LoginFailed | Self::InvalidAccessToken
+        )
+    }
+
+    /// Returns `true` when the API is throttling the caller.
+    pub const fn is_rate_limited(&self) -> bool {
+        matches!(self, Self::TooManyLogins)
+    }
+
+    /// Returns `true` when the error is transient and the same request may succeed later.
+    pub const fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::ConnectionBroken
+                | Self::InternalError
+                | Self::InternalUploadError
+                | Self::NoServersAvailable
+        ) || self.is_rate_limited()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ApiErrorCode;
+
+    #[test]
+    fn should_convert_back_and_forth() {
+        for code in [1000, 2005, 2009, 4000, 5000, 1234] {
+            assert_eq!(ApiErrorCode::from(code).code(), code);
+        }
+        assert_eq!(ApiErrorCode::from(1234), ApiErrorCode::Other(1234));
+    }
+
+    #[test]
+    fn should_classify() {
+        assert!(ApiErrorCode::DirectoryNotFound.is_not_found());
+        assert!(ApiErrorCode::FileNotFound.is_not_found());
+        assert!(!ApiErrorCode::AccessDenied.is_not_found());
+        assert!(ApiErrorCode::LoginRequired.is_auth_error());
+        assert!(ApiErrorCode::InvalidAccessToken.is_auth_error());
+        assert!(ApiErrorCode::TooManyLogins.is_rate_limited());
+        assert!(ApiErrorCode::TooManyLogins.is_retryable());
+        assert!(ApiErrorCode::InternalError.is_retryable());
+        assert!(!ApiErrorCode::InvalidPath.is_retryable());
+        assert!(!ApiErrorCode::Other(1234).is_retryable());
+    }
+}
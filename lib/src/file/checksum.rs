@@ -1,3 +1,9 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use sha1::Digest;
+use tokio::io::AsyncWrite;
+
 use super::{File, FileIdentifier};
 
 /// Represents the checksum information for a file stored on pCloud.
@@ -12,6 +18,174 @@ pub struct FileChecksum {
     pub metadata: File,
 }
 
+impl FileChecksum {
+    /// Returns the checksums of the file, without its metadata.
+    pub fn checksums(&self) -> Checksums {
+        Checksums {
+            sha1: self.sha1.clone(),
+            sha256: self.sha256.clone(),
+            md5: self.md5.clone(),
+        }
+    }
+}
+
+/// Checksums of a file content, as computed by pCloud.
+///
+/// The SHA-1 is always provided, the SHA-256 is only provided by the European
+/// region and the MD5 by the US region.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize)]
+pub struct Checksums {
+    pub sha1: String,
+    pub sha256: Option<String>,
+    pub md5: Option<String>,
+}
+
+impl Checksums {
+    /// Compares the checksums with the ones computed locally.
+    ///
+    /// Only the algorithms available on both sides are compared.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ChecksumMismatch`] describing the first checksum that differs.
+    pub fn verify(&self, actual: &Checksums) -> Result<(), ChecksumMismatch> {
+        compare("sha1", Some(&self.sha1), Some(&actual.sha1))?;
+        compare("sha256", self.sha256.as_ref(), actual.sha256.as_ref())?;
+        compare("md5", self.md5.as_ref(), actual.md5.as_ref())
+    }
+}
+
+fn compare(
+    algorithm: &'static str,
+    expected: Option<&String>,
+    actual: Option<&String>,
+) -> Result<(), ChecksumMismatch> {
+    match (expected, actual) {
+        (Some(expected), Some(actual)) if !expected.eq_ignore_ascii_case(actual) => {
+            Err(ChecksumMismatch {
+                algorithm,
+                expected: expected.clone(),
+                actual: actual.clone(),
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Describes a checksum computed locally that doesn't match the one provided by pCloud.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChecksumMismatch {
+    /// Name of the algorithm (`sha1`, `sha256` or `md5`).
+    pub algorithm: &'static str,
+    /// Checksum provided by pCloud.
+    pub expected: String,
+    /// Checksum computed from the transferred bytes.
+    pub actual: String,
+}
+
+impl std::fmt::Display for ChecksumMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "expected {} {}, got {}",
+            self.algorithm, self.expected, self.actual
+        )
+    }
+}
+
+impl std::error::Error for ChecksumMismatch {}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Computes the checksums of a content while it's being transferred.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ChecksumHasher {
+    sha1: sha1::Sha1,
+    sha256: Option<sha2::Sha256>,
+    md5: Option<md5::Md5>,
+}
+
+impl ChecksumHasher {
+    /// Creates a hasher computing the algorithms provided in `expected`.
+    pub(crate) fn matching(expected: &Checksums) -> Self {
+        Self {
+            sha1: Default::default(),
+            sha256: expected.sha256.as_ref().map(|_| Default::default()),
+            md5: expected.md5.as_ref().map(|_| Default::default()),
+        }
+    }
+
+    /// Creates a hasher computing the algorithms returned by the region behind `base_url`.
+    ///
+    /// Both SHA-256 and MD5 are computed when the region is unknown.
+    pub(crate) fn for_region(base_url: &str) -> Self {
+        Self {
+            sha1: Default::default(),
+            sha256: (base_url != crate::US_REGION).then(Default::default),
+            md5: (base_url != crate::EU_REGION).then(Default::default),
+        }
+    }
+
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        self.sha1.update(data);
+        if let Some(ref mut hasher) = self.sha256 {
+            hasher.update(data);
+        }
+        if let Some(ref mut hasher) = self.md5 {
+            hasher.update(data);
+        }
+    }
+
+    pub(crate) fn finalize(self) -> Checksums {
+        Checksums {
+            sha1: to_hex(&self.sha1.finalize()),
+            sha256: self.sha256.map(|hasher| to_hex(&hasher.finalize())),
+            md5: self.md5.map(|hasher| to_hex(&hasher.finalize())),
+        }
+    }
+}
+
+/// Writer computing the checksums of the bytes written in the inner writer.
+pub(crate) struct HashingWriter<'a, W: ?Sized> {
+    inner: &'a mut W,
+    hasher: ChecksumHasher,
+}
+
+impl<'a, W: ?Sized> HashingWriter<'a, W> {
+    pub(crate) fn new(inner: &'a mut W, hasher: ChecksumHasher) -> Self {
+        Self { inner, hasher }
+    }
+
+    pub(crate) fn finalize(self) -> Checksums {
+        self.hasher.finalize()
+    }
+}
+
+impl<W: AsyncWrite + Unpin + ?Sized> AsyncWrite for HashingWriter<'_, W> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = self.get_mut();
+        let result = Pin::new(&mut *this.inner).poll_write(cx, buf);
+        if let Poll::Ready(Ok(written)) = result {
+            this.hasher.update(&buf[..written]);
+        }
+        result
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut *self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut *self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
 impl crate::Client {
     /// Retrieves the checksums and metadata for a file on pCloud.
     ///
@@ -44,6 +218,48 @@ impl crate::Client {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::{ChecksumHasher, Checksums};
+
+    #[test]
+    fn should_compute_checksums() {
+        let mut hasher = ChecksumHasher::for_region("http://localhost");
+        hasher.update(b"hello ");
+        hasher.update(b"world");
+        let checksums = hasher.finalize();
+        assert_eq!(checksums.sha1, "2aae6c35c94fcfb415dbe95f408b9ce91ee846ed");
+        assert_eq!(
+            checksums.sha256.as_deref(),
+            Some("b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9")
+        );
+        assert_eq!(
+            checksums.md5.as_deref(),
+            Some("5eb63bbbe01eeed093cb22bb8f5acdc3")
+        );
+        let hasher = ChecksumHasher::for_region(crate::EU_REGION);
+        assert!(hasher.finalize().md5.is_none());
+    }
+
+    #[test]
+    fn should_verify_common_algorithms() {
+        let expected = Checksums {
+            sha1: "2AAE6C35C94FCFB415DBE95F408B9CE91EE846ED".into(),
+            sha256: None,
+            md5: Some("5eb63bbbe01eeed093cb22bb8f5acdc3".into()),
+        };
+        let mut actual = Checksums {
+            sha1: "2aae6c35c94fcfb415dbe95f408b9ce91ee846ed".into(),
+            sha256: Some("b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9".into()),
+            md5: Some("5eb63bbbe01eeed093cb22bb8f5acdc3".into()),
+        };
+        assert!(expected.verify(&actual).is_ok());
+        actual.md5 = Some("00000000000000000000000000000000".into());
+        let error = expected.verify(&actual).unwrap_err();
+        assert_eq!(error.algorithm, "md5");
+    }
+}
+
 #[cfg(test)]
 mod http_tests {
     use crate::{Client, Credentials};
@@ -54,10 +270,8 @@ mod http_tests {
         let mut server = mockito::Server::new_async().await;
         let m = server
             .mock("GET", "/checksumfile")
-            .match_query(Matcher::AllOf(vec![
-                Matcher::UrlEncoded("access_token".into(), "access-token".into()),
-                Matcher::UrlEncoded("fileid".into(), "42".into()),
-            ]))
+            .match_header("authorization", "Bearer access-token")
+            .match_query(Matcher::UrlEncoded("fileid".into(), "42".into()))
             .with_status(200)
             .with_body(
                 r#"{
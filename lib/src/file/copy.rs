@@ -0,0 +1,217 @@
+use std::borrow::Cow;
+
+use crate::folder::{FolderIdentifier, ToFolderIdentifier};
+
+use super::{File, FileIdentifier, FileResponse};
+
+/// Options for customizing how a file is copied.
+#[derive(Debug, Default, serde::Serialize)]
+pub struct CopyFileOptions<'a> {
+    /// The name of the copy, the original name is kept when missing.
+    #[serde(rename = "toname", skip_serializing_if = "Option::is_none")]
+    to_name: Option<Cow<'a, str>>,
+
+    /// Whether to fail instead of overwriting an existing file.
+    #[serde(
+        rename = "noover",
+        skip_serializing_if = "crate::request::is_false",
+        serialize_with = "crate::request::serialize_bool"
+    )]
+    no_overwrite: bool,
+}
+
+impl<'a> CopyFileOptions<'a> {
+    /// Sets the name of the copy.
+    pub fn set_to_name(&mut self, value: impl Into<Cow<'a, str>>) {
+        self.to_name = Some(value.into());
+    }
+
+    /// Sets the name of the copy.
+    pub fn with_to_name(mut self, value: impl Into<Cow<'a, str>>) -> Self {
+        self.set_to_name(value);
+        self
+    }
+
+    /// Prevents an existing file from being overwritten.
+    ///
+    /// When enabled, the copy fails with [`crate::error::ApiErrorCode::AlreadyExists`].
+    pub fn set_no_overwrite(&mut self, value: bool) {
+        self.no_overwrite = value;
+    }
+
+    /// Prevents an existing file from being overwritten.
+    pub fn with_no_overwrite(mut self, value: bool) -> Self {
+        self.set_no_overwrite(value);
+        self
+    }
+}
+
+/// Parameters required to copy a file to a folder.
+///
+/// This structure is serialized and sent to the `copyfile` endpoint.
+#[derive(serde::Serialize)]
+struct FileCopyParams<'a> {
+    /// The file to copy (by path or file ID).
+    #[serde(flatten)]
+    from: FileIdentifier<'a>,
+
+    /// The target folder to copy the file into.
+    #[serde(flatten)]
+    to: ToFolderIdentifier<'a>,
+
+    #[serde(flatten)]
+    options: CopyFileOptions<'a>,
+}
+
+impl crate::Client {
+    /// Copies a file to a folder on pCloud.
+    ///
+    /// This is a convenience method that calls [`crate::Client::copy_file_with_options`] with default options,
+    /// the copy keeps the name of the original file and overwrites any existing file.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - A value that can be converted into a [`FileIdentifier`] (e.g., file ID or path).
+    /// * `to_folder` - A value that can be converted into a [`FolderIdentifier`] representing the destination folder.
+    ///
+    /// # Returns
+    ///
+    /// On success, returns a [`File`] struct containing metadata about the copy.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`crate::Error`] if the file or folder is not found, or if the API request fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # async fn example(client: &pcloud::Client) -> Result<(), pcloud::Error> {
+    /// let file = client.copy_file(12345678u64, "/backup/").await?;
+    /// println!("Copied file ID: {}", file.file_id);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn copy_file(
+        &self,
+        file: impl Into<FileIdentifier<'_>>,
+        to_folder: impl Into<FolderIdentifier<'_>>,
+    ) -> crate::Result<File> {
+        self.copy_file_with_options(file, to_folder, CopyFileOptions::default())
+            .await
+    }
+
+    /// Copies a file to a folder on pCloud with the given options.
+    ///
+    /// The copy is done on the server side by the `copyfile` endpoint, no content is transferred.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - A value that can be converted into a [`FileIdentifier`] (e.g., file ID or path).
+    /// * `to_folder` - A value that can be converted into a [`FolderIdentifier`] representing the destination folder.
+    /// * `options` - A [`CopyFileOptions`] to rename the copy or prevent overwriting.
+    ///
+    /// # Returns
+    ///
+    /// On success, returns a [`File`] struct containing metadata about the copy.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`crate::Error`] if the file or folder is not found, if a file already exists
+    /// while overwriting is disabled, or if the API request fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pcloud::file::copy::CopyFileOptions;
+    ///
+    /// # async fn example(client: &pcloud::Client) -> Result<(), pcloud::Error> {
+    /// let options = CopyFileOptions::default()
+    ///     .with_to_name("report-copy.pdf")
+    ///     .with_no_overwrite(true);
+    /// let file = client.copy_file_with_options(12345678u64, 42u64, options).await?;
+    /// println!("Copied file name: {}", file.base.name);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn copy_file_with_options(
+        &self,
+        file: impl Into<FileIdentifier<'_>>,
+        to_folder: impl Into<FolderIdentifier<'_>>,
+        options: CopyFileOptions<'_>,
+    ) -> crate::Result<File> {
+        let to_folder = to_folder.into();
+        let cached = self.cache_key(&to_folder);
+        let file = self
+            .get_request::<FileResponse, _>(
+                "copyfile",
+                FileCopyParams {
+                    from: file.into(),
+                    to: ToFolderIdentifier(to_folder),
+                    options,
+                },
+            )
+            .await?
+            .metadata;
+        if let Some((cache, key)) = cached {
+            cache.content_changed(&key);
+        }
+        Ok(file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CopyFileOptions;
+    use crate::{Client, Credentials};
+    use mockito::Matcher;
+
+    #[tokio::test]
+    async fn success() {
+        let mut server = mockito::Server::new_async().await;
+        let m = server
+            .mock("GET", "/copyfile")
+            .match_header("authorization", "Bearer access-token")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("fileid".into(), "42".into()),
+                Matcher::UrlEncoded("tofolderid".into(), "12".into()),
+                Matcher::UrlEncoded("toname".into(), "copy.bin".into()),
+                Matcher::UrlEncoded("noover".into(), "1".into()),
+            ]))
+            .with_status(200)
+            .with_body(
+                r#"{
+    "result": 0,
+    "metadata": {
+        "name": "copy.bin",
+        "created": "Sat, 24 Jul 2021 07:38:41 +0000",
+        "thumb": false,
+        "modified": "Sat, 24 Jul 2021 07:38:41 +0000",
+        "isfolder": false,
+        "fileid": 43,
+        "hash": 9403476549337371523,
+        "comments": 0,
+        "category": 0,
+        "id": "f43",
+        "isshared": false,
+        "ismine": true,
+        "size": 10485760,
+        "parentfolderid": 12,
+        "contenttype": "application\/octet-stream",
+        "icon": "file"
+    }
+}"#,
+            )
+            .create();
+        let client = Client::new(server.url(), Credentials::access_token("access-token")).unwrap();
+        let options = CopyFileOptions::default()
+            .with_to_name("copy.bin")
+            .with_no_overwrite(true);
+        let result = client
+            .copy_file_with_options(42, 12, options)
+            .await
+            .unwrap();
+        assert_eq!(result.file_id, 43);
+        assert_eq!(result.base.name, "copy.bin");
+        m.assert();
+    }
+}
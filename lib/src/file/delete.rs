@@ -9,7 +9,7 @@ impl crate::Client {
     /// # Arguments
     ///
     /// * `identifier` - A type convertible into a [`FileIdentifier`] that identifies the file to delete
-    /// (e.g., by file ID or path).
+    ///   (e.g., by file ID or path).
     ///
     /// # Returns
     ///
@@ -32,9 +32,16 @@ impl crate::Client {
         &self,
         identifier: impl Into<FileIdentifier<'_>>,
     ) -> crate::Result<File> {
-        self.get_request::<FileResponse, _>("deletefile", identifier.into())
-            .await
-            .map(|res| res.metadata)
+        let identifier = identifier.into();
+        let cached = self.cache_key(&identifier);
+        let file = self
+            .get_request::<FileResponse, _>("deletefile", identifier)
+            .await?
+            .metadata;
+        if let Some((cache, key)) = cached {
+            cache.file_changed(&key, Some(&file));
+        }
+        Ok(file)
     }
 }
 
@@ -48,10 +55,8 @@ mod http_tests {
         let mut server = mockito::Server::new_async().await;
         let m = server
             .mock("GET", "/deletefile")
-            .match_query(Matcher::AllOf(vec![
-                Matcher::UrlEncoded("access_token".into(), "access-token".into()),
-                Matcher::UrlEncoded("fileid".into(), "42".into()),
-            ]))
+            .match_header("authorization", "Bearer access-token")
+            .match_query(Matcher::UrlEncoded("fileid".into(), "42".into()))
             .with_status(200)
             .with_body(
                 r#"{
@@ -0,0 +1,1107 @@
+use std::ops::{Bound, RangeBounds};
+use std::path::{Path, PathBuf};
+
+use bytes::Bytes;
+use futures_core::Stream;
+use futures_util::StreamExt;
+use tokio::io::{AsyncSeekExt, AsyncWrite, AsyncWriteExt};
+
+use super::checksum::{ChecksumHasher, Checksums, HashingWriter};
+use super::FileIdentifier;
+use crate::stream::StreamingLinkList;
+
+/// A range of bytes to request to a content host, the end being inclusive.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ByteRange {
+    start: u64,
+    end: Option<u64>,
+}
+
+impl ByteRange {
+    /// Creates a range starting at `start` and ending at `end`, included.
+    pub(crate) fn new(start: u64, end: Option<u64>) -> Self {
+        Self { start, end }
+    }
+
+    /// Converts any range of `u64`, returns `None` when the range is empty.
+    pub(crate) fn from_bounds(range: impl RangeBounds<u64>) -> Option<Self> {
+        let start = match range.start_bound() {
+            Bound::Included(value) => *value,
+            Bound::Excluded(value) => value.checked_add(1)?,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(value) => Some(*value),
+            Bound::Excluded(value) => Some(value.checked_sub(1)?),
+            Bound::Unbounded => None,
+        };
+        match end {
+            Some(end) if end < start => None,
+            _ => Some(Self { start, end }),
+        }
+    }
+
+    /// Returns the same range without the first `count` bytes.
+    fn skip(self, count: u64) -> Self {
+        Self {
+            start: self.start + count,
+            end: self.end,
+        }
+    }
+
+    /// Returns the number of bytes in the range, or `None` when it goes to the end of the file.
+    fn len(&self) -> Option<u64> {
+        self.end.map(|end| end + 1 - self.start)
+    }
+
+    /// Returns `true` when the range covers the whole file.
+    fn is_full(&self) -> bool {
+        self.start == 0 && self.end.is_none()
+    }
+
+    /// Formats the value of the `Range` header.
+    fn header(&self) -> String {
+        match self.end {
+            Some(end) => format!("bytes={}-{end}", self.start),
+            None => format!("bytes={}-", self.start),
+        }
+    }
+}
+
+/// Options for downloading a single file over several connections.
+///
+/// The file is split in chunks of `chunk_size` bytes, downloaded with up to
+/// `connections` concurrent range requests spread across the available hosts.
+#[derive(Clone, Debug)]
+pub struct ParallelDownloadOptions {
+    connections: usize,
+    chunk_size: u64,
+}
+
+impl Default for ParallelDownloadOptions {
+    /// Creates options with 4 connections and chunks of 16MiB.
+    fn default() -> Self {
+        Self {
+            connections: 4,
+            chunk_size: 16 * 1024 * 1024,
+        }
+    }
+}
+
+impl ParallelDownloadOptions {
+    /// Sets the number of concurrent connections.
+    pub fn set_connections(&mut self, value: usize) {
+        self.connections = value.max(1);
+    }
+
+    /// Sets the number of concurrent connections and returns the updated options.
+    pub fn with_connections(mut self, value: usize) -> Self {
+        self.set_connections(value);
+        self
+    }
+
+    /// Sets the size of the chunks, in bytes.
+    pub fn set_chunk_size(&mut self, value: u64) {
+        self.chunk_size = value.max(1);
+    }
+
+    /// Sets the size of the chunks and returns the updated options.
+    pub fn with_chunk_size(mut self, value: u64) -> Self {
+        self.set_chunk_size(value);
+        self
+    }
+
+    /// Splits a file of `size` bytes in ranges.
+    fn ranges(&self, size: u64) -> impl Iterator<Item = ByteRange> + '_ {
+        let chunk_size = self.chunk_size;
+        (0..size.div_ceil(chunk_size)).map(move |index| {
+            let start = index * chunk_size;
+            ByteRange::new(start, Some((start + chunk_size).min(size) - 1))
+        })
+    }
+}
+
+/// State of an unfinished download, stored next to the downloaded file.
+#[derive(Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+struct ResumeState {
+    hash: u64,
+    size: Option<u64>,
+}
+
+impl ResumeState {
+    /// Path of the file keeping the state of the download of `path`.
+    fn path(path: &Path) -> PathBuf {
+        let mut name = path.file_name().unwrap_or_default().to_os_string();
+        name.push(".pcloud-resume");
+        path.with_file_name(name)
+    }
+
+    async fn read(path: &Path) -> Option<Self> {
+        let content = tokio::fs::read(path).await.ok()?;
+        serde_json::from_slice(&content).ok()
+    }
+
+    async fn write(&self, path: &Path) -> std::io::Result<()> {
+        let content = serde_json::to_vec(self).map_err(std::io::Error::other)?;
+        tokio::fs::write(path, content).await
+    }
+}
+
+/// Builds an [`Error::Download`](crate::Error::Download) with the provided context.
+fn download_error(
+    context: impl Into<String>,
+    cause: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+) -> crate::Error {
+    crate::Error::Download(context.into(), std::io::Error::other(cause))
+}
+
+/// Error returned when there is no host left to try.
+fn no_host_error() -> crate::Error {
+    crate::Error::Download(
+        "no host available".into(),
+        std::io::Error::from(std::io::ErrorKind::NotFound),
+    )
+}
+
+impl crate::Client {
+    /// Sends a GET request to a content host and checks the response status.
+    ///
+    /// When only a part of the file is requested, the host must reply with a partial content.
+    async fn open_url(&self, url: &str, range: ByteRange) -> crate::Result<reqwest::Response> {
+        let mut req = self.inner.get(url);
+        if !range.is_full() {
+            req = req.header(reqwest::header::RANGE, range.header());
+        }
+        let res = req
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|err| download_error(format!("unable to fetch {url}"), err))?;
+        if !range.is_full() && res.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+            return Err(download_error(
+                format!("unable to fetch {url}"),
+                "the host doesn't support range requests",
+            ));
+        }
+        Ok(res)
+    }
+
+    /// Opens the first URL responding successfully to the requested range.
+    async fn open_urls<I>(&self, urls: I, range: ByteRange) -> crate::Result<reqwest::Response>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut last_error = None;
+        for url in urls {
+            match self.open_url(&url, range).await {
+                Ok(res) => return Ok(res),
+                Err(err) => {
+                    tracing::warn!(url, "download failed, trying next host: {err}");
+                    last_error = Some(err);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(no_host_error))
+    }
+
+    /// Downloads the requested range from the first URL that works and writes it in `writer`.
+    ///
+    /// When a host fails, the next one is used. If some bytes were already written,
+    /// the download continues from there with a range request.
+    pub(crate) async fn download_urls<I, W>(
+        &self,
+        urls: I,
+        range: ByteRange,
+        writer: &mut W,
+    ) -> crate::Result<u64>
+    where
+        I: IntoIterator<Item = String>,
+        W: AsyncWrite + Unpin + ?Sized,
+    {
+        let mut written: u64 = 0;
+        let mut last_error = None;
+        for url in urls {
+            let res = match self.open_url(&url, range.skip(written)).await {
+                Ok(res) => res,
+                Err(err) => {
+                    tracing::warn!(url, "download failed, trying next host: {err}");
+                    last_error = Some(err);
+                    continue;
+                }
+            };
+            let mut stream = res.bytes_stream();
+            let failure = loop {
+                match stream.next().await {
+                    Some(Ok(chunk)) => {
+                        // a longer body would overwrite what follows the range in the file
+                        if range
+                            .len()
+                            .is_some_and(|len| written + chunk.len() as u64 > len)
+                        {
+                            return Err(download_error(
+                                format!("unable to fetch {url}"),
+                                "the host returned more bytes than requested",
+                            ));
+                        }
+                        writer.write_all(&chunk).await.map_err(|err| {
+                            crate::Error::Download(
+                                format!("unable to write content from {url}"),
+                                err,
+                            )
+                        })?;
+                        written += chunk.len() as u64;
+                    }
+                    Some(Err(err)) => break Some(err),
+                    None => break None,
+                }
+            };
+            match failure {
+                Some(err) => {
+                    tracing::warn!(url, written, "connection lost, trying next host: {err}");
+                    last_error = Some(download_error(
+                        format!("connection lost with {url} after {written} bytes"),
+                        err,
+                    ));
+                }
+                None => {
+                    writer.flush().await.map_err(|err| {
+                        crate::Error::Download(format!("unable to write content from {url}"), err)
+                    })?;
+                    return Ok(written);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(no_host_error))
+    }
+
+    /// Downloads a file from one of the provided links and writes it in `writer`.
+    ///
+    /// The hosts are tried in order, when one is failing, the next one is used.
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes written.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`crate::Error::Download`] if none of the hosts could provide the content,
+    /// if the connection was lost while downloading or if the writer failed.
+    pub async fn download_links<W>(
+        &self,
+        links: &StreamingLinkList,
+        writer: &mut W,
+    ) -> crate::Result<u64>
+    where
+        W: AsyncWrite + Unpin + ?Sized,
+    {
+        let urls = links.links().map(|link| link.to_string());
+        self.download_urls(urls, ByteRange::default(), writer).await
+    }
+
+    /// Downloads a file from pCloud and writes it in `writer`.
+    ///
+    /// This resolves the file links with [`crate::Client::get_file_link`] and tries
+    /// every returned host until one succeeds.
+    ///
+    /// # Arguments
+    ///
+    /// * `identifier` - A value convertible into a [`FileIdentifier`] representing the file to download.
+    /// * `writer` - Where to write the content of the file.
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes written.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`crate::Error`] if the links cannot be resolved, or a
+    /// [`crate::Error::Download`] if the download itself failed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # async fn example(client: &pcloud::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut file = tokio::fs::File::create("./file.txt").await?;
+    /// let size = client.download("/folder/file.txt", &mut file).await?;
+    /// println!("downloaded {size} bytes");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download<W>(
+        &self,
+        identifier: impl Into<FileIdentifier<'_>>,
+        writer: &mut W,
+    ) -> crate::Result<u64>
+    where
+        W: AsyncWrite + Unpin + ?Sized,
+    {
+        let links = self.get_file_link(identifier).await?;
+        self.download_links(&links, writer).await
+    }
+
+    /// Downloads a range of bytes of a file from pCloud and writes it in `writer`.
+    ///
+    /// The hosts are requested with an HTTP `Range` header, the ones not supporting it are skipped.
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes written.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`crate::Error`] if the links cannot be resolved, or a
+    /// [`crate::Error::Download`] if the download itself failed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # async fn example(client: &pcloud::Client) -> Result<(), pcloud::Error> {
+    /// let mut header = Vec::new();
+    /// client.download_range(42, 0..512, &mut header).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_range<W>(
+        &self,
+        identifier: impl Into<FileIdentifier<'_>>,
+        range: impl RangeBounds<u64>,
+        writer: &mut W,
+    ) -> crate::Result<u64>
+    where
+        W: AsyncWrite + Unpin + ?Sized,
+    {
+        let Some(range) = ByteRange::from_bounds(range) else {
+            return Ok(0);
+        };
+        let links = self.get_file_link(identifier).await?;
+        let urls = links.links().map(|link| link.to_string());
+        self.download_urls(urls, range, writer).await
+    }
+
+    /// Downloads from the provided URLs and checks the content against the `expected` checksums.
+    pub(crate) async fn download_urls_verified<I, W>(
+        &self,
+        urls: I,
+        name: &str,
+        expected: &Checksums,
+        writer: &mut W,
+    ) -> crate::Result<u64>
+    where
+        I: IntoIterator<Item = String>,
+        W: AsyncWrite + Unpin + ?Sized,
+    {
+        let mut writer = HashingWriter::new(writer, ChecksumHasher::matching(expected));
+        let written = self
+            .download_urls(urls, ByteRange::default(), &mut writer)
+            .await?;
+        expected
+            .verify(&writer.finalize())
+            .map_err(|mismatch| crate::Error::ChecksumMismatch(name.to_string(), mismatch))?;
+        Ok(written)
+    }
+
+    /// Downloads a file from pCloud, writes it in `writer` and verifies its integrity.
+    ///
+    /// The checksums of the file are fetched with [`crate::Client::get_file_checksum`]
+    /// and the written bytes are hashed while downloading. The SHA-1 is always compared,
+    /// alongside the SHA-256 or the MD5 depending on what the region provides.
+    ///
+    /// The content is written as it arrives, the caller is responsible for discarding
+    /// it when the verification fails.
+    ///
+    /// # Arguments
+    ///
+    /// * `identifier` - A value convertible into a [`FileIdentifier`] representing the file to download.
+    /// * `writer` - Where to write the content of the file.
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes written.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`crate::Error::ChecksumMismatch`] if the downloaded content doesn't match
+    /// the checksums provided by pCloud, or any error returned by [`crate::Client::download`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # async fn example(client: &pcloud::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut file = tokio::fs::File::create("./file.txt").await?;
+    /// match client.download_verified("/folder/file.txt", &mut file).await {
+    ///     Err(pcloud::Error::ChecksumMismatch(name, mismatch)) => {
+    ///         eprintln!("{name} is corrupted: {mismatch}");
+    ///     }
+    ///     other => {
+    ///         other?;
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_verified<W>(
+        &self,
+        identifier: impl Into<FileIdentifier<'_>>,
+        writer: &mut W,
+    ) -> crate::Result<u64>
+    where
+        W: AsyncWrite + Unpin + ?Sized,
+    {
+        let identifier = identifier.into();
+        let checksum = self.get_file_checksum(identifier.clone()).await?;
+        let links = self.get_file_link(identifier).await?;
+        let urls = links.links().map(|link| link.to_string());
+        self.download_urls_verified(
+            urls,
+            &checksum.metadata.base.name,
+            &checksum.checksums(),
+            writer,
+        )
+        .await
+    }
+
+    /// Downloads a file from pCloud to a local path, resuming a previous download if possible.
+    ///
+    /// While downloading, the hash of the remote file is kept in a `.pcloud-resume` file next
+    /// to the local one. When called again after an interruption, the download continues from
+    /// the current length of the local file, unless the remote file changed in the meantime,
+    /// in which case it starts again from scratch. The state file is removed once the download
+    /// is complete.
+    ///
+    /// # Returns
+    ///
+    /// The size of the local file once downloaded.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`crate::Error`] if the links cannot be resolved, or a
+    /// [`crate::Error::Download`] if the download itself failed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # async fn example(client: &pcloud::Client) -> Result<(), pcloud::Error> {
+    /// let size = client.download_resumable("/videos/movie.mkv", "./movie.mkv").await?;
+    /// println!("downloaded {size} bytes");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_resumable(
+        &self,
+        identifier: impl Into<FileIdentifier<'_>>,
+        path: impl AsRef<Path>,
+    ) -> crate::Result<u64> {
+        let links = self.get_file_link(identifier).await?;
+        let urls = links.links().map(|link| link.to_string());
+        self.download_urls_resumable(urls, links.hash, links.size, path.as_ref())
+            .await
+    }
+
+    /// Downloads the content of the first working URL to `path`, resuming from the
+    /// current length of the file when the remote `hash` didn't change.
+    pub(crate) async fn download_urls_resumable<I>(
+        &self,
+        urls: I,
+        hash: Option<u64>,
+        size: Option<u64>,
+        path: &Path,
+    ) -> crate::Result<u64>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let write_error = |err| crate::Error::Download(format!("unable to write {path:?}"), err);
+
+        let state_path = ResumeState::path(path);
+        let current_length = match tokio::fs::metadata(path).await {
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        };
+        let offset = match (hash, ResumeState::read(&state_path).await) {
+            // a local file longer than the remote one can't be a part of it
+            (Some(hash), Some(previous))
+                if previous.hash == hash && size.is_some_and(|size| current_length > size) =>
+            {
+                tracing::info!(
+                    ?path,
+                    current_length,
+                    "local file too long, restarting download"
+                );
+                0
+            }
+            (Some(hash), Some(previous)) if previous.hash == hash => {
+                tracing::debug!(?path, current_length, "resuming download");
+                current_length
+            }
+            (_, Some(_)) => {
+                tracing::info!(?path, "remote file changed, restarting download");
+                0
+            }
+            _ => 0,
+        };
+
+        if let Some(hash) = hash {
+            ResumeState { hash, size }
+                .write(&state_path)
+                .await
+                .map_err(write_error)?;
+        }
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .await
+            .map_err(write_error)?;
+        file.set_len(offset).await.map_err(write_error)?;
+        file.seek(std::io::SeekFrom::Start(offset))
+            .await
+            .map_err(write_error)?;
+
+        let written = if size.is_some_and(|size| offset >= size) {
+            0
+        } else {
+            let mut writer = tokio::io::BufWriter::new(file);
+            self.download_urls(urls, ByteRange::new(offset, None), &mut writer)
+                .await?
+        };
+
+        if hash.is_some() {
+            tokio::fs::remove_file(&state_path)
+                .await
+                .map_err(write_error)?;
+        }
+        Ok(offset + written)
+    }
+
+    /// Downloads a file from pCloud to a local path using several connections at the same time.
+    ///
+    /// The file is split in ranges fetched concurrently, each range being requested to a
+    /// different host first and falling back to the other ones on failure. This speeds up
+    /// downloads when the bandwidth of a single connection is limited.
+    ///
+    /// When the size of the file is unknown, it is downloaded with a single connection.
+    ///
+    /// # Returns
+    ///
+    /// The size of the downloaded file.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`crate::Error`] if the links cannot be resolved, or a
+    /// [`crate::Error::Download`] if one of the ranges couldn't be downloaded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pcloud::file::download::ParallelDownloadOptions;
+    ///
+    /// # async fn example(client: &pcloud::Client) -> Result<(), pcloud::Error> {
+    /// let options = ParallelDownloadOptions::default().with_connections(8);
+    /// client.download_parallel("/videos/movie.mkv", "./movie.mkv", options).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_parallel(
+        &self,
+        identifier: impl Into<FileIdentifier<'_>>,
+        path: impl AsRef<Path>,
+        options: ParallelDownloadOptions,
+    ) -> crate::Result<u64> {
+        let links = self.get_file_link(identifier).await?;
+        let urls = links.links().map(|link| link.to_string()).collect();
+        self.download_urls_parallel(urls, links.size, path.as_ref(), &options)
+            .await
+    }
+
+    /// Downloads the content of the URLs to `path` with concurrent range requests.
+    pub(crate) async fn download_urls_parallel(
+        &self,
+        urls: Vec<String>,
+        size: Option<u64>,
+        path: &Path,
+        options: &ParallelDownloadOptions,
+    ) -> crate::Result<u64> {
+        let write_error = |err| crate::Error::Download(format!("unable to write {path:?}"), err);
+
+        let file = tokio::fs::File::create(path).await.map_err(write_error)?;
+        let Some(size) = size else {
+            tracing::debug!(
+                ?path,
+                "unknown file size, downloading with a single connection"
+            );
+            let mut writer = tokio::io::BufWriter::new(file);
+            return self
+                .download_urls(urls, ByteRange::default(), &mut writer)
+                .await;
+        };
+        file.set_len(size).await.map_err(write_error)?;
+        drop(file);
+
+        let urls = &urls;
+        let tasks = options
+            .ranges(size)
+            .enumerate()
+            .map(|(index, range)| async move {
+                // each range starts with a different host and then tries the other ones
+                let count = urls.len().max(1);
+                let hosts = urls.iter().cycle().skip(index % count).take(urls.len());
+                let mut file = tokio::fs::OpenOptions::new()
+                    .write(true)
+                    .open(path)
+                    .await
+                    .map_err(write_error)?;
+                file.seek(std::io::SeekFrom::Start(range.start))
+                    .await
+                    .map_err(write_error)?;
+                let mut writer = tokio::io::BufWriter::new(file);
+                tracing::debug!(?path, range = range.header(), "downloading range");
+                let written = self
+                    .download_urls(hosts.cloned(), range, &mut writer)
+                    .await?;
+                match range.len() {
+                    Some(len) if len != written => Err(download_error(
+                        format!("unable to download {}", range.header()),
+                        format!("received {written} bytes instead of {len}"),
+                    )),
+                    _ => Ok(written),
+                }
+            });
+        let mut tasks = futures_util::stream::iter(tasks).buffer_unordered(options.connections);
+        let mut written = 0;
+        while let Some(result) = tasks.next().await {
+            written += result?;
+        }
+        if written != size {
+            return Err(download_error(
+                format!("unable to download {path:?}"),
+                format!("received {written} bytes instead of {size}"),
+            ));
+        }
+        Ok(written)
+    }
+
+    /// Opens the first working link and returns its content as a stream of bytes.
+    ///
+    /// Failing over to the next host only happens before the stream is returned,
+    /// errors happening while consuming the stream are returned as is.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`crate::Error::Download`] if none of the hosts could be reached.
+    pub async fn download_links_stream(
+        &self,
+        links: &StreamingLinkList,
+    ) -> crate::Result<impl Stream<Item = crate::Result<Bytes>> + Send + 'static> {
+        let urls: Vec<String> = links.links().map(|link| link.to_string()).collect();
+        self.download_urls_stream(urls, ByteRange::default()).await
+    }
+
+    /// Opens the first URL providing the requested range and returns its content as a stream of bytes.
+    pub(crate) async fn download_urls_stream<I>(
+        &self,
+        urls: I,
+        range: ByteRange,
+    ) -> crate::Result<impl Stream<Item = crate::Result<Bytes>> + Send + 'static>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let res = self.open_urls(urls, range).await?;
+        let url = res.url().to_string();
+        Ok(res.bytes_stream().map(move |chunk| {
+            chunk.map_err(|err| download_error(format!("unable to read {url}"), err))
+        }))
+    }
+
+    /// Downloads a file from pCloud as a stream of bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`crate::Error`] if the links cannot be resolved, or a
+    /// [`crate::Error::Download`] if none of the hosts could be reached.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use futures_util::StreamExt;
+    ///
+    /// # async fn example(client: &pcloud::Client) -> Result<(), pcloud::Error> {
+    /// let stream = client.download_stream(42).await?;
+    /// let mut stream = std::pin::pin!(stream);
+    /// while let Some(chunk) = stream.next().await {
+    ///     println!("received {} bytes", chunk?.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_stream(
+        &self,
+        identifier: impl Into<FileIdentifier<'_>>,
+    ) -> crate::Result<impl Stream<Item = crate::Result<Bytes>> + Send + 'static> {
+        let links = self.get_file_link(identifier).await?;
+        self.download_links_stream(&links).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ByteRange, ParallelDownloadOptions, ResumeState};
+    use crate::file::checksum::Checksums;
+    use crate::testing::TempDir;
+    use crate::{Client, Credentials};
+    use futures_util::StreamExt;
+    use mockito::Matcher;
+
+    #[test]
+    fn should_convert_range_bounds() {
+        assert_eq!(ByteRange::from_bounds(..), Some(ByteRange::new(0, None)));
+        assert_eq!(
+            ByteRange::from_bounds(10..20),
+            Some(ByteRange::new(10, Some(19)))
+        );
+        assert_eq!(
+            ByteRange::from_bounds(10..=20),
+            Some(ByteRange::new(10, Some(20)))
+        );
+        assert_eq!(ByteRange::from_bounds(10..10), None);
+        assert_eq!(ByteRange::new(10, Some(19)).header(), "bytes=10-19");
+        assert_eq!(ByteRange::new(10, None).skip(5).header(), "bytes=15-");
+    }
+
+    #[tokio::test]
+    async fn should_resume_on_next_host_with_range() {
+        let mut server = mockito::Server::new_async().await;
+        let no_range = server
+            .mock("GET", "/no-range/file.bin")
+            .match_header("range", "bytes=6-10")
+            .with_status(200)
+            .with_body("hello world")
+            .create_async()
+            .await;
+        let with_range = server
+            .mock("GET", "/with-range/file.bin")
+            .match_header("range", "bytes=6-10")
+            .with_status(206)
+            .with_body("world")
+            .create_async()
+            .await;
+        let client = Client::new(server.url(), Credentials::anonymous()).unwrap();
+        let urls = vec![
+            format!("{}/no-range/file.bin", server.url()),
+            format!("{}/with-range/file.bin", server.url()),
+        ];
+        let mut buffer = Vec::new();
+        let size = client
+            .download_urls(urls, ByteRange::new(6, Some(10)), &mut buffer)
+            .await
+            .unwrap();
+        assert_eq!(size, 5);
+        assert_eq!(buffer, b"world");
+        no_range.assert_async().await;
+        with_range.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn should_verify_downloaded_content() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/file.bin")
+            .with_status(200)
+            .with_body("hello world")
+            .create_async()
+            .await;
+        let client = Client::new(server.url(), Credentials::anonymous()).unwrap();
+        let urls = vec![format!("{}/file.bin", server.url())];
+        let mut expected = Checksums {
+            sha1: "2aae6c35c94fcfb415dbe95f408b9ce91ee846ed".into(),
+            sha256: Some("b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9".into()),
+            md5: None,
+        };
+        let mut buffer = Vec::new();
+        let size = client
+            .download_urls_verified(urls.clone(), "file.bin", &expected, &mut buffer)
+            .await
+            .unwrap();
+        assert_eq!(size, 11);
+
+        expected.sha256 = Some("0".repeat(64));
+        let error = client
+            .download_urls_verified(urls, "file.bin", &expected, &mut Vec::new())
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(error, crate::Error::ChecksumMismatch(ref name, ref mismatch) if name == "file.bin" && mismatch.algorithm == "sha256")
+        );
+    }
+
+    #[test]
+    fn should_split_in_ranges() {
+        let options = ParallelDownloadOptions::default().with_chunk_size(4);
+        let ranges = options.ranges(11).collect::<Vec<_>>();
+        assert_eq!(
+            ranges,
+            vec![
+                ByteRange::new(0, Some(3)),
+                ByteRange::new(4, Some(7)),
+                ByteRange::new(8, Some(10)),
+            ]
+        );
+        assert_eq!(options.ranges(0).count(), 0);
+    }
+
+    #[tokio::test]
+    async fn should_download_ranges_across_hosts() {
+        let dir = TempDir::new("parallel");
+        let path = dir.join("file.bin");
+
+        let mut server = mockito::Server::new_async().await;
+        let mut mocks = Vec::new();
+        for (host, range, body) in [
+            ("a", "bytes=0-3", "hell"),
+            ("b", "bytes=4-7", "o wo"),
+            ("a", "bytes=8-10", "rld"),
+        ] {
+            let m = server
+                .mock("GET", format!("/{host}/file.bin").as_str())
+                .match_header("range", range)
+                .with_status(206)
+                .with_body(body)
+                .create_async()
+                .await;
+            mocks.push(m);
+        }
+        let client = Client::new(server.url(), Credentials::anonymous()).unwrap();
+        let urls = vec![
+            format!("{}/a/file.bin", server.url()),
+            format!("{}/b/file.bin", server.url()),
+        ];
+        let options = ParallelDownloadOptions::default()
+            .with_chunk_size(4)
+            .with_connections(2);
+        let size = client
+            .download_urls_parallel(urls, Some(11), &path, &options)
+            .await
+            .unwrap();
+        assert_eq!(size, 11);
+        assert_eq!(tokio::fs::read(&path).await.unwrap(), b"hello world");
+        for m in mocks {
+            m.assert_async().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn should_fail_on_incomplete_ranges() {
+        let dir = TempDir::new("parallel-short");
+        let path = dir.join("file.bin");
+
+        let mut server = mockito::Server::new_async().await;
+        for (range, body) in [("bytes=0-3", "hell"), ("bytes=4-7", "o")] {
+            server
+                .mock("GET", "/file.bin")
+                .match_header("range", range)
+                .with_status(206)
+                .with_body(body)
+                .create_async()
+                .await;
+        }
+        let client = Client::new(server.url(), Credentials::anonymous()).unwrap();
+        let urls = vec![format!("{}/file.bin", server.url())];
+        let options = ParallelDownloadOptions::default().with_chunk_size(4);
+        let err = client
+            .download_urls_parallel(urls.clone(), Some(8), &path, &options)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, crate::Error::Download(..)), "{:?}", err);
+
+        // a longer body is rejected before overwriting the next range
+        server.reset();
+        for (range, body) in [("bytes=0-3", "hello"), ("bytes=4-7", "o wo")] {
+            server
+                .mock("GET", "/file.bin")
+                .match_header("range", range)
+                .with_status(206)
+                .with_body(body)
+                .create_async()
+                .await;
+        }
+        let err = client
+            .download_urls_parallel(urls, Some(8), &path, &options)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, crate::Error::Download(..)), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn should_resume_partial_file() {
+        let dir = TempDir::new("resume");
+        let path = dir.join("file.bin");
+        tokio::fs::write(&path, b"hello ").await.unwrap();
+        ResumeState {
+            hash: 42,
+            size: Some(11),
+        }
+        .write(&ResumeState::path(&path))
+        .await
+        .unwrap();
+
+        let mut server = mockito::Server::new_async().await;
+        let m = server
+            .mock("GET", "/file.bin")
+            .match_header("range", "bytes=6-")
+            .with_status(206)
+            .with_body("world")
+            .create_async()
+            .await;
+        let client = Client::new(server.url(), Credentials::anonymous()).unwrap();
+        let urls = vec![format!("{}/file.bin", server.url())];
+        let size = client
+            .download_urls_resumable(urls, Some(42), Some(11), &path)
+            .await
+            .unwrap();
+        assert_eq!(size, 11);
+        assert_eq!(tokio::fs::read(&path).await.unwrap(), b"hello world");
+        assert!(!ResumeState::path(&path).exists());
+        m.assert_async().await;
+
+        // the remote file changed, the download starts from scratch
+        tokio::fs::write(&path, b"hello ").await.unwrap();
+        ResumeState {
+            hash: 42,
+            size: Some(11),
+        }
+        .write(&ResumeState::path(&path))
+        .await
+        .unwrap();
+        let m = server
+            .mock("GET", "/file.bin")
+            .match_header("range", Matcher::Missing)
+            .with_status(200)
+            .with_body("HELLO WORLD")
+            .create_async()
+            .await;
+        let urls = vec![format!("{}/file.bin", server.url())];
+        let size = client
+            .download_urls_resumable(urls, Some(43), Some(11), &path)
+            .await
+            .unwrap();
+        assert_eq!(size, 11);
+        assert_eq!(tokio::fs::read(&path).await.unwrap(), b"HELLO WORLD");
+        m.assert_async().await;
+
+        // the local file is longer than the remote one, the download starts from scratch
+        tokio::fs::write(&path, b"hello world and more")
+            .await
+            .unwrap();
+        ResumeState {
+            hash: 44,
+            size: Some(11),
+        }
+        .write(&ResumeState::path(&path))
+        .await
+        .unwrap();
+        let m = server
+            .mock("GET", "/file.bin")
+            .match_header("range", Matcher::Missing)
+            .with_status(200)
+            .with_body("HELLO WORLD")
+            .create_async()
+            .await;
+        let urls = vec![format!("{}/file.bin", server.url())];
+        let size = client
+            .download_urls_resumable(urls, Some(44), Some(11), &path)
+            .await
+            .unwrap();
+        assert_eq!(size, 11);
+        assert_eq!(tokio::fs::read(&path).await.unwrap(), b"HELLO WORLD");
+        m.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn should_fail_over_to_next_host() {
+        let mut server = mockito::Server::new_async().await;
+        let failing = server
+            .mock("GET", "/failing/file.bin")
+            .with_status(500)
+            .create_async()
+            .await;
+        let working = server
+            .mock("GET", "/working/file.bin")
+            .with_status(200)
+            .with_body("hello world")
+            .create_async()
+            .await;
+        let client = Client::new(server.url(), Credentials::anonymous()).unwrap();
+        let urls = vec![
+            format!("{}/failing/file.bin", server.url()),
+            format!("{}/working/file.bin", server.url()),
+        ];
+        let mut buffer = Vec::new();
+        let size = client
+            .download_urls(urls, ByteRange::default(), &mut buffer)
+            .await
+            .unwrap();
+        assert_eq!(size, 11);
+        assert_eq!(buffer, b"hello world");
+        failing.assert_async().await;
+        working.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn should_fail_when_all_hosts_fail() {
+        let mut server = mockito::Server::new_async().await;
+        let failing = server
+            .mock("GET", "/failing/file.bin")
+            .with_status(500)
+            .expect(2)
+            .create_async()
+            .await;
+        let client = Client::new(server.url(), Credentials::anonymous()).unwrap();
+        let urls = vec![
+            format!("{}/failing/file.bin", server.url()),
+            format!("{}/failing/file.bin", server.url()),
+        ];
+        let mut buffer = Vec::new();
+        let error = client
+            .download_urls(urls, ByteRange::default(), &mut buffer)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, crate::Error::Download(_, _)));
+        assert!(buffer.is_empty());
+        failing.assert_async().await;
+
+        let error = client
+            .download_urls(Vec::new(), ByteRange::default(), &mut buffer)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, crate::Error::Download(_, _)));
+    }
+
+    #[tokio::test]
+    async fn should_open_stream_on_working_host() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/failing/file.bin")
+            .with_status(404)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/working/file.bin")
+            .with_status(200)
+            .with_body("hello world")
+            .create_async()
+            .await;
+        let client = Client::new(server.url(), Credentials::anonymous()).unwrap();
+        let urls = vec![
+            format!("{}/failing/file.bin", server.url()),
+            format!("{}/working/file.bin", server.url()),
+        ];
+        let res = client.open_urls(urls, ByteRange::default()).await.unwrap();
+        let content = res
+            .bytes_stream()
+            .map(|chunk| chunk.unwrap())
+            .collect::<Vec<_>>()
+            .await
+            .concat();
+        assert_eq!(content, b"hello world");
+    }
+}
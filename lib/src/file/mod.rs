@@ -5,7 +5,9 @@ use serde::ser::SerializeStruct;
 use crate::entry::EntryBase;
 
 pub mod checksum;
+pub mod copy;
 pub mod delete;
+pub mod download;
 pub mod movefile; // Can't name it "move" as it's a reserved keyword
 pub mod rename;
 pub mod upload;
@@ -103,6 +105,179 @@ impl serde::Serialize for FileIdentifier<'_> {
     }
 }
 
+/// The category of a file, as detected by pCloud from its content type.
+///
+/// It's sent by pCloud as a number, from `0` for [`Category::Uncategorized`]
+/// to `5` for [`Category::Archive`].
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize,
+)]
+#[serde(from = "u8", into = "u8")]
+pub enum Category {
+    /// A file that doesn't belong to any other category (`0`).
+    #[default]
+    Uncategorized,
+    /// A picture (`1`).
+    Image,
+    /// A video (`2`).
+    Video,
+    /// A sound or music file (`3`).
+    Audio,
+    /// A text document, spreadsheet or presentation (`4`).
+    Document,
+    /// A compressed archive (`5`).
+    Archive,
+    /// A category unknown to this version of the library.
+    Other(u8),
+}
+
+impl From<u8> for Category {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Self::Uncategorized,
+            1 => Self::Image,
+            2 => Self::Video,
+            3 => Self::Audio,
+            4 => Self::Document,
+            5 => Self::Archive,
+            other => Self::Other(other),
+        }
+    }
+}
+
+impl From<Category> for u8 {
+    fn from(value: Category) -> Self {
+        match value {
+            Category::Uncategorized => 0,
+            Category::Image => 1,
+            Category::Video => 2,
+            Category::Audio => 3,
+            Category::Document => 4,
+            Category::Archive => 5,
+            Category::Other(other) => other,
+        }
+    }
+}
+
+/// The tags of an audio file, when pCloud could read them.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct AudioTags {
+    /// The artist performing the track.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artist: Option<String>,
+    /// The album the track belongs to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub album: Option<String>,
+    /// The title of the track.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// The music genre of the track.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub genre: Option<String>,
+    /// The track number, sometimes with the total (e.g., `"3/12"`).
+    #[serde(
+        default,
+        rename = "trackno",
+        with = "lenient",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub track: Option<String>,
+}
+
+/// The properties of a video file, when pCloud could read them.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct VideoInfo {
+    /// The duration of the video, in seconds.
+    #[serde(default, with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub duration: Option<f64>,
+    /// The number of frames per second.
+    #[serde(default, with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub fps: Option<f64>,
+    /// The codec of the video stream, like `h264`.
+    #[serde(
+        default,
+        rename = "videocodec",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub video_codec: Option<String>,
+    /// The codec of the audio stream, like `aac`.
+    #[serde(
+        default,
+        rename = "audiocodec",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub audio_codec: Option<String>,
+    /// The bitrate of the video stream, in bits per second.
+    #[serde(
+        default,
+        rename = "videobitrate",
+        with = "lenient",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub video_bitrate: Option<u64>,
+    /// The bitrate of the audio stream, in bits per second.
+    #[serde(
+        default,
+        rename = "audiobitrate",
+        with = "lenient",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub audio_bitrate: Option<u64>,
+    /// The sample rate of the audio stream, in hertz.
+    #[serde(
+        default,
+        rename = "audiosamplerate",
+        with = "lenient",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub audio_sample_rate: Option<u64>,
+    /// The rotation to apply when playing the video, in degrees.
+    #[serde(default, with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub rotate: Option<i32>,
+}
+
+/// Numbers the API returns either as JSON numbers or as strings.
+mod lenient {
+    use std::fmt::Display;
+    use std::str::FromStr;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Text(String),
+        Unsigned(u64),
+        Signed(i64),
+        Float(f64),
+    }
+
+    pub fn serialize<T, S>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        value.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        let text = match Option::<Raw>::deserialize(deserializer)? {
+            None => return Ok(None),
+            Some(Raw::Text(value)) if value.is_empty() => return Ok(None),
+            Some(Raw::Text(value)) => value,
+            Some(Raw::Unsigned(value)) => value.to_string(),
+            Some(Raw::Signed(value)) => value.to_string(),
+            Some(Raw::Float(value)) => value.to_string(),
+        };
+        text.parse().map(Some).map_err(serde::de::Error::custom)
+    }
+}
+
 /// A structure representing a file stored on pCloud.
 ///
 /// Includes metadata such as the file's unique ID, size, content type, and other attributes.
@@ -119,14 +294,42 @@ pub struct File {
     pub file_id: u64,
 
     /// The size of the file in bytes.
-    pub size: Option<usize>,
+    pub size: Option<u64>,
 
     /// A hash of the file content (may be used for caching or deduplication).
-    pub hash: Option<usize>,
+    pub hash: Option<u64>,
 
     /// The MIME type of the file (e.g., `"image/jpeg"`, `"application/pdf"`).
     #[serde(rename = "contenttype")]
     pub content_type: Option<String>,
+
+    /// The category of the file, detected from its content type.
+    #[serde(default)]
+    pub category: Category,
+
+    /// The width of an image or a video, in pixels.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+
+    /// The height of an image or a video, in pixels.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+
+    /// The tags of an audio file.
+    #[serde(flatten)]
+    pub audio: AudioTags,
+
+    /// The properties of a video file.
+    #[serde(flatten)]
+    pub video: VideoInfo,
+
+    /// The ID of the deleted file this one replaced, when it was restored from the trash.
+    #[serde(
+        default,
+        rename = "deletedfileid",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub deleted_file_id: Option<u64>,
 }
 
 impl Eq for File {}
@@ -148,3 +351,60 @@ impl PartialOrd for File {
         Some(self.cmp(other))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Category, File};
+
+    #[test]
+    fn should_deserialize_media_metadata() {
+        let file: File = serde_json::from_str(
+            r#"{
+    "name": "clip.mp4",
+    "created": "Sat, 24 Jul 2021 07:38:41 +0000",
+    "modified": "Sat, 24 Jul 2021 07:38:41 +0000",
+    "isfolder": false,
+    "fileid": 42,
+    "id": "f42",
+    "icon": "video",
+    "thumb": true,
+    "isshared": false,
+    "ismine": true,
+    "isdeleted": true,
+    "deletedfileid": 41,
+    "size": 5000000000,
+    "hash": 17500000000000000000,
+    "contenttype": "video/mp4",
+    "category": 2,
+    "width": 1920,
+    "height": 1080,
+    "duration": "12.5",
+    "fps": 29.97,
+    "videocodec": "h264",
+    "audiocodec": "aac",
+    "videobitrate": "4000000",
+    "audiosamplerate": 44100,
+    "trackno": 3
+}"#,
+        )
+        .unwrap();
+        assert_eq!(file.size, Some(5_000_000_000));
+        assert_eq!(file.hash, Some(17_500_000_000_000_000_000));
+        assert_eq!(file.category, Category::Video);
+        assert_eq!((file.width, file.height), (Some(1920), Some(1080)));
+        assert!(file.base.is_deleted);
+        assert_eq!(file.deleted_file_id, Some(41));
+        assert_eq!(file.video.duration, Some(12.5));
+        assert_eq!(file.video.fps, Some(29.97));
+        assert_eq!(file.video.video_codec.as_deref(), Some("h264"));
+        assert_eq!(file.video.video_bitrate, Some(4_000_000));
+        assert_eq!(file.video.audio_sample_rate, Some(44100));
+        assert_eq!(file.audio.track.as_deref(), Some("3"));
+        assert!(file.audio.artist.is_none());
+
+        let json = serde_json::to_value(&file).unwrap();
+        assert_eq!(json["category"], 2);
+        assert_eq!(json["videocodec"], "h264");
+        assert!(json.get("artist").is_none());
+    }
+}
@@ -51,15 +51,22 @@ impl crate::Client {
         file: impl Into<FileIdentifier<'_>>,
         to_folder: impl Into<FolderIdentifier<'_>>,
     ) -> crate::Result<File> {
-        self.get_request::<FileResponse, _>(
-            "renamefile",
-            FileMoveParams {
-                from: file.into(),
-                to: ToFolderIdentifier(to_folder.into()),
-            },
-        )
-        .await
-        .map(|res| res.metadata)
+        let from = file.into();
+        let cached = self.cache_key(&from);
+        let file = self
+            .get_request::<FileResponse, _>(
+                "renamefile",
+                FileMoveParams {
+                    from,
+                    to: ToFolderIdentifier(to_folder.into()),
+                },
+            )
+            .await?
+            .metadata;
+        if let Some((cache, key)) = cached {
+            cache.file_changed(&key, Some(&file));
+        }
+        Ok(file)
     }
 }
 
@@ -73,8 +80,8 @@ mod tests {
         let mut server = mockito::Server::new_async().await;
         let m = server
             .mock("GET", "/renamefile")
+            .match_header("authorization", "Bearer access-token")
             .match_query(Matcher::AllOf(vec![
-                Matcher::UrlEncoded("access_token".into(), "access-token".into()),
                 Matcher::UrlEncoded("fileid".into(), "42".into()),
                 Matcher::UrlEncoded("topath".into(), "/this/dir/".into()),
             ]))
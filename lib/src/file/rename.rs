@@ -50,15 +50,22 @@ impl crate::Client {
         identifier: impl Into<FileIdentifier<'a>>,
         name: impl Into<Cow<'a, str>>,
     ) -> crate::Result<File> {
-        self.get_request::<FileResponse, _>(
-            "renamefile",
-            FileRenameParams {
-                identifier: identifier.into(),
-                to_name: name.into(),
-            },
-        )
-        .await
-        .map(|res| res.metadata)
+        let identifier = identifier.into();
+        let cached = self.cache_key(&identifier);
+        let file = self
+            .get_request::<FileResponse, _>(
+                "renamefile",
+                FileRenameParams {
+                    identifier,
+                    to_name: name.into(),
+                },
+            )
+            .await?
+            .metadata;
+        if let Some((cache, key)) = cached {
+            cache.file_changed(&key, Some(&file));
+        }
+        Ok(file)
     }
 }
 
@@ -72,8 +79,8 @@ mod tests {
         let mut server = mockito::Server::new_async().await;
         let m = server
             .mock("GET", "/renamefile")
+            .match_header("authorization", "Bearer access-token")
             .match_query(Matcher::AllOf(vec![
-                Matcher::UrlEncoded("access_token".into(), "access-token".into()),
                 Matcher::UrlEncoded("fileid".into(), "42".into()),
                 Matcher::UrlEncoded("toname".into(), "yolo.bin".into()),
             ]))
@@ -1,5 +1,13 @@
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+use futures_core::Stream;
+use futures_util::TryStreamExt;
+
 use crate::folder::FolderIdentifier;
 
+use super::checksum::{ChecksumHasher, Checksums};
 use super::File;
 
 /// Response returned by the `uploadfile` endpoint when uploading multiple files.
@@ -13,6 +21,10 @@ pub struct MultipartFileUploadResponse {
 
     /// Metadata for each uploaded file.
     pub metadata: Vec<File>,
+
+    /// Checksums computed by pCloud for each uploaded file.
+    #[serde(default)]
+    pub checksums: Vec<Checksums>,
 }
 
 /// Builder for uploading multiple files to pCloud.
@@ -21,7 +33,117 @@ pub struct MultipartFileUploadResponse {
 /// either from in-memory data, raw bodies, or asynchronous streams.
 #[derive(Debug, Default)]
 pub struct MultiFileUpload {
-    parts: Vec<reqwest::multipart::Part>,
+    entries: Vec<UploadEntry>,
+}
+
+type ByteStream = Pin<
+    Box<dyn Stream<Item = Result<Bytes, Box<dyn std::error::Error + Send + Sync>>> + Send + Sync>,
+>;
+
+/// Content of an [`UploadEntry`].
+///
+/// Streams are kept as is until the form is built so that they can be inspected.
+enum UploadContent {
+    Body(reqwest::Body),
+    Stream(ByteStream),
+}
+
+impl std::fmt::Debug for UploadContent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Body(body) => f.debug_tuple("Body").field(body).finish(),
+            Self::Stream(_) => f.debug_tuple("Stream").finish_non_exhaustive(),
+        }
+    }
+}
+
+impl From<UploadContent> for reqwest::Body {
+    fn from(value: UploadContent) -> Self {
+        match value {
+            UploadContent::Body(body) => body,
+            UploadContent::Stream(stream) => reqwest::Body::wrap_stream(stream),
+        }
+    }
+}
+
+/// A single file of a [`MultiFileUpload`], kept until the form is built.
+#[derive(Debug)]
+struct UploadEntry {
+    filename: String,
+    length: Option<u64>,
+    content: UploadContent,
+}
+
+impl UploadEntry {
+    /// Duplicates the entry when its content is held in memory.
+    fn try_clone(&self) -> Option<Self> {
+        let UploadContent::Body(ref body) = self.content else {
+            return None;
+        };
+        let content = body.as_bytes()?;
+        Some(Self {
+            filename: self.filename.clone(),
+            length: self.length,
+            content: UploadContent::Body(reqwest::Body::from(content.to_vec())),
+        })
+    }
+
+    /// Feeds the content to `hasher` while it's being sent.
+    ///
+    /// Content held in memory is hashed right away. Fails when the body
+    /// can't be inspected (e.g. a file handle given as a raw body).
+    fn hashed(self, hasher: Arc<Mutex<ChecksumHasher>>) -> crate::Result<Self> {
+        let content = match self.content {
+            UploadContent::Body(body) => {
+                let Some(content) = body.as_bytes() else {
+                    return Err(crate::Error::Upload(std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        format!(
+                            "the content of {} can't be hashed, use a stream entry",
+                            self.filename
+                        ),
+                    )));
+                };
+                lock(&hasher).update(content);
+                UploadContent::Body(body)
+            }
+            UploadContent::Stream(stream) => {
+                UploadContent::Stream(Box::pin(stream.inspect_ok(move |chunk| {
+                    lock(&hasher).update(chunk);
+                })))
+            }
+        };
+        Ok(Self {
+            filename: self.filename,
+            length: self.length,
+            content,
+        })
+    }
+
+    /// Converts the entry into a multipart part.
+    fn into_part(self) -> reqwest::multipart::Part {
+        if let Some(length) = self.length {
+            let mut headers = reqwest::header::HeaderMap::with_capacity(1);
+            let content_length = length.to_string();
+            headers.append(
+                reqwest::header::CONTENT_LENGTH,
+                reqwest::header::HeaderValue::from_str(&content_length)
+                    .expect("content-length must be a valid number"),
+            );
+
+            reqwest::multipart::Part::stream_with_length(self.content, length)
+                .file_name(self.filename)
+                .headers(headers)
+        } else {
+            reqwest::multipart::Part::stream(self.content).file_name(self.filename)
+        }
+    }
+}
+
+fn lock(hasher: &Mutex<ChecksumHasher>) -> std::sync::MutexGuard<'_, ChecksumHasher> {
+    hasher
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
 }
 
 impl MultiFileUpload {
@@ -59,8 +181,13 @@ impl MultiFileUpload {
         S::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
         bytes::Bytes: From<S::Ok>,
     {
-        let body = reqwest::Body::wrap_stream(stream);
-        self.add_body_entry(filename, length, body);
+        self.entries.push(UploadEntry {
+            filename: filename.into(),
+            length,
+            content: UploadContent::Stream(Box::pin(
+                stream.map_ok(Bytes::from).map_err(Into::into),
+            )),
+        });
     }
 
     /// Adds a file from a raw body and returns the updated builder.
@@ -93,33 +220,51 @@ impl MultiFileUpload {
         F: Into<String>,
         B: Into<reqwest::Body>,
     {
-        let part = if let Some(length) = length {
-            let mut headers = reqwest::header::HeaderMap::with_capacity(1);
-            let content_length = length.to_string();
-            headers.append(
-                reqwest::header::CONTENT_LENGTH,
-                reqwest::header::HeaderValue::from_str(&content_length)
-                    .expect("content-length must be a valid number"),
-            );
+        self.entries.push(UploadEntry {
+            filename: filename.into(),
+            length,
+            content: UploadContent::Body(body.into()),
+        });
+    }
 
-            reqwest::multipart::Part::stream_with_length(body, length)
-                .file_name(filename.into())
-                .headers(headers)
-        } else {
-            reqwest::multipart::Part::stream(body).file_name(filename.into())
-        };
+    /// Duplicates the upload so that it can be sent again.
+    ///
+    /// Returns `None` if one of the entries is a stream, which can only be consumed once.
+    pub(crate) fn try_clone(&self) -> Option<Self> {
+        self.entries
+            .iter()
+            .map(UploadEntry::try_clone)
+            .collect::<Option<Vec<_>>>()
+            .map(|entries| Self { entries })
+    }
 
-        self.parts.push(part);
+    /// Wraps every entry so that its checksums are computed while being sent.
+    ///
+    /// Returns the updated upload and, for each entry in order, its hasher.
+    fn hashed(self, base_url: &str) -> crate::Result<(Self, Vec<Arc<Mutex<ChecksumHasher>>>)> {
+        let mut hashers = Vec::with_capacity(self.entries.len());
+        let entries = self
+            .entries
+            .into_iter()
+            .map(|entry| {
+                let hasher = Arc::new(Mutex::new(ChecksumHasher::for_region(base_url)));
+                hashers.push(hasher.clone());
+                entry.hashed(hasher)
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+        Ok((Self { entries }, hashers))
     }
 
-    /// Converts the upload builder into a multipart form.
+    /// Adds the files of the upload builder to a multipart form.
     ///
     /// This method is used internally before sending the request.
-    fn into_form(self) -> reqwest::multipart::Form {
-        self.parts.into_iter().enumerate().fold(
-            reqwest::multipart::Form::default(),
-            |form, (index, part)| form.part(format!("f{index}"), part),
-        )
+    pub(crate) fn into_form(self, form: reqwest::multipart::Form) -> reqwest::multipart::Form {
+        self.entries
+            .into_iter()
+            .enumerate()
+            .fold(form, |form, (index, entry)| {
+                form.part(format!("f{index}"), entry.into_part())
+            })
     }
 }
 
@@ -165,13 +310,80 @@ impl crate::Client {
         parent: impl Into<FolderIdentifier<'_>>,
         files: MultiFileUpload,
     ) -> crate::Result<Vec<File>> {
-        self.post_request_multipart::<MultipartFileUploadResponse, _>(
-            "uploadfile",
-            parent.into(),
-            files.into_form(),
-        )
-        .await
-        .map(|res| res.metadata)
+        let parent = parent.into();
+        let cached = self.cache_key(&parent);
+        let res = self
+            .post_request_multipart::<MultipartFileUploadResponse, _>("uploadfile", parent, files)
+            .await?;
+        if let Some((cache, key)) = cached {
+            cache.content_changed(&key);
+        }
+        Ok(res.metadata)
+    }
+
+    /// Uploads multiple files to a specified folder on pCloud and verifies their integrity.
+    ///
+    /// The content of each file is hashed while being sent and compared with the
+    /// checksums returned by pCloud. The SHA-1 is always compared, alongside the SHA-256
+    /// or the MD5 depending on the region.
+    ///
+    /// Only in-memory bodies and stream entries can be verified.
+    ///
+    /// # Arguments
+    ///
+    /// * `parent` - A value convertible into a [`FolderIdentifier`] representing the destination folder.
+    /// * `files` - A [`MultiFileUpload`] builder containing the files to upload.
+    ///
+    /// # Returns
+    ///
+    /// On success, returns a list of [`File`] metadata for each uploaded file.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`crate::Error::ChecksumMismatch`] if one of the files doesn't match the
+    /// checksums computed by pCloud. The uploaded files are left in place in that case.
+    /// Returns a [`crate::Error::Upload`] if an entry can't be hashed or if pCloud didn't
+    /// return the checksums, or any error returned by [`crate::Client::upload_files`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # async fn example(client: &pcloud::Client) -> Result<(), pcloud::Error> {
+    /// let upload = pcloud::file::upload::MultiFileUpload::default()
+    ///     .with_body_entry("hello.txt", None, "hello world");
+    ///
+    /// let uploaded = client.upload_files_verified("/my-folder", upload).await?;
+    /// println!("Uploaded {} file(s)", uploaded.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn upload_files_verified(
+        &self,
+        parent: impl Into<FolderIdentifier<'_>>,
+        files: MultiFileUpload,
+    ) -> crate::Result<Vec<File>> {
+        let (files, hashers) = files.hashed(&self.base_url)?;
+        let parent = parent.into();
+        let cached = self.cache_key(&parent);
+        let res = self
+            .post_request_multipart::<MultipartFileUploadResponse, _>("uploadfile", parent, files)
+            .await?;
+        if let Some((cache, key)) = cached {
+            cache.content_changed(&key);
+        }
+        for (index, (file, hasher)) in res.metadata.iter().zip(hashers).enumerate() {
+            let expected = res.checksums.get(index).ok_or_else(|| {
+                crate::Error::Upload(std::io::Error::other(format!(
+                    "no checksum returned for {}",
+                    file.base.name
+                )))
+            })?;
+            let actual = lock(&hasher).clone().finalize();
+            expected.verify(&actual).map_err(|mismatch| {
+                crate::Error::ChecksumMismatch(file.base.name.clone(), mismatch)
+            })?;
+        }
+        Ok(res.metadata)
     }
 }
 
@@ -185,10 +397,8 @@ mod tests {
         let mut server = mockito::Server::new_async().await;
         let m_upload = server
             .mock("POST", "/uploadfile")
-            .match_query(Matcher::AllOf(vec![
-                Matcher::UrlEncoded("access_token".into(), "access-token".into()),
-                Matcher::UrlEncoded("folderid".into(), "0".into()),
-            ]))
+            .match_header("authorization", "Bearer access-token")
+            .match_query(Matcher::UrlEncoded("folderid".into(), "0".into()))
             .match_body(Matcher::Any)
             .match_header("accept", "*/*")
             .match_header("user-agent", crate::USER_AGENT)
@@ -241,4 +451,112 @@ mod tests {
         assert_eq!(result.len(), 1);
         m_upload.assert();
     }
+
+    #[tokio::test]
+    async fn multipart_with_credentials_in_form() {
+        let mut server = mockito::Server::new_async().await;
+        let m = server
+            .mock("POST", "/uploadfile")
+            .match_query(Matcher::UrlEncoded("folderid".into(), "0".into()))
+            .match_body(Matcher::Regex(
+                r#"(?s)name="auth"\r\n\r\nthe-token\r\n.*name="f0""#.to_string(),
+            ))
+            .with_status(200)
+            .with_body(r#"{"result": 0, "metadata": [], "checksums": [], "fileids": []}"#)
+            .create_async()
+            .await;
+        let client = Client::new(server.url(), Credentials::authorization("the-token")).unwrap();
+        let files = MultiFileUpload::default().with_body_entry("file.txt", Some(5), "hello");
+        client.upload_files(0, files).await.unwrap();
+        m.assert_async().await;
+    }
+
+    fn verified_response(sha1: &str) -> String {
+        format!(
+            r#"{{
+        "result": 0,
+        "metadata": [
+            {{
+                "name": "hello.txt",
+                "created": "Tue, 09 Aug 2022 13:43:17 +0000",
+                "thumb": false,
+                "modified": "Tue, 09 Aug 2022 13:43:17 +0000",
+                "isfolder": false,
+                "fileid": 42,
+                "path": "/hello.txt",
+                "id": "f42",
+                "isshared": false,
+                "ismine": true,
+                "size": 11,
+                "parentfolderid": 0,
+                "icon": "file"
+            }}
+        ],
+        "checksums": [
+            {{
+                "sha1": "{sha1}",
+                "md5": "5eb63bbbe01eeed093cb22bb8f5acdc3"
+            }}
+        ],
+        "fileids": [42]
+    }}"#
+        )
+    }
+
+    #[tokio::test]
+    async fn should_verify_uploaded_files() {
+        let mut server = mockito::Server::new_async().await;
+        let m = server
+            .mock("POST", "/uploadfile")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_body(verified_response(
+                "2aae6c35c94fcfb415dbe95f408b9ce91ee846ed",
+            ))
+            .create_async()
+            .await;
+        let client = Client::new(server.url(), Credentials::access_token("access-token")).unwrap();
+        let files = MultiFileUpload::default().with_body_entry("hello.txt", None, "hello world");
+        let result = client.upload_files_verified(0, files).await.unwrap();
+        assert_eq!(result.len(), 1);
+        m.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn should_fail_when_uploaded_stream_differs() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/uploadfile")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_body(verified_response(
+                "0000000000000000000000000000000000000000",
+            ))
+            .create_async()
+            .await;
+        let client = Client::new(server.url(), Credentials::access_token("access-token")).unwrap();
+        let stream = futures_util::stream::iter(vec![
+            Ok::<_, std::io::Error>(bytes::Bytes::from_static(b"hello ")),
+            Ok(bytes::Bytes::from_static(b"world")),
+        ]);
+        let files = MultiFileUpload::default().with_stream_entry("hello.txt", Some(11), stream);
+        let error = client.upload_files_verified(0, files).await.unwrap_err();
+        match error {
+            crate::Error::ChecksumMismatch(name, mismatch) => {
+                assert_eq!(name, "hello.txt");
+                assert_eq!(mismatch.algorithm, "sha1");
+                assert_eq!(mismatch.actual, "2aae6c35c94fcfb415dbe95f408b9ce91ee846ed");
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn should_reject_opaque_bodies_when_verifying() {
+        let client = Client::new("http://localhost", Credentials::anonymous()).unwrap();
+        let file = tokio::fs::File::open("./readme.md").await.unwrap();
+        let files = MultiFileUpload::default().with_body_entry("readme.md", None, file);
+        let error = client.upload_files_verified(0, files).await.unwrap_err();
+        assert!(matches!(error, crate::Error::Upload(_)));
+    }
 }
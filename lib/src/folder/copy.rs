@@ -0,0 +1,211 @@
+use super::{Folder, FolderIdentifier, FolderResponse, ToFolderIdentifier};
+
+/// Options for customizing how a folder is copied.
+#[derive(Debug, Default, serde::Serialize)]
+pub struct CopyFolderOptions {
+    /// Whether to fail instead of overwriting the existing files.
+    #[serde(
+        rename = "noover",
+        skip_serializing_if = "crate::request::is_false",
+        serialize_with = "crate::request::serialize_bool"
+    )]
+    no_overwrite: bool,
+
+    /// Whether to skip the existing files instead of overwriting them.
+    #[serde(
+        rename = "skipexisting",
+        skip_serializing_if = "crate::request::is_false",
+        serialize_with = "crate::request::serialize_bool"
+    )]
+    skip_existing: bool,
+
+    /// Whether to copy the content of the folder without the folder itself.
+    #[serde(
+        rename = "copycontentonly",
+        skip_serializing_if = "crate::request::is_false",
+        serialize_with = "crate::request::serialize_bool"
+    )]
+    copy_content_only: bool,
+}
+
+impl CopyFolderOptions {
+    /// Prevents the existing files from being overwritten.
+    ///
+    /// When enabled, the copy fails with [`crate::error::ApiErrorCode::AlreadyExists`].
+    pub fn set_no_overwrite(&mut self, value: bool) {
+        self.no_overwrite = value;
+    }
+
+    /// Prevents the existing files from being overwritten.
+    pub fn with_no_overwrite(mut self, value: bool) -> Self {
+        self.set_no_overwrite(value);
+        self
+    }
+
+    /// Skips the existing files, keeping them untouched.
+    pub fn set_skip_existing(&mut self, value: bool) {
+        self.skip_existing = value;
+    }
+
+    /// Skips the existing files, keeping them untouched.
+    pub fn with_skip_existing(mut self, value: bool) -> Self {
+        self.set_skip_existing(value);
+        self
+    }
+
+    /// Copies the content of the folder directly into the destination folder.
+    pub fn set_copy_content_only(&mut self, value: bool) {
+        self.copy_content_only = value;
+    }
+
+    /// Copies the content of the folder directly into the destination folder.
+    pub fn with_copy_content_only(mut self, value: bool) -> Self {
+        self.set_copy_content_only(value);
+        self
+    }
+}
+
+/// Parameters required to copy a folder into another folder.
+///
+/// This structure is serialized and sent to the `copyfolder` endpoint.
+#[derive(serde::Serialize)]
+struct FolderCopyParams<'a> {
+    /// The folder to copy (by path or folder ID).
+    #[serde(flatten)]
+    from: FolderIdentifier<'a>,
+
+    /// The target folder to copy the folder into.
+    #[serde(flatten)]
+    to: ToFolderIdentifier<'a>,
+
+    #[serde(flatten)]
+    options: CopyFolderOptions,
+}
+
+impl crate::Client {
+    /// Copies a folder and its content into another folder on pCloud.
+    ///
+    /// This is a convenience method that calls [`crate::Client::copy_folder_with_options`] with
+    /// default options, the existing files being overwritten.
+    ///
+    /// # Arguments
+    ///
+    /// * `folder` - A value that can be converted into a [`FolderIdentifier`] (e.g., folder ID or path).
+    /// * `to_folder` - A value that can be converted into a [`FolderIdentifier`] representing the destination folder.
+    ///
+    /// # Returns
+    ///
+    /// On success, returns a [`Folder`] struct containing metadata about the copy.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`crate::Error`] if either folder is not found, or if the API request fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # async fn example(client: &pcloud::Client) -> Result<(), pcloud::Error> {
+    /// let folder = client.copy_folder("/photos", "/backup").await?;
+    /// println!("Copied folder ID: {}", folder.folder_id);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn copy_folder(
+        &self,
+        folder: impl Into<FolderIdentifier<'_>>,
+        to_folder: impl Into<FolderIdentifier<'_>>,
+    ) -> crate::Result<Folder> {
+        self.copy_folder_with_options(folder, to_folder, CopyFolderOptions::default())
+            .await
+    }
+
+    /// Copies a folder and its content into another folder on pCloud with the given options.
+    ///
+    /// The copy is done on the server side by the `copyfolder` endpoint, no content is transferred.
+    ///
+    /// # Arguments
+    ///
+    /// * `folder` - A value that can be converted into a [`FolderIdentifier`] (e.g., folder ID or path).
+    /// * `to_folder` - A value that can be converted into a [`FolderIdentifier`] representing the destination folder.
+    /// * `options` - A [`CopyFolderOptions`] to choose how the existing files are handled.
+    ///
+    /// # Returns
+    ///
+    /// On success, returns a [`Folder`] struct containing metadata about the copy.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`crate::Error`] if either folder is not found, if a file already exists
+    /// while overwriting is disabled, or if the API request fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pcloud::folder::copy::CopyFolderOptions;
+    ///
+    /// # async fn example(client: &pcloud::Client) -> Result<(), pcloud::Error> {
+    /// let options = CopyFolderOptions::default().with_skip_existing(true);
+    /// let folder = client.copy_folder_with_options(12u64, 42u64, options).await?;
+    /// println!("Copied folder name: {}", folder.base.name);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn copy_folder_with_options(
+        &self,
+        folder: impl Into<FolderIdentifier<'_>>,
+        to_folder: impl Into<FolderIdentifier<'_>>,
+        options: CopyFolderOptions,
+    ) -> crate::Result<Folder> {
+        let to_folder = to_folder.into();
+        let cached = self.cache_key(&to_folder);
+        let folder = self
+            .get_request::<FolderResponse, _>(
+                "copyfolder",
+                FolderCopyParams {
+                    from: folder.into(),
+                    to: ToFolderIdentifier(to_folder),
+                    options,
+                },
+            )
+            .await?
+            .metadata;
+        if let Some((cache, key)) = cached {
+            cache.content_changed(&key);
+        }
+        Ok(folder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CopyFolderOptions;
+    use crate::testing::{folder, response};
+    use crate::{Client, Credentials};
+    use mockito::Matcher;
+
+    #[tokio::test]
+    async fn success() {
+        let mut server = mockito::Server::new_async().await;
+        let m = server
+            .mock("GET", "/copyfolder")
+            .match_header("authorization", "Bearer access-token")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("folderid".into(), "42".into()),
+                Matcher::UrlEncoded("tofolderid".into(), "12".into()),
+                Matcher::UrlEncoded("skipexisting".into(), "1".into()),
+            ]))
+            .with_status(200)
+            .with_body(response(folder(43, "photos", None)))
+            .create_async()
+            .await;
+        let client = Client::new(server.url(), Credentials::access_token("access-token")).unwrap();
+        let options = CopyFolderOptions::default().with_skip_existing(true);
+        let result = client
+            .copy_folder_with_options(42, 12, options)
+            .await
+            .unwrap();
+        assert_eq!(result.folder_id, 43);
+        assert_eq!(result.base.name, "photos");
+        m.assert_async().await;
+    }
+}
@@ -1,6 +1,8 @@
 use std::borrow::Cow;
 
+use super::list::ListFolderOptions;
 use super::{Folder, FolderIdentifier, FolderResponse};
+use crate::error::ApiErrorCode;
 
 /// Parameters used for folder creation requests.
 ///
@@ -49,13 +51,20 @@ impl crate::Client {
         parent: impl Into<FolderIdentifier<'a>>,
         name: impl Into<Cow<'a, str>>,
     ) -> crate::Result<Folder> {
+        let parent = parent.into();
+        let cached = self.cache_key(&parent);
         let params = Params {
-            parent: parent.into(),
+            parent,
             name: name.into(),
         };
-        self.get_request::<FolderResponse, _>("createfolder", params)
-            .await
-            .map(|res| res.metadata)
+        let folder = self
+            .get_request::<FolderResponse, _>("createfolder", params)
+            .await?
+            .metadata;
+        if let Some((cache, key)) = cached {
+            cache.content_changed(&key);
+        }
+        Ok(folder)
     }
 }
 
@@ -93,18 +102,89 @@ impl crate::Client {
         parent: impl Into<FolderIdentifier<'a>>,
         name: impl Into<Cow<'a, str>>,
     ) -> crate::Result<Folder> {
+        let parent = parent.into();
+        let cached = self.cache_key(&parent);
         let params = Params {
-            parent: parent.into(),
+            parent,
             name: name.into(),
         };
-        self.get_request::<FolderResponse, _>("createfolderifnotexists", params)
-            .await
-            .map(|res| res.metadata)
+        let folder = self
+            .get_request::<FolderResponse, _>("createfolderifnotexists", params)
+            .await?
+            .metadata;
+        if let Some((cache, key)) = cached {
+            cache.content_changed(&key);
+        }
+        Ok(folder)
+    }
+
+    /// Creates a folder and all its missing parents, like `mkdir -p`.
+    ///
+    /// Every component of the path is created with [`crate::Client::create_folder_if_not_exists`],
+    /// starting from the root folder. When another client creates the same component at the
+    /// same time, the existing folder is looked up and used instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The absolute path of the folder to create (e.g. `"/a/b/c"`).
+    ///
+    /// # Returns
+    ///
+    /// On success, returns the [`Folder`] at the end of the path.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`crate::Error`] if one of the components can't be created, for example
+    /// when a file with the same name already exists.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # async fn example(client: &pcloud::Client) -> Result<(), pcloud::Error> {
+    /// let folder = client.create_folder_all("/backups/2024/january").await?;
+    /// println!("Folder ID: {}", folder.folder_id);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_folder_all(&self, path: &str) -> crate::Result<Folder> {
+        let mut current: Option<Folder> = None;
+        let mut current_path = String::new();
+        for name in path.split('/').filter(|name| !name.is_empty()) {
+            current_path.push('/');
+            current_path.push_str(name);
+            let parent_id = current
+                .as_ref()
+                .map_or(super::ROOT, |folder| folder.folder_id);
+            let folder = match self.create_folder_if_not_exists(parent_id, name).await {
+                Ok(folder) => folder,
+                Err(crate::Error::Protocol(ApiErrorCode::AlreadyExists, _)) => {
+                    tracing::debug!(path = current_path, "folder created concurrently");
+                    self.list_folder_with_options(
+                        current_path.as_str(),
+                        ListFolderOptions::default().with_no_files(),
+                    )
+                    .await?
+                }
+                Err(err) => return Err(err),
+            };
+            current = Some(folder);
+        }
+        match current {
+            Some(folder) => Ok(folder),
+            None => {
+                self.list_folder_with_options(
+                    super::ROOT,
+                    ListFolderOptions::default().with_no_files(),
+                )
+                .await
+            }
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::testing::{folder, response, with_fields};
     use crate::{Client, Credentials};
     use mockito::Matcher;
 
@@ -113,8 +193,8 @@ mod tests {
         let mut server = mockito::Server::new_async().await;
         let m = server
             .mock("GET", "/createfolder")
+            .match_header("authorization", "Bearer access-token")
             .match_query(Matcher::AllOf(vec![
-                Matcher::UrlEncoded("access_token".into(), "access-token".into()),
                 Matcher::UrlEncoded("folderid".into(), "0".into()),
                 Matcher::UrlEncoded("name".into(), "testing".into()),
             ]))
@@ -145,13 +225,63 @@ mod tests {
         m.assert();
     }
 
+    fn folder_response(id: u64, parent: u64, name: &str) -> String {
+        response(with_fields(
+            folder(id, name, None),
+            serde_json::json!({ "path": format!("/{name}"), "parentfolderid": parent }),
+        ))
+    }
+
+    #[tokio::test]
+    async fn should_create_all_components() {
+        let mut server = mockito::Server::new_async().await;
+        let mut mocks = Vec::new();
+        for (parent, name, body) in [
+            ("0", "a", folder_response(1, 0, "a")),
+            (
+                "1",
+                "b",
+                r#"{ "result": 2004, "error": "File or folder alredy exists." }"#.to_string(),
+            ),
+            ("2", "c", folder_response(3, 2, "c")),
+        ] {
+            let mock = server
+                .mock("GET", "/createfolderifnotexists")
+                .match_query(Matcher::AllOf(vec![
+                    Matcher::UrlEncoded("folderid".into(), parent.into()),
+                    Matcher::UrlEncoded("name".into(), name.into()),
+                ]))
+                .with_status(200)
+                .with_body(body)
+                .expect(1)
+                .create_async()
+                .await;
+            mocks.push(mock);
+        }
+        let m_list = server
+            .mock("GET", "/listfolder")
+            .match_query(Matcher::UrlEncoded("path".into(), "/a/b".into()))
+            .with_status(200)
+            .with_body(folder_response(2, 1, "b"))
+            .expect(1)
+            .create_async()
+            .await;
+        let client = Client::new(server.url(), Credentials::access_token("access-token")).unwrap();
+        let result = client.create_folder_all("/a//b/c/").await.unwrap();
+        assert_eq!(result.folder_id, 3);
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+        m_list.assert_async().await;
+    }
+
     #[tokio::test]
     async fn error() {
         let mut server = mockito::Server::new_async().await;
         let m = server
             .mock("GET", "/createfolder")
+            .match_header("authorization", "Bearer access-token")
             .match_query(Matcher::AllOf(vec![
-                Matcher::UrlEncoded("access_token".into(), "access-token".into()),
                 Matcher::UrlEncoded("folderid".into(), "0".into()),
                 Matcher::UrlEncoded("name".into(), "testing".into()),
             ]))
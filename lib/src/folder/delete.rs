@@ -46,9 +46,16 @@ impl crate::Client {
         &self,
         identifier: impl Into<FolderIdentifier<'a>>,
     ) -> crate::Result<Folder> {
-        self.get_request::<FolderResponse, _>("deletefolder", identifier.into())
-            .await
-            .map(|res| res.metadata)
+        let identifier = identifier.into();
+        let cached = self.cache_key(&identifier);
+        let folder = self
+            .get_request::<FolderResponse, _>("deletefolder", identifier)
+            .await?
+            .metadata;
+        if let Some((cache, key)) = cached {
+            cache.folder_changed(&key, Some(&folder));
+        }
+        Ok(folder)
     }
 }
 
@@ -87,8 +94,14 @@ impl crate::Client {
         &self,
         identifier: impl Into<FolderIdentifier<'a>>,
     ) -> crate::Result<RecursivePayload> {
-        self.get_request("deletefolderrecursive", identifier.into())
-            .await
+        let payload = self
+            .get_request("deletefolderrecursive", identifier.into())
+            .await?;
+        // the deleted descendants can't be known, everything is dropped
+        if let Some(cache) = self.metadata_cache() {
+            cache.clear();
+        }
+        Ok(payload)
     }
 }
 
@@ -102,10 +115,8 @@ mod http_tests {
         let mut server = mockito::Server::new_async().await;
         let m = server
             .mock("GET", "/deletefolder")
-            .match_query(Matcher::AllOf(vec![
-                Matcher::UrlEncoded("access_token".into(), "access-token".into()),
-                Matcher::UrlEncoded("folderid".into(), "42".into()),
-            ]))
+            .match_header("authorization", "Bearer access-token")
+            .match_query(Matcher::UrlEncoded("folderid".into(), "42".into()))
             .with_status(200)
             .with_body(
                 r#"{
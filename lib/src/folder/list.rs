@@ -60,6 +60,11 @@ impl ListFolderOptions {
         self.no_shares = true;
         self
     }
+
+    /// Returns `true` when no option is enabled, the only listings kept in the metadata cache.
+    pub(crate) fn is_default(&self) -> bool {
+        !(self.recursive || self.show_deleted || self.no_files || self.no_shares)
+    }
 }
 
 /// Internal parameter bundle for listing folders.
@@ -143,13 +148,26 @@ impl crate::Client {
         identifier: impl Into<FolderIdentifier<'_>>,
         options: ListFolderOptions,
     ) -> crate::Result<Folder> {
+        let identifier = identifier.into();
+        let cached = self.cache_key(&identifier).filter(|_| options.is_default());
+        if let Some(folder) = cached
+            .as_ref()
+            .and_then(|(cache, key)| cache.get_folder(key))
+        {
+            return Ok(folder);
+        }
         let params = Params {
-            identifier: identifier.into(),
+            identifier,
             options,
         };
-        self.get_request::<FolderResponse, _>("listfolder", params)
-            .await
-            .map(|res| res.metadata)
+        let folder = self
+            .get_request::<FolderResponse, _>("listfolder", params)
+            .await?
+            .metadata;
+        if let Some((cache, key)) = cached {
+            cache.insert_folder(&key, &folder);
+        }
+        Ok(folder)
     }
 }
 
@@ -163,8 +181,8 @@ mod tests {
         let mut server = mockito::Server::new_async().await;
         let m = server
             .mock("GET", "/listfolder")
+            .match_header("authorization", "Bearer access-token")
             .match_query(Matcher::AllOf(vec![
-                Matcher::UrlEncoded("access_token".into(), "access-token".into()),
                 Matcher::UrlEncoded("folderid".into(), "0".into()),
                 Matcher::UrlEncoded("recursive".into(), "1".into()),
                 Matcher::UrlEncoded("showdeleted".into(), "1".into()),
@@ -209,10 +227,8 @@ mod tests {
         let mut server = mockito::Server::new_async().await;
         let m = server
             .mock("GET", "/listfolder")
-            .match_query(Matcher::AllOf(vec![
-                Matcher::UrlEncoded("access_token".into(), "access-token".into()),
-                Matcher::UrlEncoded("folderid".into(), "0".into()),
-            ]))
+            .match_header("authorization", "Bearer access-token")
+            .match_query(Matcher::UrlEncoded("folderid".into(), "0".into()))
             .with_status(200)
             .with_body(
                 r#"{
@@ -245,10 +261,8 @@ mod tests {
         let mut server = mockito::Server::new_async().await;
         let m = server
             .mock("GET", "/listfolder")
-            .match_query(Matcher::AllOf(vec![
-                Matcher::UrlEncoded("access_token".into(), "access-token".into()),
-                Matcher::UrlEncoded("folderid".into(), "0".into()),
-            ]))
+            .match_header("authorization", "Bearer access-token")
+            .match_query(Matcher::UrlEncoded("folderid".into(), "0".into()))
             .with_status(200)
             .with_body(r#"{ "result": 1020, "error": "something went wrong" }"#)
             .create();
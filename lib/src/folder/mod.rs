@@ -2,11 +2,14 @@ use std::{borrow::Cow, cmp::Ordering};
 
 use serde::ser::SerializeStruct;
 
+pub mod copy;
 pub mod create;
 pub mod delete;
 pub mod list;
 pub mod movefolder;
 pub mod rename;
+pub mod upload;
+pub mod walk;
 
 pub const ROOT: u64 = 0;
 
@@ -18,7 +21,7 @@ pub(crate) struct FolderResponse {
 }
 
 /// Enumeration for identifying a folder by either its path or folder ID.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum FolderIdentifier<'a> {
     /// A folder is identified by its path.
     Path(Cow<'a, str>),
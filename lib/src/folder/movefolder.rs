@@ -47,15 +47,22 @@ impl crate::Client {
         folder: impl Into<FolderIdentifier<'_>>,
         to_folder: impl Into<FolderIdentifier<'_>>,
     ) -> crate::Result<Folder> {
-        self.get_request::<FolderResponse, _>(
-            "renamefolder",
-            FolderMoveParams {
-                from: folder.into(),
-                to: ToFolderIdentifier(to_folder.into()),
-            },
-        )
-        .await
-        .map(|res| res.metadata)
+        let from = folder.into();
+        let cached = self.cache_key(&from);
+        let folder = self
+            .get_request::<FolderResponse, _>(
+                "renamefolder",
+                FolderMoveParams {
+                    from,
+                    to: ToFolderIdentifier(to_folder.into()),
+                },
+            )
+            .await?
+            .metadata;
+        if let Some((cache, key)) = cached {
+            cache.folder_changed(&key, Some(&folder));
+        }
+        Ok(folder)
     }
 }
 
@@ -69,8 +76,8 @@ mod tests {
         let mut server = mockito::Server::new_async().await;
         let m = server
             .mock("GET", "/renamefolder")
+            .match_header("authorization", "Bearer access-token")
             .match_query(Matcher::AllOf(vec![
-                Matcher::UrlEncoded("access_token".into(), "access-token".into()),
                 Matcher::UrlEncoded("folderid".into(), "42".into()),
                 Matcher::UrlEncoded("topath".into(), "/this/dir/".into()),
             ]))
@@ -24,7 +24,7 @@ impl crate::Client {
     /// # Arguments
     ///
     /// * `identifier` - The identifier for the folder to be renamed, which can be provided either
-    ///                  by folder ID or path.
+    ///   by folder ID or path.
     /// * `name` - The new name for the folder.
     ///
     /// # Returns
@@ -50,15 +50,22 @@ impl crate::Client {
         identifier: impl Into<FolderIdentifier<'a>>,
         name: impl Into<Cow<'a, str>>,
     ) -> crate::Result<Folder> {
-        self.get_request::<FolderResponse, _>(
-            "renamefolder",
-            FolderRenameParams {
-                identifier: identifier.into(),
-                to_name: name.into(),
-            },
-        )
-        .await
-        .map(|res| res.metadata)
+        let identifier = identifier.into();
+        let cached = self.cache_key(&identifier);
+        let folder = self
+            .get_request::<FolderResponse, _>(
+                "renamefolder",
+                FolderRenameParams {
+                    identifier,
+                    to_name: name.into(),
+                },
+            )
+            .await?
+            .metadata;
+        if let Some((cache, key)) = cached {
+            cache.folder_changed(&key, Some(&folder));
+        }
+        Ok(folder)
     }
 }
 
@@ -72,8 +79,8 @@ mod tests {
         let mut server = mockito::Server::new_async().await;
         let m = server
             .mock("GET", "/renamefolder")
+            .match_header("authorization", "Bearer access-token")
             .match_query(Matcher::AllOf(vec![
-                Matcher::UrlEncoded("access_token".into(), "access-token".into()),
                 Matcher::UrlEncoded("folderid".into(), "42".into()),
                 Matcher::UrlEncoded("toname".into(), "yolo".into()),
             ]))
@@ -0,0 +1,471 @@
+use std::collections::{HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+
+use bytes::{Bytes, BytesMut};
+use futures_core::Stream;
+use futures_util::StreamExt;
+use tokio::io::AsyncReadExt;
+
+use super::list::ListFolderOptions;
+use super::FolderIdentifier;
+use crate::file::upload::MultiFileUpload;
+use crate::file::File;
+
+/// Size of the chunks read from the local files.
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+/// How symbolic links are handled when uploading a directory.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SymlinkPolicy {
+    /// Symbolic links are not uploaded and reported as skipped.
+    #[default]
+    Skip,
+    /// Symbolic links are followed, a directory already visited is skipped to avoid cycles.
+    Follow,
+}
+
+/// Options for [`crate::Client::upload_dir`].
+#[derive(Clone, Debug)]
+pub struct UploadDirOptions {
+    concurrency: usize,
+    symlinks: SymlinkPolicy,
+    verify: bool,
+}
+
+impl Default for UploadDirOptions {
+    /// Uploads 4 files at a time, skips symbolic links and doesn't verify the checksums.
+    fn default() -> Self {
+        Self {
+            concurrency: 4,
+            symlinks: SymlinkPolicy::default(),
+            verify: false,
+        }
+    }
+}
+
+impl UploadDirOptions {
+    /// Sets the maximum number of files uploaded at the same time.
+    pub fn set_concurrency(&mut self, value: usize) {
+        self.concurrency = value.max(1);
+    }
+
+    /// Sets the maximum number of files uploaded at the same time and returns the updated options.
+    pub fn with_concurrency(mut self, value: usize) -> Self {
+        self.set_concurrency(value);
+        self
+    }
+
+    /// Sets how symbolic links are handled.
+    pub fn set_symlinks(&mut self, value: SymlinkPolicy) {
+        self.symlinks = value;
+    }
+
+    /// Sets how symbolic links are handled and returns the updated options.
+    pub fn with_symlinks(mut self, value: SymlinkPolicy) -> Self {
+        self.set_symlinks(value);
+        self
+    }
+
+    /// Enables or disables the verification of the checksums of every uploaded file.
+    ///
+    /// See [`crate::Client::upload_files_verified`].
+    pub fn set_verify(&mut self, value: bool) {
+        self.verify = value;
+    }
+
+    /// Enables or disables the verification of the checksums and returns the updated options.
+    pub fn with_verify(mut self, value: bool) -> Self {
+        self.set_verify(value);
+        self
+    }
+}
+
+/// Reason why a local entry wasn't uploaded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The entry is a symbolic link and [`SymlinkPolicy::Skip`] is used.
+    Symlink,
+    /// The directory was already uploaded through another symbolic link.
+    AlreadyVisited,
+    /// The name of the entry isn't valid UTF-8.
+    InvalidName,
+    /// The entry is neither a file nor a directory (socket, device...).
+    Unsupported,
+}
+
+/// Outcome of the upload of a single local file.
+#[derive(Debug)]
+pub struct FileUploadResult {
+    /// Path of the local file.
+    pub local_path: PathBuf,
+    /// The uploaded file, or the reason of the failure.
+    pub result: crate::Result<File>,
+}
+
+/// Report returned by [`crate::Client::upload_dir`].
+#[derive(Debug, Default)]
+pub struct UploadDirReport {
+    /// The result of every file upload, in no particular order.
+    pub files: Vec<FileUploadResult>,
+    /// The local entries that were not uploaded.
+    pub skipped: Vec<(PathBuf, SkipReason)>,
+}
+
+impl UploadDirReport {
+    /// Iterates over the successfully uploaded files.
+    pub fn uploaded(&self) -> impl Iterator<Item = &File> {
+        self.files
+            .iter()
+            .filter_map(|item| item.result.as_ref().ok())
+    }
+
+    /// Iterates over the files that failed to upload, with their error.
+    pub fn failed(&self) -> impl Iterator<Item = (&Path, &crate::Error)> {
+        self.files.iter().filter_map(|item| {
+            item.result
+                .as_ref()
+                .err()
+                .map(|err| (item.local_path.as_path(), err))
+        })
+    }
+
+    /// Returns `true` when every file has been uploaded.
+    pub fn is_success(&self) -> bool {
+        self.files.iter().all(|item| item.result.is_ok())
+    }
+}
+
+/// A local file waiting to be uploaded.
+struct UploadJob {
+    local_path: PathBuf,
+    folder_id: u64,
+    name: String,
+    length: u64,
+}
+
+/// Reads a local file as a stream of chunks.
+fn file_stream(file: tokio::fs::File) -> impl Stream<Item = std::io::Result<Bytes>> + Send + Sync {
+    futures_util::stream::try_unfold(file, |mut file| async move {
+        let mut buffer = BytesMut::with_capacity(READ_CHUNK_SIZE);
+        let read = file.read_buf(&mut buffer).await?;
+        Ok((read > 0).then(|| (buffer.freeze(), file)))
+    })
+}
+
+fn io_error(path: &Path, err: std::io::Error) -> crate::Error {
+    crate::Error::Upload(std::io::Error::new(
+        err.kind(),
+        format!("unable to read {}: {err}", path.display()),
+    ))
+}
+
+impl crate::Client {
+    /// Resolves the folder ID of an identifier.
+    async fn resolve_folder_id(&self, identifier: FolderIdentifier<'_>) -> crate::Result<u64> {
+        match identifier {
+            FolderIdentifier::FolderId(id) => Ok(id),
+            path => self
+                .list_folder_with_options(path, ListFolderOptions::default().with_no_files())
+                .await
+                .map(|folder| folder.folder_id),
+        }
+    }
+
+    /// Walks the local directory, creates the remote folders and lists the files to upload.
+    async fn prepare_upload_dir(
+        &self,
+        root: &Path,
+        root_id: u64,
+        options: &UploadDirOptions,
+        report: &mut UploadDirReport,
+    ) -> crate::Result<Vec<UploadJob>> {
+        let mut jobs = Vec::new();
+        let mut visited = HashSet::new();
+        visited.insert(
+            tokio::fs::canonicalize(root)
+                .await
+                .map_err(|err| io_error(root, err))?,
+        );
+        let mut queue = VecDeque::from([(root.to_path_buf(), root_id)]);
+        while let Some((dir, folder_id)) = queue.pop_front() {
+            let mut entries = tokio::fs::read_dir(&dir)
+                .await
+                .map_err(|err| io_error(&dir, err))?;
+            while let Some(entry) = entries
+                .next_entry()
+                .await
+                .map_err(|err| io_error(&dir, err))?
+            {
+                let path = entry.path();
+                let file_type = entry
+                    .file_type()
+                    .await
+                    .map_err(|err| io_error(&path, err))?;
+                let metadata = if file_type.is_symlink() {
+                    if options.symlinks == SymlinkPolicy::Skip {
+                        report.skipped.push((path, SkipReason::Symlink));
+                        continue;
+                    }
+                    match tokio::fs::metadata(&path).await {
+                        Ok(metadata) => metadata,
+                        Err(err) => {
+                            let result = Err(io_error(&path, err));
+                            report.files.push(FileUploadResult {
+                                local_path: path,
+                                result,
+                            });
+                            continue;
+                        }
+                    }
+                } else {
+                    entry.metadata().await.map_err(|err| io_error(&path, err))?
+                };
+                let Some(name) = entry.file_name().to_str().map(String::from) else {
+                    report.skipped.push((path, SkipReason::InvalidName));
+                    continue;
+                };
+                if metadata.is_dir() {
+                    let canonical = tokio::fs::canonicalize(&path)
+                        .await
+                        .map_err(|err| io_error(&path, err))?;
+                    if !visited.insert(canonical) {
+                        report.skipped.push((path, SkipReason::AlreadyVisited));
+                        continue;
+                    }
+                    let folder = self.create_folder_if_not_exists(folder_id, name).await?;
+                    queue.push_back((path, folder.folder_id));
+                } else if metadata.is_file() {
+                    jobs.push(UploadJob {
+                        local_path: path,
+                        folder_id,
+                        name,
+                        length: metadata.len(),
+                    });
+                } else {
+                    report.skipped.push((path, SkipReason::Unsupported));
+                }
+            }
+        }
+        Ok(jobs)
+    }
+
+    /// Uploads a single local file.
+    async fn upload_dir_file(&self, job: &UploadJob, verify: bool) -> crate::Result<File> {
+        let file = tokio::fs::File::open(&job.local_path)
+            .await
+            .map_err(|err| io_error(&job.local_path, err))?;
+        let files = MultiFileUpload::default().with_stream_entry(
+            job.name.as_str(),
+            Some(job.length),
+            file_stream(file),
+        );
+        let uploaded = if verify {
+            self.upload_files_verified(job.folder_id, files).await?
+        } else {
+            self.upload_files(job.folder_id, files).await?
+        };
+        uploaded.into_iter().next().ok_or_else(|| {
+            crate::Error::Upload(std::io::Error::other(format!(
+                "no metadata returned for {}",
+                job.local_path.display()
+            )))
+        })
+    }
+
+    /// Uploads a local directory and all its content in a folder on pCloud.
+    ///
+    /// The content of `local_path` is mirrored inside `parent`: the subdirectories are
+    /// created with [`crate::Client::create_folder_if_not_exists`] and the files are
+    /// uploaded with [`crate::Client::upload_files`], several at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `local_path` - The local directory to upload.
+    /// * `parent` - A value convertible into a [`FolderIdentifier`] representing the destination folder.
+    /// * `options` - An [`UploadDirOptions`] to configure the concurrency, symbolic links and verification.
+    ///
+    /// # Returns
+    ///
+    /// An [`UploadDirReport`] with the result of every file and the skipped entries.
+    /// A file failing to upload doesn't stop the other ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`crate::Error`] if a local directory can't be read or if a folder
+    /// can't be created on pCloud, in which case no file is uploaded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pcloud::folder::upload::UploadDirOptions;
+    ///
+    /// # async fn example(client: &pcloud::Client) -> Result<(), pcloud::Error> {
+    /// let options = UploadDirOptions::default().with_concurrency(8);
+    /// let report = client.upload_dir("./photos", "/Photos", options).await?;
+    /// for (path, error) in report.failed() {
+    ///     eprintln!("unable to upload {}: {error}", path.display());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn upload_dir(
+        &self,
+        local_path: impl AsRef<Path>,
+        parent: impl Into<FolderIdentifier<'_>>,
+        options: UploadDirOptions,
+    ) -> crate::Result<UploadDirReport> {
+        let root_id = self.resolve_folder_id(parent.into()).await?;
+        let mut report = UploadDirReport::default();
+        let jobs = self
+            .prepare_upload_dir(local_path.as_ref(), root_id, &options, &mut report)
+            .await?;
+        let verify = options.verify;
+        let results = futures_util::stream::iter(jobs.iter())
+            .map(|job| async move {
+                let result = self.upload_dir_file(job, verify).await;
+                FileUploadResult {
+                    local_path: job.local_path.clone(),
+                    result,
+                }
+            })
+            .buffer_unordered(options.concurrency)
+            .collect::<Vec<_>>()
+            .await;
+        report.files.extend(results);
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SkipReason, UploadDirOptions};
+    use crate::testing::TempDir;
+    use crate::{Client, Credentials};
+    use mockito::Matcher;
+
+    const FILE_RESPONSE: &str = r#"{
+    "result": 0,
+    "metadata": [
+        {
+            "name": "file.txt",
+            "created": "Tue, 09 Aug 2022 13:43:17 +0000",
+            "thumb": false,
+            "modified": "Tue, 09 Aug 2022 13:43:17 +0000",
+            "isfolder": false,
+            "fileid": 42,
+            "path": "/file.txt",
+            "id": "f42",
+            "isshared": false,
+            "ismine": true,
+            "size": 5,
+            "parentfolderid": 0,
+            "icon": "file"
+        }
+    ],
+    "fileids": [42]
+}"#;
+
+    #[tokio::test]
+    async fn should_mirror_local_tree() {
+        let dir = TempDir::new("upload-dir");
+        tokio::fs::create_dir_all(dir.join("sub")).await.unwrap();
+        tokio::fs::write(dir.join("root.txt"), b"hello")
+            .await
+            .unwrap();
+        tokio::fs::write(dir.join("sub/child.txt"), b"world")
+            .await
+            .unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(dir.join("sub"), dir.join("link")).unwrap();
+
+        let mut server = mockito::Server::new_async().await;
+        let m_folder = server
+            .mock("GET", "/createfolderifnotexists")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("folderid".into(), "12".into()),
+                Matcher::UrlEncoded("name".into(), "sub".into()),
+            ]))
+            .with_status(200)
+            .with_body(
+                r#"{
+    "result": 0,
+    "metadata": {
+        "path": "/sub",
+        "name": "sub",
+        "created": "Fri, 23 Jul 2021 19:39:09 +0000",
+        "ismine": true,
+        "thumb": false,
+        "modified": "Fri, 23 Jul 2021 19:39:09 +0000",
+        "id": "d13",
+        "isshared": false,
+        "icon": "folder",
+        "isfolder": true,
+        "parentfolderid": 12,
+        "folderid": 13
+    }
+}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let m_root = server
+            .mock("POST", "/uploadfile")
+            .match_query(Matcher::UrlEncoded("folderid".into(), "12".into()))
+            .with_status(200)
+            .with_body(FILE_RESPONSE)
+            .expect(1)
+            .create_async()
+            .await;
+        let m_sub = server
+            .mock("POST", "/uploadfile")
+            .match_query(Matcher::UrlEncoded("folderid".into(), "13".into()))
+            .with_status(200)
+            .with_body(FILE_RESPONSE)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = Client::new(server.url(), Credentials::access_token("access-token")).unwrap();
+        let report = client
+            .upload_dir(&dir, 12, UploadDirOptions::default())
+            .await
+            .unwrap();
+        assert!(report.is_success());
+        assert_eq!(report.files.len(), 2);
+        #[cfg(unix)]
+        assert_eq!(
+            report.skipped,
+            vec![(dir.join("link"), SkipReason::Symlink)]
+        );
+        m_folder.assert_async().await;
+        m_root.assert_async().await;
+        m_sub.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn should_report_failed_files() {
+        let dir = TempDir::new("upload-fail");
+        tokio::fs::write(dir.join("file.txt"), b"hello")
+            .await
+            .unwrap();
+
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/uploadfile")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_body(r#"{ "result": 2008, "error": "User is over quota." }"#)
+            .create_async()
+            .await;
+
+        let client = Client::new(server.url(), Credentials::access_token("access-token")).unwrap();
+        let report = client
+            .upload_dir(&dir, 0, UploadDirOptions::default())
+            .await
+            .unwrap();
+        assert!(!report.is_success());
+        let failed = report.failed().collect::<Vec<_>>();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].0, dir.join("file.txt"));
+    }
+}
@@ -0,0 +1,421 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use futures_core::Stream;
+
+use super::list::ListFolderOptions;
+use super::FolderIdentifier;
+use crate::entry::Entry;
+
+/// Path of an entry relative to the root of a walk, like `photos/2024/beach.jpg`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RemotePath(String);
+
+impl RemotePath {
+    /// Returns the path as a string, segments being separated by `/`.
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    /// Returns `true` for the root of the walk.
+    pub fn is_root(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterates over the names composing the path.
+    pub fn segments(&self) -> impl Iterator<Item = &str> {
+        self.0.split('/').filter(|segment| !segment.is_empty())
+    }
+
+    /// Returns the number of segments of the path, `1` for the direct children of the root.
+    pub fn depth(&self) -> usize {
+        self.segments().count()
+    }
+
+    /// Returns the last segment of the path.
+    pub fn name(&self) -> Option<&str> {
+        self.segments().last()
+    }
+
+    /// Creates the path of a child entry.
+    pub fn join(&self, name: &str) -> Self {
+        if self.is_root() {
+            Self(name.to_string())
+        } else {
+            Self(format!("{}/{name}", self.0))
+        }
+    }
+}
+
+impl std::fmt::Display for RemotePath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl AsRef<str> for RemotePath {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+/// How the folders are listed when walking a tree.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WalkStrategy {
+    /// Lists every folder when reached, only fetching what's consumed.
+    #[default]
+    BreadthFirst,
+    /// Fetches the whole tree at once with a single recursive `listfolder` call.
+    ///
+    /// Faster on large trees that are walked entirely, but the whole tree is held in memory.
+    Recursive,
+}
+
+/// Kind of entries returned by a walk.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WalkFilter {
+    /// Both files and folders are returned.
+    #[default]
+    All,
+    /// Only files are returned, folders are still traversed.
+    FilesOnly,
+    /// Only folders are returned.
+    FoldersOnly,
+}
+
+impl WalkFilter {
+    fn accepts(&self, entry: &Entry) -> bool {
+        match self {
+            Self::All => true,
+            Self::FilesOnly => entry.is_file(),
+            Self::FoldersOnly => entry.is_folder(),
+        }
+    }
+}
+
+type PrunePredicate = Arc<dyn Fn(&RemotePath, &Entry) -> bool + Send + Sync>;
+
+/// Options for [`crate::Client::walk`].
+#[derive(Clone, Default)]
+pub struct WalkOptions {
+    strategy: WalkStrategy,
+    max_depth: Option<usize>,
+    filter: WalkFilter,
+    prune: Option<PrunePredicate>,
+}
+
+impl std::fmt::Debug for WalkOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WalkOptions")
+            .field("strategy", &self.strategy)
+            .field("max_depth", &self.max_depth)
+            .field("filter", &self.filter)
+            .field("prune", &self.prune.is_some())
+            .finish()
+    }
+}
+
+impl WalkOptions {
+    /// Sets how the folders are listed.
+    pub fn set_strategy(&mut self, value: WalkStrategy) {
+        self.strategy = value;
+    }
+
+    /// Sets how the folders are listed and returns the updated options.
+    pub fn with_strategy(mut self, value: WalkStrategy) -> Self {
+        self.set_strategy(value);
+        self
+    }
+
+    /// Sets the maximum depth of the returned entries, `1` only returns the content of the root.
+    pub fn set_max_depth(&mut self, value: usize) {
+        self.max_depth = Some(value);
+    }
+
+    /// Sets the maximum depth and returns the updated options.
+    pub fn with_max_depth(mut self, value: usize) -> Self {
+        self.set_max_depth(value);
+        self
+    }
+
+    /// Sets the kind of entries returned.
+    pub fn set_filter(&mut self, value: WalkFilter) {
+        self.filter = value;
+    }
+
+    /// Sets the kind of entries returned and returns the updated options.
+    pub fn with_filter(mut self, value: WalkFilter) -> Self {
+        self.set_filter(value);
+        self
+    }
+
+    /// Sets a predicate excluding entries from the walk.
+    ///
+    /// When the predicate returns `true`, the entry isn't returned and,
+    /// for a folder, its content isn't traversed.
+    pub fn set_prune<F>(&mut self, predicate: F)
+    where
+        F: Fn(&RemotePath, &Entry) -> bool + Send + Sync + 'static,
+    {
+        self.prune = Some(Arc::new(predicate));
+    }
+
+    /// Sets a predicate excluding entries from the walk and returns the updated options.
+    pub fn with_prune<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&RemotePath, &Entry) -> bool + Send + Sync + 'static,
+    {
+        self.set_prune(predicate);
+        self
+    }
+
+    fn is_pruned(&self, path: &RemotePath, entry: &Entry) -> bool {
+        self.prune
+            .as_ref()
+            .is_some_and(|predicate| predicate(path, entry))
+    }
+
+    fn can_descend(&self, path: &RemotePath) -> bool {
+        self.max_depth.is_none_or(|max| path.depth() < max)
+    }
+}
+
+/// State of a walk, consumed step by step by the stream.
+struct Walk<'a> {
+    client: &'a crate::Client,
+    options: WalkOptions,
+    root: Option<FolderIdentifier<'a>>,
+    /// Entries listed but not returned yet.
+    entries: VecDeque<(RemotePath, Entry)>,
+    /// Folders to list with the breadth-first strategy.
+    folders: VecDeque<(RemotePath, u64)>,
+}
+
+impl Walk<'_> {
+    fn push_children(&mut self, path: &RemotePath, children: Vec<Entry>) {
+        self.entries.extend(
+            children
+                .into_iter()
+                .map(|child| (path.join(&child.base().name), child)),
+        );
+    }
+
+    async fn list(&self, identifier: FolderIdentifier<'_>) -> crate::Result<Vec<Entry>> {
+        let options = match self.options.strategy {
+            WalkStrategy::BreadthFirst => ListFolderOptions::default(),
+            WalkStrategy::Recursive => ListFolderOptions::default().with_recursive(),
+        };
+        let folder = self
+            .client
+            .list_folder_with_options(identifier, options)
+            .await?;
+        Ok(folder.contents.unwrap_or_default())
+    }
+
+    async fn next(&mut self) -> Option<crate::Result<(RemotePath, Entry)>> {
+        loop {
+            if let Some((path, mut entry)) = self.entries.pop_front() {
+                if self.options.is_pruned(&path, &entry) {
+                    continue;
+                }
+                if let Entry::Folder(ref mut folder) = entry {
+                    let contents = folder.contents.take();
+                    if self.options.can_descend(&path) {
+                        match contents {
+                            Some(children) => self.push_children(&path, children),
+                            None => self.folders.push_back((path.clone(), folder.folder_id)),
+                        }
+                    }
+                }
+                if self.options.filter.accepts(&entry) {
+                    return Some(Ok((path, entry)));
+                }
+            } else if let Some(root) = self.root.take() {
+                match self.list(root).await {
+                    Ok(children) => self.push_children(&RemotePath::default(), children),
+                    Err(err) => return Some(Err(err)),
+                }
+            } else if let Some((path, folder_id)) = self.folders.pop_front() {
+                match self.list(FolderIdentifier::FolderId(folder_id)).await {
+                    Ok(children) => self.push_children(&path, children),
+                    Err(err) => return Some(Err(err)),
+                }
+            } else {
+                return None;
+            }
+        }
+    }
+}
+
+impl crate::Client {
+    /// Walks the tree under a folder, returning every entry with its path relative to `root`.
+    ///
+    /// The entries are returned in breadth-first order and the root itself isn't returned.
+    /// The returned folders don't hold their `contents`, the children being returned
+    /// as separate entries.
+    ///
+    /// When a folder can't be listed, an error is returned in the stream and the walk
+    /// continues with the other folders.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - A value convertible into a [`FolderIdentifier`] representing the folder to walk.
+    /// * `options` - A [`WalkOptions`] to configure the listing strategy, depth and filters.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use futures_util::StreamExt;
+    /// use pcloud::folder::walk::{WalkFilter, WalkOptions};
+    ///
+    /// # async fn example(client: &pcloud::Client) -> Result<(), pcloud::Error> {
+    /// let options = WalkOptions::default()
+    ///     .with_filter(WalkFilter::FilesOnly)
+    ///     .with_prune(|path, _entry| path.name() == Some(".git"));
+    /// let mut walk = std::pin::pin!(client.walk("/Projects", options));
+    /// while let Some(item) = walk.next().await {
+    ///     let (path, entry) = item?;
+    ///     println!("{path} ({} bytes)", entry.as_file().and_then(|f| f.size).unwrap_or(0));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn walk<'a>(
+        &'a self,
+        root: impl Into<FolderIdentifier<'a>>,
+        options: WalkOptions,
+    ) -> impl Stream<Item = crate::Result<(RemotePath, Entry)>> + 'a {
+        let walk = Walk {
+            client: self,
+            options,
+            root: Some(root.into()),
+            entries: VecDeque::new(),
+            folders: VecDeque::new(),
+        };
+        futures_util::stream::unfold(walk, |mut walk| async move {
+            walk.next().await.map(|item| (item, walk))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+    use mockito::Matcher;
+
+    use super::{RemotePath, WalkFilter, WalkOptions, WalkStrategy};
+    use crate::testing::{file, folder, response};
+    use crate::{Client, Credentials};
+
+    async fn collect(client: &Client, options: WalkOptions) -> Vec<String> {
+        client
+            .walk(0, options)
+            .map(|item| item.unwrap().0.to_string())
+            .collect()
+            .await
+    }
+
+    #[test]
+    fn should_build_paths() {
+        let path = RemotePath::default().join("foo").join("bar");
+        assert_eq!(path.as_str(), "foo/bar");
+        assert_eq!(path.depth(), 2);
+        assert_eq!(path.name(), Some("bar"));
+        assert!(RemotePath::default().is_root());
+    }
+
+    #[tokio::test]
+    async fn should_walk_lazily() {
+        let mut server = mockito::Server::new_async().await;
+        let root = folder(
+            0,
+            "/",
+            Some(&format!("{},{}", folder(1, "a", None), file(10, "x.txt"))),
+        );
+        let m_root = server
+            .mock("GET", "/listfolder")
+            .match_query(Matcher::UrlEncoded("folderid".into(), "0".into()))
+            .with_status(200)
+            .with_body(response(root))
+            .create_async()
+            .await;
+        let child = folder(
+            1,
+            "a",
+            Some(&format!("{},{}", folder(2, "b", None), file(11, "y.txt"))),
+        );
+        let m_child = server
+            .mock("GET", "/listfolder")
+            .match_query(Matcher::UrlEncoded("folderid".into(), "1".into()))
+            .with_status(200)
+            .with_body(response(child))
+            .expect(1)
+            .create_async()
+            .await;
+        let m_leaf = server
+            .mock("GET", "/listfolder")
+            .match_query(Matcher::UrlEncoded("folderid".into(), "2".into()))
+            .with_status(200)
+            .with_body(response(folder(2, "b", Some(""))))
+            .expect(1)
+            .create_async()
+            .await;
+        let client = Client::new(server.url(), Credentials::anonymous()).unwrap();
+
+        let paths = collect(&client, WalkOptions::default()).await;
+        assert_eq!(paths, vec!["a", "x.txt", "a/b", "a/y.txt"]);
+        m_root.assert_async().await;
+        m_child.assert_async().await;
+        m_leaf.assert_async().await;
+
+        let paths = collect(
+            &client,
+            WalkOptions::default()
+                .with_max_depth(1)
+                .with_filter(WalkFilter::FilesOnly),
+        )
+        .await;
+        assert_eq!(paths, vec!["x.txt"]);
+    }
+
+    #[tokio::test]
+    async fn should_walk_recursive_listing() {
+        let mut server = mockito::Server::new_async().await;
+        let tree = folder(
+            0,
+            "/",
+            Some(&format!(
+                "{},{},{}",
+                folder(
+                    1,
+                    "a",
+                    Some(&format!(
+                        "{},{}",
+                        folder(2, "b", Some("")),
+                        file(11, "y.txt")
+                    ))
+                ),
+                folder(3, "skip", Some(&file(12, "z.txt"))),
+                file(10, "x.txt"),
+            )),
+        );
+        let m = server
+            .mock("GET", "/listfolder")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("folderid".into(), "0".into()),
+                Matcher::UrlEncoded("recursive".into(), "1".into()),
+            ]))
+            .with_status(200)
+            .with_body(response(tree))
+            .expect(1)
+            .create_async()
+            .await;
+        let client = Client::new(server.url(), Credentials::anonymous()).unwrap();
+        let options = WalkOptions::default()
+            .with_strategy(WalkStrategy::Recursive)
+            .with_prune(|path, _| path.as_str() == "skip");
+        let paths = collect(&client, options).await;
+        assert_eq!(paths, vec!["a", "x.txt", "a/b", "a/y.txt"]);
+        m.assert_async().await;
+    }
+}
@@ -57,8 +57,8 @@ mod tests {
     async fn success() {
         let mut server = mockito::Server::new_async().await;
         let m = server
-            .mock("GET", "/oauth2_token")
-            .match_query(Matcher::AllOf(vec![
+            .mock("POST", "/oauth2_token")
+            .match_body(Matcher::AllOf(vec![
                 Matcher::UrlEncoded("client_id".into(), "id".into()),
                 Matcher::UrlEncoded("client_secret".into(), "secret".into()),
                 Matcher::UrlEncoded("code".into(), "abc".into()),
@@ -82,8 +82,8 @@ mod tests {
     async fn protocol_error() {
         let mut server = mockito::Server::new_async().await;
         let m = server
-            .mock("GET", "/oauth2_token")
-            .match_query(Matcher::Any)
+            .mock("POST", "/oauth2_token")
+            .match_body(Matcher::Any)
             .with_status(200)
             .with_body(r#"{"result": 2000, "error": "invalid code"}"#)
             .create_async()
@@ -93,7 +93,10 @@ mod tests {
             .oauth2_token("id", "secret", "bad")
             .await
             .unwrap_err();
-        assert!(matches!(err, crate::Error::Protocol(2000, _)));
+        assert!(matches!(
+            err,
+            crate::Error::Protocol(crate::error::ApiErrorCode::LoginFailed, _)
+        ));
         m.assert_async().await;
     }
 }
@@ -0,0 +1,322 @@
+//! Hooks called around every request sent to the API.
+//!
+//! Hooks are registered with [`ClientBuilder::with_hook`](crate::builder::ClientBuilder::with_hook)
+//! and shared by the client and its clones. They're called for every attempt, a request retried
+//! by the [retry policy](crate::retry::RetryPolicy) goes through the hooks once per attempt.
+//!
+//! ```rust
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//!
+//! use pcloud::hook::{Hook, RequestContext, ResponseContext};
+//!
+//! #[derive(Default)]
+//! struct Counter(AtomicUsize);
+//!
+//! impl Hook for Counter {
+//!     fn before_request(&self, request: &mut RequestContext<'_>) {
+//!         request.insert_header("x-request-origin", "example");
+//!     }
+//!
+//!     fn after_response(&self, response: &ResponseContext<'_>) {
+//!         self.0.fetch_add(1, Ordering::Relaxed);
+//!         println!("{} took {:?}", response.method(), response.latency());
+//!     }
+//! }
+//!
+//! let client = pcloud::Client::builder()
+//!     .with_hook(Counter::default())
+//!     .build()
+//!     .unwrap();
+//! ```
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::StatusCode;
+
+use crate::error::ApiErrorCode;
+
+/// The value replacing the credentials in the parameters given to the hooks.
+pub const REDACTED: &str = "<redacted>";
+
+/// The parameters holding credentials or secrets, never given to the hooks.
+const SECRET_PARAMS: &[&str] = &[
+    "access_token",
+    "auth",
+    "password",
+    "passworddigest",
+    "client_secret",
+    "code",
+];
+
+/// Returns `true` when the parameter holds a credential.
+pub(crate) fn is_secret(name: &str) -> bool {
+    SECRET_PARAMS.contains(&name)
+}
+
+/// Callbacks invoked before sending a request to the API and after receiving its response.
+///
+/// Both callbacks do nothing by default.
+pub trait Hook: Send + Sync + 'static {
+    /// Called before the request is sent, headers can be added to it.
+    fn before_request(&self, request: &mut RequestContext<'_>) {
+        let _ = request;
+    }
+
+    /// Called once the response has been decoded, or when the request failed.
+    fn after_response(&self, response: &ResponseContext<'_>) {
+        let _ = response;
+    }
+}
+
+/// A request about to be sent to the API.
+#[derive(Debug)]
+pub struct RequestContext<'a> {
+    method: &'a str,
+    params: &'a [(String, String)],
+    headers: &'a mut HeaderMap,
+}
+
+impl RequestContext<'_> {
+    /// Returns the name of the API method, like `listfolder`.
+    pub fn method(&self) -> &str {
+        self.method
+    }
+
+    /// Returns the parameters of the request, the credentials being replaced by [`REDACTED`].
+    pub fn params(&self) -> &[(String, String)] {
+        self.params
+    }
+
+    /// Returns the headers of the request.
+    ///
+    /// The `Authorization` header holding the access token isn't part of them, it's added
+    /// once the hooks have been called.
+    pub fn headers(&self) -> &HeaderMap {
+        self.headers
+    }
+
+    /// Returns the headers of the request, to add or replace some.
+    pub fn headers_mut(&mut self) -> &mut HeaderMap {
+        self.headers
+    }
+
+    /// Adds a header to the request, replacing any previous value.
+    ///
+    /// Invalid names or values are ignored.
+    pub fn insert_header(&mut self, name: &str, value: &str) {
+        match (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            (Ok(name), Ok(value)) => {
+                self.headers.insert(name, value);
+            }
+            _ => tracing::warn!(name, "ignoring invalid header"),
+        }
+    }
+}
+
+/// The outcome of a request sent to the API.
+#[derive(Debug)]
+pub struct ResponseContext<'a> {
+    method: &'a str,
+    params: &'a [(String, String)],
+    status: Option<StatusCode>,
+    latency: Duration,
+    result: Option<u16>,
+}
+
+impl ResponseContext<'_> {
+    /// Returns the name of the API method, like `listfolder`.
+    pub fn method(&self) -> &str {
+        self.method
+    }
+
+    /// Returns the parameters of the request, the credentials being replaced by [`REDACTED`].
+    pub fn params(&self) -> &[(String, String)] {
+        self.params
+    }
+
+    /// Returns the HTTP status, or `None` when no response was received.
+    pub fn status(&self) -> Option<StatusCode> {
+        self.status
+    }
+
+    /// Returns the time between sending the request and decoding the response.
+    pub fn latency(&self) -> Duration {
+        self.latency
+    }
+
+    /// Returns the result code of the API, `0` on success, or `None` when the response
+    /// couldn't be decoded.
+    pub fn result(&self) -> Option<u16> {
+        self.result
+    }
+
+    /// Returns the typed error code, when the API returned an error.
+    pub fn error_code(&self) -> Option<ApiErrorCode> {
+        self.result
+            .filter(|code| *code != 0)
+            .map(ApiErrorCode::from)
+    }
+
+    /// Returns `true` when the API returned a successful result.
+    pub fn is_success(&self) -> bool {
+        self.result == Some(0)
+    }
+}
+
+/// The hooks registered on a client.
+#[derive(Clone, Default)]
+pub(crate) struct Hooks(Vec<Arc<dyn Hook>>);
+
+impl std::fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple(stringify!(Hooks))
+            .field(&self.0.len())
+            .finish()
+    }
+}
+
+impl Hooks {
+    pub(crate) fn push(&mut self, hook: Arc<dyn Hook>) {
+        self.0.push(hook);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub(crate) fn before_request(
+        &self,
+        method: &str,
+        params: &[(String, String)],
+        headers: &mut HeaderMap,
+    ) {
+        let mut context = RequestContext {
+            method,
+            params,
+            headers,
+        };
+        for hook in self.0.iter() {
+            hook.before_request(&mut context);
+        }
+    }
+
+    pub(crate) fn after_response(
+        &self,
+        method: &str,
+        params: &[(String, String)],
+        status: Option<StatusCode>,
+        latency: Duration,
+        result: Option<u16>,
+    ) {
+        let context = ResponseContext {
+            method,
+            params,
+            status,
+            latency,
+            result,
+        };
+        for hook in self.0.iter() {
+            hook.after_response(&context);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::{Hook, RequestContext, ResponseContext, REDACTED};
+    use crate::{Client, Credentials, CredentialsMode};
+    use mockito::Matcher;
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl Hook for Arc<Recorder> {
+        fn before_request(&self, request: &mut RequestContext<'_>) {
+            request.insert_header("x-custom", "value");
+            self.0.lock().unwrap().push(format!(
+                "before {} {:?} {:?}",
+                request.method(),
+                request.params(),
+                request.headers().get("authorization"),
+            ));
+        }
+
+        fn after_response(&self, response: &ResponseContext<'_>) {
+            self.0.lock().unwrap().push(format!(
+                "after {} {:?} {:?} {:?}",
+                response.method(),
+                response.status().map(|status| status.as_u16()),
+                response.result(),
+                response.error_code(),
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn should_call_hooks_around_requests() {
+        let mut server = mockito::Server::new_async().await;
+        let m = server
+            .mock("GET", "/getfilelink")
+            .match_query(Matcher::UrlEncoded("fileid".into(), "42".into()))
+            .match_header("x-custom", "value")
+            .with_status(200)
+            .with_body(r#"{"result": 2009, "error": "File not found."}"#)
+            .create_async()
+            .await;
+        let recorder = Arc::new(Recorder::default());
+        let client = Client::builder()
+            .with_base_url(server.url())
+            .with_credentials(Credentials::access_token("secret-token"))
+            .with_credentials_mode(CredentialsMode::Query)
+            .with_hook(recorder.clone())
+            .build()
+            .unwrap();
+        client.get_file_link(42).await.unwrap_err();
+        m.assert_async().await;
+
+        let calls = recorder.0.lock().unwrap().clone();
+        assert_eq!(
+            calls,
+            vec![
+                format!(
+                    r#"before getfilelink [("access_token", "{REDACTED}"), ("fileid", "42")] None"#
+                ),
+                "after getfilelink Some(200) Some(2009) Some(FileNotFound)".to_string(),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_hide_bearer_token_from_hooks() {
+        let mut server = mockito::Server::new_async().await;
+        let m = server
+            .mock("GET", "/getfilelink")
+            .match_header("authorization", "Bearer secret-token")
+            .match_header("x-custom", "value")
+            .match_query(Matcher::UrlEncoded("fileid".into(), "42".into()))
+            .with_status(200)
+            .with_body(r#"{"result": 2009, "error": "File not found."}"#)
+            .create_async()
+            .await;
+        let recorder = Arc::new(Recorder::default());
+        let client = Client::builder()
+            .with_base_url(server.url())
+            .with_credentials(Credentials::access_token("secret-token"))
+            .with_hook(recorder.clone())
+            .build()
+            .unwrap();
+        client.get_file_link(42).await.unwrap_err();
+        m.assert_async().await;
+
+        let calls = recorder.0.lock().unwrap().clone();
+        assert_eq!(calls[0], r#"before getfilelink [("fileid", "42")] None"#);
+        assert!(calls.iter().all(|call| !call.contains("secret-token")));
+    }
+}
@@ -2,10 +2,23 @@
 
 use std::borrow::Cow;
 
+// Module exposing a synchronous client, running the requests on its own runtime.
+#[cfg(feature = "blocking")]
+pub mod blocking;
+
+// Module running delete, move, copy and rename operations on many entries, with a report.
+pub mod batch;
+
 // Module responsible for building requests to the API, including setting parameters and
 // configuring request details such as method type, headers, and body content.
 pub mod builder;
 
+// Module describing the typed result codes returned by the API when a request fails.
+pub mod error;
+
+// Module caching the metadata of files and folders, invalidated on changes.
+pub mod cache;
+
 // Module for handling entries in the system. This could include creating, modifying,
 // or retrieving data related to various types of entries (e.g., file or folder entries).
 pub mod entry;
@@ -22,6 +35,32 @@ pub mod folder;
 /// https://docs.pcloud.com/methods/general/
 pub mod general;
 
+// Module encrypting the contents and the names of the files on the client side.
+#[cfg(feature = "encryption")]
+pub mod encryption;
+
+// Module defining the hooks called around every request, to add headers or collect metrics.
+pub mod hook;
+
+// Module implementing the OAuth2 authorization flow, from the authorize URL to the access token.
+pub mod oauth2;
+
+// Module saving and loading named profiles, with the credentials and the region of an account.
+pub mod profile;
+
+/// Module defining how failed requests are retried
+pub mod retry;
+
+// Module implementing object_store::ObjectStore for the client, mapping objects onto pCloud paths.
+#[cfg(feature = "object_store")]
+pub mod object_store;
+
+// Module searching a folder tree for entries matching patterns, sizes or dates.
+pub mod search;
+
+// Module comparing a local directory with a folder on pCloud and planning their synchronization.
+pub mod sync;
+
 // Module for working with streams, likely including streaming files or media
 // content, such as audio and video, over the network or from storage.
 pub mod stream;
@@ -30,10 +69,20 @@ pub mod stream;
 // or other date-related utilities across the library.
 mod date;
 
+// Private module limiting the concurrency and the rate of the requests sent by a client.
+mod throttle;
+
+// Private module logging in with a username and a password, and renewing the authorization token.
+mod session;
+
 // Private module that contains the logic for handling HTTP requests, such as sending GET, POST,
 // PUT requests, serializing parameters, and processing responses from the API.
 mod request;
 
+// Private module providing the fixtures shared by the unit tests.
+#[cfg(test)]
+mod testing;
+
 // Re-exporting the reqwest crate for convenient access
 pub use reqwest;
 use sha1::Digest;
@@ -48,7 +97,7 @@ pub const EU_REGION: &str = "https://eapi.pcloud.com";
 pub const US_REGION: &str = "https://api.pcloud.com";
 
 /// Represents a pCloud API region.
-#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum Region {
     /// Europe region endpoint
     #[serde(alias = "EU")]
@@ -82,6 +131,19 @@ impl Region {
     }
 }
 
+/// The format of the dates returned by the API.
+///
+/// Both formats are decoded into the same [`chrono::DateTime`], timestamps being
+/// cheaper to decode on large listings.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TimeFormat {
+    /// RFC 2822 strings, like `Sat, 24 Jul 2021 07:38:41 +0000`, the default of the API.
+    #[default]
+    Rfc2822,
+    /// Number of seconds since the Unix epoch, requested with `timeformat=timestamp`.
+    Timestamp,
+}
+
 /// Authentication credentials used for pCloud API requests.
 #[derive(Clone, serde::Serialize, serde::Deserialize)]
 #[serde(untagged)]
@@ -197,6 +259,21 @@ impl Credentials {
     }
 }
 
+/// Where the credentials are placed in the requests sent to the API.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CredentialsMode {
+    /// Keeps the credentials out of the URLs.
+    ///
+    /// Access tokens are sent in the `Authorization: Bearer` header and the other credentials
+    /// in the body of a POST request, or as fields of the form when uploading files.
+    #[default]
+    Hidden,
+    /// Sends the credentials in the query string, with the other parameters.
+    ///
+    /// The credentials may then end up in the logs of the proxies between the client and the API.
+    Query,
+}
+
 impl std::fmt::Debug for Credentials {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct(stringify!(Credentials))
@@ -205,11 +282,21 @@ impl std::fmt::Debug for Credentials {
 }
 
 /// HTTP client used to interact with the pCloud API.
-#[derive(Debug)]
+///
+/// Cloning the client is cheap and the clones share the same connection pool
+/// and the same concurrency and rate limits.
+#[derive(Clone, Debug)]
 pub struct Client {
     base_url: Cow<'static, str>,
     credentials: Credentials,
     inner: reqwest::Client,
+    retry_policy: Option<crate::retry::RetryPolicy>,
+    throttle: Option<std::sync::Arc<crate::throttle::Throttle>>,
+    cache: Option<std::sync::Arc<crate::cache::MetadataCache>>,
+    session: Option<std::sync::Arc<crate::session::Session>>,
+    hooks: crate::hook::Hooks,
+    credentials_mode: CredentialsMode,
+    time_format: TimeFormat,
 }
 
 impl Default for Client {
@@ -218,6 +305,13 @@ impl Default for Client {
             base_url: crate::EU_REGION.into(),
             credentials: Credentials::Anonymous,
             inner: reqwest::Client::default(),
+            retry_policy: None,
+            throttle: None,
+            cache: None,
+            session: None,
+            hooks: Default::default(),
+            credentials_mode: CredentialsMode::Hidden,
+            time_format: TimeFormat::Rfc2822,
         }
     }
 }
@@ -244,12 +338,22 @@ impl Client {
             inner: reqwest::ClientBuilder::new()
                 .user_agent(USER_AGENT)
                 .build()?,
+            retry_policy: None,
+            throttle: None,
+            cache: None,
+            session: None,
+            hooks: Default::default(),
+            credentials_mode: CredentialsMode::Hidden,
+            time_format: TimeFormat::Rfc2822,
         })
     }
 
     /// Update the credentials of the client
+    ///
+    /// The login configured with [`crate::builder::ClientBuilder::with_login`] is dropped.
     pub fn set_credentials(&mut self, credentials: Credentials) {
         self.credentials = credentials;
+        self.session = None;
     }
 
     /// Take ownership of the client and update the credentials
@@ -267,7 +371,10 @@ pub type Result<V> = std::result::Result<V, Error>;
 pub enum Error {
     /// An error response from the API, including status code and message.
     #[error("protocol error status {0}: {1}")]
-    Protocol(u16, String),
+    Protocol(crate::error::ApiErrorCode, String),
+    /// The server replied with an HTTP error that isn't a pCloud response (e.g. an HTML error page).
+    #[error("http error status {0}")]
+    Http(reqwest::StatusCode, String),
     /// A network-related error from the underlying HTTP client.
     #[error("network error")]
     Reqwest(
@@ -282,10 +389,71 @@ pub enum Error {
         #[source]
         serde_json::Error,
     ),
-    /// An I/O error occurred while downloading a file.
-    #[error("unable to download file")]
-    Download(#[source] std::io::Error),
+    /// An error occurred while downloading a file, with some context about what failed.
+    #[error("unable to download file: {0}")]
+    Download(String, #[source] std::io::Error),
     /// An I/O error occurred while uploading a file.
     #[error("unable to upload file")]
     Upload(#[source] std::io::Error),
+    /// The checksum of the transferred content doesn't match the one computed by pCloud.
+    #[error("checksum mismatch for {0}: {1}")]
+    ChecksumMismatch(String, crate::file::checksum::ChecksumMismatch),
+    /// An encrypted content couldn't be decrypted.
+    #[cfg(feature = "encryption")]
+    #[error("unable to decrypt content")]
+    Encryption(
+        #[from]
+        #[source]
+        crate::encryption::EncryptionError,
+    ),
+}
+
+impl Error {
+    /// Returns the API result code when the error is a [`Error::Protocol`].
+    pub fn api_code(&self) -> Option<crate::error::ApiErrorCode> {
+        match self {
+            Self::Protocol(code, _) => Some(*code),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` when the requested file, folder or one of its parents doesn't exist.
+    pub fn is_not_found(&self) -> bool {
+        self.api_code().is_some_and(|code| code.is_not_found())
+    }
+
+    /// Returns `true` when the request was rejected because of missing or invalid credentials.
+    pub fn is_auth_error(&self) -> bool {
+        match self {
+            Self::Protocol(code, _) => code.is_auth_error(),
+            Self::Http(status, _) => *status == reqwest::StatusCode::UNAUTHORIZED,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` when the API or the HTTP layer is throttling the caller.
+    pub fn is_rate_limited(&self) -> bool {
+        match self {
+            Self::Protocol(code, _) => code.is_rate_limited(),
+            Self::Http(status, _) => *status == reqwest::StatusCode::TOO_MANY_REQUESTS,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` when the error is transient and the same request may succeed later.
+    ///
+    /// This covers network failures, HTTP server errors, rate limiting and the
+    /// API result codes asking to try again later.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Protocol(code, _) => code.is_retryable(),
+            Self::Http(status, _) => {
+                status.is_server_error() || *status == reqwest::StatusCode::TOO_MANY_REQUESTS
+            }
+            Self::Reqwest(err) => {
+                err.is_timeout() || err.is_connect() || err.is_request() || err.is_body()
+            }
+            _ => false,
+        }
+    }
 }
@@ -0,0 +1,325 @@
+//! The OAuth2 authorization flow, see <https://docs.pcloud.com/methods/oauth_2.0/authorize.html>.
+//!
+//! The flow goes in three steps:
+//!
+//! 1. the user opens the URL built by [`OAuth2Flow::authorize_url`] and grants access,
+//! 2. pCloud redirects to the redirect URI, the request is given to [`OAuth2Flow::parse_callback`]
+//!    which checks that it answers to this flow,
+//! 3. the code is exchanged for an access token with [`OAuth2Flow::exchange`], on the API host of
+//!    the region of the user.
+//!
+//! ```rust,no_run
+//! use pcloud::oauth2::OAuth2Flow;
+//!
+//! # async fn example(redirected_to: &str) -> Result<(), Box<dyn std::error::Error>> {
+//! let flow = OAuth2Flow::new("client-id", "client-secret")
+//!     .with_redirect_uri("http://localhost:53682/");
+//! println!("open {}", flow.authorize_url());
+//! // ...wait for the browser to be redirected...
+//! let callback = flow.parse_callback(redirected_to)?;
+//! let token = flow.exchange(&callback).await?;
+//! let client = pcloud::Client::builder()
+//!     .with_base_url(callback.base_url())
+//!     .with_credentials(pcloud::Credentials::access_token(token.access_token))
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::borrow::Cow;
+
+use rand::distr::{Alphanumeric, SampleString};
+use url::Url;
+
+use crate::general::oauth2_token::OAuth2Token;
+
+/// The page where the user grants access to the application.
+pub const AUTHORIZE_URL: &str = "https://my.pcloud.com/oauth2/authorize";
+
+/// The length of the generated `state` parameter.
+const STATE_LENGTH: usize = 32;
+
+/// Errors returned when the redirection to the application can't be accepted.
+#[derive(Debug, thiserror::Error)]
+pub enum CallbackError {
+    /// The callback couldn't be parsed as a URL or a request target.
+    #[error("invalid callback url")]
+    InvalidUrl(#[source] url::ParseError),
+    /// The user denied the access, or pCloud refused the authorization.
+    #[error("authorization failed: {0}")]
+    Denied(String),
+    /// The callback doesn't contain the authorization code.
+    #[error("missing code in callback")]
+    MissingCode,
+    /// The `state` doesn't match the one of the flow, the callback wasn't initiated by it.
+    #[error("state mismatch in callback")]
+    StateMismatch,
+    /// The callback designates an API host that doesn't belong to pCloud.
+    #[error("unknown api host {0}")]
+    UnknownHost(String),
+}
+
+/// The parameters pCloud redirects to the application with.
+#[derive(Clone, Debug)]
+pub struct Callback {
+    /// The authorization code, to exchange for an access token.
+    pub code: String,
+    /// The API host of the region of the user.
+    pub hostname: Option<String>,
+    /// The identifier of the region of the user, `1` for the US and `2` for Europe.
+    pub location_id: Option<u64>,
+}
+
+impl Callback {
+    /// Returns the base URL of the API for the region of the user.
+    ///
+    /// Falls back to the US region, the default of pCloud, when the region isn't specified.
+    pub fn base_url(&self) -> Cow<'static, str> {
+        match (self.hostname.as_deref(), self.location_id) {
+            (Some(hostname), _) => Cow::Owned(format!("https://{hostname}")),
+            (None, Some(2)) => Cow::Borrowed(crate::EU_REGION),
+            (None, _) => Cow::Borrowed(crate::US_REGION),
+        }
+    }
+}
+
+/// Returns `true` when the host is an API host of pCloud.
+fn is_api_host(hostname: &str) -> bool {
+    hostname
+        .strip_suffix(".pcloud.com")
+        .is_some_and(|name| !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+
+/// An OAuth2 authorization, from building the authorize URL to exchanging the code.
+///
+/// A random `state` is generated for each flow and checked when the user is redirected,
+/// protecting the redirect URI against forged requests.
+#[derive(Clone)]
+pub struct OAuth2Flow {
+    client_id: String,
+    client_secret: String,
+    redirect_uri: Option<String>,
+    state: String,
+}
+
+impl std::fmt::Debug for OAuth2Flow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct(stringify!(OAuth2Flow))
+            .field("client_id", &self.client_id)
+            .field("redirect_uri", &self.redirect_uri)
+            .finish_non_exhaustive()
+    }
+}
+
+impl OAuth2Flow {
+    /// Starts a new flow for the given application, with a random `state`.
+    pub fn new(client_id: impl Into<String>, client_secret: impl Into<String>) -> Self {
+        Self {
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
+            redirect_uri: None,
+            state: Alphanumeric.sample_string(&mut rand::rng(), STATE_LENGTH),
+        }
+    }
+
+    /// Sets where the user is redirected once access is granted.
+    ///
+    /// Without a redirect URI, pCloud displays the code to the user instead.
+    pub fn set_redirect_uri(&mut self, value: impl Into<String>) {
+        self.redirect_uri = Some(value.into());
+    }
+
+    /// Sets the redirect URI and returns the modified flow.
+    pub fn with_redirect_uri(mut self, value: impl Into<String>) -> Self {
+        self.set_redirect_uri(value);
+        self
+    }
+
+    /// Sets the `state`, when it must be persisted between the steps of the flow.
+    pub fn set_state(&mut self, value: impl Into<String>) {
+        self.state = value.into();
+    }
+
+    /// Sets the `state` and returns the modified flow.
+    pub fn with_state(mut self, value: impl Into<String>) -> Self {
+        self.set_state(value);
+        self
+    }
+
+    /// Returns the `state` sent to pCloud and expected in the callback.
+    pub fn state(&self) -> &str {
+        &self.state
+    }
+
+    /// Builds the URL the user has to open to grant access to the application.
+    pub fn authorize_url(&self) -> String {
+        let mut params = vec![
+            ("client_id", self.client_id.as_str()),
+            ("response_type", "code"),
+            ("state", self.state.as_str()),
+        ];
+        if let Some(ref redirect_uri) = self.redirect_uri {
+            params.push(("redirect_uri", redirect_uri.as_str()));
+        }
+        Url::parse_with_params(AUTHORIZE_URL, params)
+            .expect("the authorize url is valid")
+            .to_string()
+    }
+
+    /// Parses the URL the user was redirected to.
+    ///
+    /// Either the full URL or the request target (the path and the query) is accepted.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CallbackError`] if the authorization was denied, if the `state` doesn't
+    /// match the one of this flow, or if the API host isn't one of pCloud.
+    pub fn parse_callback(&self, url: &str) -> Result<Callback, CallbackError> {
+        let base = Url::parse("http://localhost/").expect("the base url is valid");
+        let url = base.join(url).map_err(CallbackError::InvalidUrl)?;
+
+        let mut code = None;
+        let mut state = None;
+        let mut error = None;
+        let mut hostname = None;
+        let mut location_id = None;
+        for (key, value) in url.query_pairs() {
+            match key.as_ref() {
+                "code" => code = Some(value.into_owned()),
+                "state" => state = Some(value.into_owned()),
+                "error" => error = Some(value.into_owned()),
+                "hostname" => hostname = Some(value.into_owned()),
+                "locationid" => location_id = value.parse().ok(),
+                _ => {}
+            }
+        }
+
+        if state.as_deref() != Some(self.state.as_str()) {
+            return Err(CallbackError::StateMismatch);
+        }
+        if let Some(error) = error {
+            return Err(CallbackError::Denied(error));
+        }
+        let code = code.ok_or(CallbackError::MissingCode)?;
+        if let Some(ref hostname) = hostname {
+            if !is_api_host(hostname) {
+                return Err(CallbackError::UnknownHost(hostname.clone()));
+            }
+        }
+        Ok(Callback {
+            code,
+            hostname,
+            location_id,
+        })
+    }
+
+    /// Exchanges the code of the callback for an access token, on the API host of the user.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`crate::Error`] if the request fails or if pCloud rejects the code.
+    pub async fn exchange(&self, callback: &Callback) -> crate::Result<OAuth2Token> {
+        let client = crate::Client::new(callback.base_url(), crate::Credentials::anonymous())?;
+        self.exchange_with(&client, callback).await
+    }
+
+    /// Exchanges the code of the callback with the given client, ignoring the host of the callback.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`crate::Error`] if the request fails or if pCloud rejects the code.
+    pub async fn exchange_with(
+        &self,
+        client: &crate::Client,
+        callback: &Callback,
+    ) -> crate::Result<OAuth2Token> {
+        client
+            .oauth2_token(&self.client_id, &self.client_secret, &callback.code)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CallbackError, OAuth2Flow};
+    use crate::{Client, Credentials};
+    use mockito::Matcher;
+
+    #[test]
+    fn should_build_authorize_url() {
+        let flow = OAuth2Flow::new("the-id", "the-secret")
+            .with_redirect_uri("http://localhost:53682/")
+            .with_state("abc");
+        assert_eq!(
+            flow.authorize_url(),
+            "https://my.pcloud.com/oauth2/authorize?client_id=the-id&response_type=code&state=abc&redirect_uri=http%3A%2F%2Flocalhost%3A53682%2F"
+        );
+        let other = OAuth2Flow::new("the-id", "the-secret");
+        assert_eq!(other.state().len(), 32);
+        assert_ne!(
+            other.state(),
+            OAuth2Flow::new("the-id", "the-secret").state()
+        );
+    }
+
+    #[test]
+    fn should_parse_callback() {
+        let flow = OAuth2Flow::new("the-id", "the-secret").with_state("abc");
+        let callback = flow
+            .parse_callback("/?code=the%20code&state=abc&locationid=2&hostname=eapi.pcloud.com")
+            .unwrap();
+        assert_eq!(callback.code, "the code");
+        assert_eq!(callback.base_url(), "https://eapi.pcloud.com");
+        let callback = flow
+            .parse_callback("http://localhost:53682/?code=xyz&state=abc&locationid=2")
+            .unwrap();
+        assert_eq!(callback.base_url(), crate::EU_REGION);
+    }
+
+    #[test]
+    fn should_reject_invalid_callback() {
+        let flow = OAuth2Flow::new("the-id", "the-secret").with_state("abc");
+        assert!(matches!(
+            flow.parse_callback("/?code=xyz&state=other"),
+            Err(CallbackError::StateMismatch)
+        ));
+        assert!(matches!(
+            flow.parse_callback("/?code=xyz"),
+            Err(CallbackError::StateMismatch)
+        ));
+        assert!(matches!(
+            flow.parse_callback("/?error=access_denied&state=abc"),
+            Err(CallbackError::Denied(ref error)) if error == "access_denied"
+        ));
+        assert!(matches!(
+            flow.parse_callback("/?state=abc"),
+            Err(CallbackError::MissingCode)
+        ));
+        assert!(matches!(
+            flow.parse_callback("/?code=xyz&state=abc&hostname=evil.example.com"),
+            Err(CallbackError::UnknownHost(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_exchange_code() {
+        let mut server = mockito::Server::new_async().await;
+        let m = server
+            .mock("POST", "/oauth2_token")
+            .match_body(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("client_id".into(), "the-id".into()),
+                Matcher::UrlEncoded("client_secret".into(), "the-secret".into()),
+                Matcher::UrlEncoded("code".into(), "xyz".into()),
+            ]))
+            .with_status(200)
+            .with_body(r#"{"result": 0, "access_token": "the-token", "locationid": 2}"#)
+            .create_async()
+            .await;
+        let flow = OAuth2Flow::new("the-id", "the-secret").with_state("abc");
+        let callback = flow.parse_callback("/?code=xyz&state=abc").unwrap();
+        let client = Client::new(server.url(), Credentials::anonymous()).unwrap();
+        let token = flow.exchange_with(&client, &callback).await.unwrap();
+        assert_eq!(token.access_token, "the-token");
+        m.assert_async().await;
+    }
+}
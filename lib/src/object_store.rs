@@ -0,0 +1,589 @@
+//! Implementation of [`ObjectStore`] on top of the pCloud API.
+//!
+//! Object paths are mapped onto pCloud paths from the root folder: the object `a/b/c.txt`
+//! is the file `/a/b/c.txt`. Folders are created when writing an object and are exposed
+//! as common prefixes when listing.
+//!
+//! ```rust,no_run
+//! use object_store::{path::Path, ObjectStoreExt};
+//!
+//! # async fn example(client: pcloud::Client) -> object_store::Result<()> {
+//! client.put(&Path::from("reports/2024.csv"), "id,total".into()).await?;
+//! let content = client.get(&Path::from("reports/2024.csv")).await?.bytes().await?;
+//! println!("{} bytes", content.len());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! To work inside a folder, wrap the client in an [`object_store::prefix::PrefixStore`].
+
+use std::ops::Range;
+
+use ::object_store::path::{Path, DELIMITER};
+use ::object_store::{
+    Attribute, Attributes, CopyMode, CopyOptions, GetOptions, GetResult, GetResultPayload,
+    ListResult, MultipartUpload, ObjectMeta, ObjectStore, PutMode, PutMultipartOptions, PutOptions,
+    PutPayload, PutResult, UploadPart,
+};
+use async_trait::async_trait;
+use futures_util::stream::BoxStream;
+use futures_util::{StreamExt, TryStreamExt};
+
+use crate::entry::Entry;
+use crate::file::copy::CopyFileOptions;
+use crate::file::download::ByteRange;
+use crate::file::upload::MultiFileUpload;
+use crate::file::File;
+use crate::folder::list::ListFolderOptions;
+use crate::folder::{Folder, ROOT};
+
+/// The name of the store used in the errors.
+const STORE: &str = "pCloud";
+
+/// The number of files deleted concurrently by [`ObjectStore::delete_stream`].
+const DELETE_CONCURRENCY: usize = 8;
+
+/// Converts an object path into an absolute pCloud path.
+fn remote_path(location: &Path) -> String {
+    format!("/{location}")
+}
+
+/// Splits an object path into the pCloud path of its folder and its file name.
+fn split_location(location: &Path) -> Option<(Option<&str>, &str)> {
+    match location.as_ref().rsplit_once(DELIMITER) {
+        Some((parent, name)) => Some((Some(parent), name)),
+        None if location.as_ref().is_empty() => None,
+        None => Some((None, location.as_ref())),
+    }
+}
+
+fn is_not_found(err: &crate::Error) -> bool {
+    err.api_code().is_some_and(|code| code.is_not_found())
+}
+
+/// Converts an error of the client into an error of the store.
+fn store_error(location: &Path, err: crate::Error) -> ::object_store::Error {
+    match err.api_code() {
+        Some(code) if code.is_not_found() => ::object_store::Error::NotFound {
+            path: location.to_string(),
+            source: Box::new(err),
+        },
+        Some(crate::error::ApiErrorCode::AlreadyExists) => ::object_store::Error::AlreadyExists {
+            path: location.to_string(),
+            source: Box::new(err),
+        },
+        _ => ::object_store::Error::Generic {
+            store: STORE,
+            source: Box::new(err),
+        },
+    }
+}
+
+fn not_implemented(operation: &str) -> ::object_store::Error {
+    ::object_store::Error::NotImplemented {
+        operation: operation.to_owned(),
+        implementer: STORE.to_owned(),
+    }
+}
+
+/// Builds the metadata of an object from the metadata of a file.
+fn object_meta(location: Path, file: &File) -> ObjectMeta {
+    ObjectMeta {
+        location,
+        last_modified: file.base.modified,
+        size: file.size.unwrap_or_default(),
+        e_tag: file.hash.map(|hash| hash.to_string()),
+        version: None,
+    }
+}
+
+/// Collects the files of a recursive listing, their location being relative to `location`.
+fn collect_objects(location: &Path, folder: Folder, objects: &mut Vec<ObjectMeta>) {
+    for entry in folder.contents.unwrap_or_default() {
+        let child = location.clone().join(entry.base().name.as_str());
+        match entry {
+            Entry::File(file) => objects.push(object_meta(child, &file)),
+            Entry::Folder(folder) => collect_objects(&child, folder, objects),
+        }
+    }
+}
+
+impl std::fmt::Display for crate::Client {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{STORE}({})", self.base_url)
+    }
+}
+
+impl crate::Client {
+    /// Returns the identifier of the folder holding `location`, creating it when missing.
+    async fn object_folder(&self, parent: Option<&str>) -> crate::Result<u64> {
+        match parent {
+            Some(parent) => self
+                .create_folder_all(&format!("/{parent}"))
+                .await
+                .map(|folder| folder.folder_id),
+            None => Ok(ROOT),
+        }
+    }
+
+    /// Lists every file below `prefix`, an empty list is returned when the folder doesn't exist.
+    async fn list_objects(&self, prefix: Path) -> crate::Result<Vec<ObjectMeta>> {
+        let options = ListFolderOptions::default().with_recursive();
+        match self
+            .list_folder_with_options(remote_path(&prefix), options)
+            .await
+        {
+            Ok(folder) => {
+                let mut objects = Vec::new();
+                collect_objects(&prefix, folder, &mut objects);
+                Ok(objects)
+            }
+            Err(err) if is_not_found(&err) => Ok(Vec::new()),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+#[async_trait]
+impl ObjectStore for crate::Client {
+    async fn put_opts(
+        &self,
+        location: &Path,
+        payload: PutPayload,
+        opts: PutOptions,
+    ) -> ::object_store::Result<PutResult> {
+        if opts.mode != PutMode::Overwrite {
+            return Err(not_implemented("conditional put"));
+        }
+        if !opts.attributes.is_empty() {
+            return Err(not_implemented("put with attributes"));
+        }
+        let (parent, name) = split_location(location).ok_or_else(|| {
+            store_error(
+                location,
+                crate::Error::Upload(std::io::ErrorKind::InvalidInput.into()),
+            )
+        })?;
+        let folder_id = self
+            .object_folder(parent)
+            .await
+            .map_err(|err| store_error(location, err))?;
+        let length = payload.content_length() as u64;
+        let content = futures_util::stream::iter(payload.into_iter().map(Ok::<_, std::io::Error>));
+        let files = MultiFileUpload::default().with_stream_entry(name, Some(length), content);
+        let file = self
+            .upload_files(folder_id, files)
+            .await
+            .map_err(|err| store_error(location, err))?
+            .pop()
+            .ok_or_else(|| {
+                store_error(
+                    location,
+                    crate::Error::Upload(std::io::ErrorKind::Other.into()),
+                )
+            })?;
+        Ok(PutResult {
+            e_tag: file.hash.map(|hash| hash.to_string()),
+            version: None,
+        })
+    }
+
+    /// Starts a multipart upload.
+    ///
+    /// The parts are kept in memory and sent in a single upload when completing.
+    async fn put_multipart_opts(
+        &self,
+        location: &Path,
+        opts: PutMultipartOptions,
+    ) -> ::object_store::Result<Box<dyn MultipartUpload>> {
+        if !opts.attributes.is_empty() {
+            return Err(not_implemented("put with attributes"));
+        }
+        Ok(Box::new(BufferedUpload {
+            client: self.clone(),
+            location: location.clone(),
+            parts: Vec::new(),
+        }))
+    }
+
+    async fn get_opts(
+        &self,
+        location: &Path,
+        options: GetOptions,
+    ) -> ::object_store::Result<GetResult> {
+        let file = self
+            .get_file_checksum(remote_path(location))
+            .await
+            .map_err(|err| store_error(location, err))?
+            .metadata;
+        let meta = object_meta(location.clone(), &file);
+        options.check_preconditions(&meta)?;
+
+        let mut attributes = Attributes::new();
+        if let Some(content_type) = file.content_type {
+            attributes.insert(Attribute::ContentType, content_type.into());
+        }
+        let range: Range<u64> = match options.range {
+            Some(ref range) => {
+                range
+                    .as_range(meta.size)
+                    .map_err(|err| ::object_store::Error::Generic {
+                        store: STORE,
+                        source: Box::new(err),
+                    })?
+            }
+            None => 0..meta.size,
+        };
+        let payload = if options.head || range.is_empty() {
+            futures_util::stream::empty().boxed()
+        } else {
+            let links = self
+                .get_file_link(file.file_id)
+                .await
+                .map_err(|err| store_error(location, err))?;
+            let urls = links
+                .links()
+                .map(|link| link.to_string())
+                .collect::<Vec<_>>();
+            let byte_range = if range.start == 0 && range.end == meta.size {
+                ByteRange::default()
+            } else {
+                ByteRange::new(range.start, Some(range.end - 1))
+            };
+            let path = location.clone();
+            self.download_urls_stream(urls, byte_range)
+                .await
+                .map_err(|err| store_error(location, err))?
+                .map_err(move |err| store_error(&path, err))
+                .boxed()
+        };
+        Ok(GetResult {
+            payload: GetResultPayload::Stream(payload),
+            meta,
+            range,
+            attributes,
+        })
+    }
+
+    /// Deletes the files, a missing file is considered as deleted.
+    fn delete_stream(
+        &self,
+        locations: BoxStream<'static, ::object_store::Result<Path>>,
+    ) -> BoxStream<'static, ::object_store::Result<Path>> {
+        let client = self.clone();
+        locations
+            .map(move |location| {
+                let client = client.clone();
+                async move {
+                    let location = location?;
+                    match client.delete_file(remote_path(&location)).await {
+                        Ok(_) => Ok(location),
+                        Err(err) if is_not_found(&err) => Ok(location),
+                        Err(err) => Err(store_error(&location, err)),
+                    }
+                }
+            })
+            .buffered(DELETE_CONCURRENCY)
+            .boxed()
+    }
+
+    fn list(
+        &self,
+        prefix: Option<&Path>,
+    ) -> BoxStream<'static, ::object_store::Result<ObjectMeta>> {
+        let client = self.clone();
+        let prefix = prefix.cloned().unwrap_or_default();
+        futures_util::stream::once(async move {
+            client
+                .list_objects(prefix.clone())
+                .await
+                .map_err(|err| store_error(&prefix, err))
+        })
+        .map_ok(|objects| futures_util::stream::iter(objects.into_iter().map(Ok)))
+        .try_flatten()
+        .boxed()
+    }
+
+    async fn list_with_delimiter(
+        &self,
+        prefix: Option<&Path>,
+    ) -> ::object_store::Result<ListResult> {
+        let prefix = prefix.cloned().unwrap_or_default();
+        let mut result = ListResult {
+            common_prefixes: Vec::new(),
+            objects: Vec::new(),
+        };
+        let folder = match self.list_folder(remote_path(&prefix)).await {
+            Ok(folder) => folder,
+            Err(err) if is_not_found(&err) => return Ok(result),
+            Err(err) => return Err(store_error(&prefix, err)),
+        };
+        for entry in folder.contents.unwrap_or_default() {
+            let child = prefix.clone().join(entry.base().name.as_str());
+            match entry {
+                Entry::File(file) => result.objects.push(object_meta(child, &file)),
+                Entry::Folder(_) => result.common_prefixes.push(child),
+            }
+        }
+        Ok(result)
+    }
+
+    async fn copy_opts(
+        &self,
+        from: &Path,
+        to: &Path,
+        options: CopyOptions,
+    ) -> ::object_store::Result<()> {
+        let (parent, name) = split_location(to).ok_or_else(|| {
+            store_error(
+                to,
+                crate::Error::Upload(std::io::ErrorKind::InvalidInput.into()),
+            )
+        })?;
+        let folder_id = self
+            .object_folder(parent)
+            .await
+            .map_err(|err| store_error(to, err))?;
+        let options = CopyFileOptions::default()
+            .with_to_name(name)
+            .with_no_overwrite(options.mode == CopyMode::Create);
+        self.copy_file_with_options(remote_path(from), folder_id, options)
+            .await
+            .map_err(|err| match err.api_code() {
+                Some(crate::error::ApiErrorCode::AlreadyExists) => store_error(to, err),
+                _ => store_error(from, err),
+            })?;
+        Ok(())
+    }
+}
+
+/// A multipart upload keeping the parts in memory until completed.
+#[derive(Debug)]
+struct BufferedUpload {
+    client: crate::Client,
+    location: Path,
+    parts: Vec<PutPayload>,
+}
+
+#[async_trait]
+impl MultipartUpload for BufferedUpload {
+    fn put_part(&mut self, data: PutPayload) -> UploadPart {
+        self.parts.push(data);
+        Box::pin(futures_util::future::ready(Ok(())))
+    }
+
+    async fn complete(&mut self) -> ::object_store::Result<PutResult> {
+        let payload = std::mem::take(&mut self.parts)
+            .into_iter()
+            .flatten()
+            .collect::<PutPayload>();
+        self.client
+            .put_opts(&self.location, payload, PutOptions::default())
+            .await
+    }
+
+    async fn abort(&mut self) -> ::object_store::Result<()> {
+        self.parts.clear();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::object_store::path::Path;
+    use ::object_store::{CopyMode, CopyOptions, GetOptions, ObjectStore, ObjectStoreExt};
+    use futures_util::{StreamExt, TryStreamExt};
+    use mockito::Matcher;
+
+    use crate::testing::{file, folder};
+    use crate::{Client, Credentials};
+
+    #[tokio::test]
+    async fn should_list_with_delimiter() {
+        let mut server = mockito::Server::new_async().await;
+        let contents = format!("{},{}", folder(2, "sub", Some("")), file(10, "a.txt"));
+        let m = server
+            .mock("GET", "/listfolder")
+            .match_query(Matcher::UrlEncoded("path".into(), "/data".into()))
+            .with_status(200)
+            .with_body(format!(
+                r#"{{ "result": 0, "metadata": {} }}"#,
+                folder(1, "data", Some(&contents))
+            ))
+            .create_async()
+            .await;
+        let client = Client::new(server.url(), Credentials::anonymous()).unwrap();
+        let result = client
+            .list_with_delimiter(Some(&Path::from("data")))
+            .await
+            .unwrap();
+        assert_eq!(result.common_prefixes, vec![Path::from("data/sub")]);
+        assert_eq!(result.objects.len(), 1);
+        assert_eq!(result.objects[0].location, Path::from("data/a.txt"));
+        assert_eq!(result.objects[0].size, 10);
+        assert_eq!(result.objects[0].e_tag.as_deref(), Some("42"));
+        m.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn should_list_recursively() {
+        let mut server = mockito::Server::new_async().await;
+        let contents = format!(
+            "{},{}",
+            folder(2, "sub", Some(&file(11, "b.txt"))),
+            file(10, "a.txt")
+        );
+        server
+            .mock("GET", "/listfolder")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("path".into(), "/data".into()),
+                Matcher::UrlEncoded("recursive".into(), "1".into()),
+            ]))
+            .with_status(200)
+            .with_body(format!(
+                r#"{{ "result": 0, "metadata": {} }}"#,
+                folder(1, "data", Some(&contents))
+            ))
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/listfolder")
+            .match_query(Matcher::UrlEncoded("path".into(), "/missing".into()))
+            .with_status(200)
+            .with_body(r#"{ "result": 2005, "error": "Directory does not exist." }"#)
+            .create_async()
+            .await;
+        let client = Client::new(server.url(), Credentials::anonymous()).unwrap();
+        let locations: Vec<String> = client
+            .list(Some(&Path::from("data")))
+            .map_ok(|meta| meta.location.to_string())
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(locations, vec!["data/sub/b.txt", "data/a.txt"]);
+        let missing: Vec<_> = client
+            .list(Some(&Path::from("missing")))
+            .try_collect()
+            .await
+            .unwrap();
+        assert!(missing.is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_put_object() {
+        let mut server = mockito::Server::new_async().await;
+        let m = server
+            .mock("POST", "/uploadfile")
+            .match_query(Matcher::UrlEncoded("folderid".into(), "0".into()))
+            .match_body(Matcher::Regex("hello world".into()))
+            .with_status(200)
+            .with_body(format!(
+                r#"{{ "result": 0, "fileids": [10], "metadata": [{}] }}"#,
+                file(10, "hello.txt")
+            ))
+            .create_async()
+            .await;
+        let client = Client::new(server.url(), Credentials::anonymous()).unwrap();
+        let result = client
+            .put(&Path::from("hello.txt"), "hello world".into())
+            .await
+            .unwrap();
+        assert_eq!(result.e_tag.as_deref(), Some("42"));
+        m.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn should_head_object() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/checksumfile")
+            .match_query(Matcher::UrlEncoded("path".into(), "/data/a.txt".into()))
+            .with_status(200)
+            .with_body(format!(
+                r#"{{ "result": 0, "sha1": "abc", "metadata": {} }}"#,
+                file(10, "a.txt")
+            ))
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/checksumfile")
+            .match_query(Matcher::UrlEncoded("path".into(), "/data/b.txt".into()))
+            .with_status(200)
+            .with_body(r#"{ "result": 2009, "error": "File not found." }"#)
+            .create_async()
+            .await;
+        let client = Client::new(server.url(), Credentials::anonymous()).unwrap();
+        let meta = client.head(&Path::from("data/a.txt")).await.unwrap();
+        assert_eq!(meta.size, 10);
+        let options = GetOptions {
+            if_match: Some("43".into()),
+            head: true,
+            ..Default::default()
+        };
+        let error = client
+            .get_opts(&Path::from("data/a.txt"), options)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, ::object_store::Error::Precondition { .. }));
+        let error = client.head(&Path::from("data/b.txt")).await.unwrap_err();
+        assert!(matches!(error, ::object_store::Error::NotFound { .. }));
+    }
+
+    #[tokio::test]
+    async fn should_delete_objects() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/deletefile")
+            .match_query(Matcher::UrlEncoded("path".into(), "/a.txt".into()))
+            .with_status(200)
+            .with_body(format!(
+                r#"{{ "result": 0, "metadata": {} }}"#,
+                file(10, "a.txt")
+            ))
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/deletefile")
+            .match_query(Matcher::UrlEncoded("path".into(), "/b.txt".into()))
+            .with_status(200)
+            .with_body(r#"{ "result": 2009, "error": "File not found." }"#)
+            .create_async()
+            .await;
+        let client = Client::new(server.url(), Credentials::anonymous()).unwrap();
+        let locations =
+            futures_util::stream::iter(vec![Ok(Path::from("a.txt")), Ok(Path::from("b.txt"))])
+                .boxed();
+        let deleted: Vec<Path> = client.delete_stream(locations).try_collect().await.unwrap();
+        assert_eq!(deleted, vec![Path::from("a.txt"), Path::from("b.txt")]);
+    }
+
+    #[tokio::test]
+    async fn should_copy_object() {
+        let mut server = mockito::Server::new_async().await;
+        let m = server
+            .mock("GET", "/copyfile")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("path".into(), "/a.txt".into()),
+                Matcher::UrlEncoded("tofolderid".into(), "0".into()),
+                Matcher::UrlEncoded("toname".into(), "b.txt".into()),
+                Matcher::UrlEncoded("noover".into(), "1".into()),
+            ]))
+            .with_status(200)
+            .with_body(r#"{ "result": 2004, "error": "File or folder alredy exists." }"#)
+            .create_async()
+            .await;
+        let client = Client::new(server.url(), Credentials::anonymous()).unwrap();
+        let options = CopyOptions {
+            mode: CopyMode::Create,
+            ..Default::default()
+        };
+        let error = client
+            .copy_opts(&Path::from("a.txt"), &Path::from("b.txt"), options)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(error, ::object_store::Error::AlreadyExists { ref path, .. } if path == "b.txt")
+        );
+        m.assert_async().await;
+    }
+}
@@ -0,0 +1,426 @@
+//! Named profiles, storing the credentials and the region of several accounts.
+//!
+//! Only tokens are stored, never passwords. Profiles are kept in a JSON file readable only
+//! by its owner, `profiles.json` in the `pcloud` configuration folder by default, or in the
+//! keyring of the operating system with the `keyring` feature.
+//!
+//! ```rust,no_run
+//! use pcloud::profile::{FileStore, Profile, ProfileStore};
+//!
+//! # fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let store = FileStore::default_location().expect("no configuration folder");
+//! let profile = Profile::access_token("the-token").with_region(pcloud::Region::Eu);
+//! store.save("work", &profile)?;
+//!
+//! let client = pcloud::Client::builder_from_profile("work")?.build()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::builder::ClientBuilder;
+use crate::{Credentials, Region};
+
+/// Environment variable overriding the location of the profiles file.
+pub const PROFILES_PATH_VAR: &str = "PCLOUD_PROFILES";
+
+/// Errors that may occur when loading or saving profiles.
+#[derive(Debug, thiserror::Error)]
+pub enum ProfileError {
+    /// The profiles file couldn't be read or written.
+    #[error("unable to access {0}")]
+    Io(PathBuf, #[source] std::io::Error),
+    /// The stored profiles couldn't be decoded.
+    #[error("invalid profile format")]
+    Format(#[source] serde_json::Error),
+    /// No profile is stored with the requested name.
+    #[error("profile {0} not found")]
+    NotFound(String),
+    /// No configuration folder could be found to store the profiles.
+    #[error("unable to find the configuration folder")]
+    NoLocation,
+    /// The keyring of the operating system returned an error.
+    #[cfg(feature = "keyring")]
+    #[error("keyring error")]
+    Keyring(#[from] keyring::Error),
+}
+
+/// The credentials and the endpoint of an account.
+#[derive(Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Profile {
+    /// A personal or OAuth2 access token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_token: Option<String>,
+    /// An authorization token, as returned by [`crate::Client::get_token`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<String>,
+    /// The region of the account.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<Region>,
+    /// A custom base URL, taking precedence over the region.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+}
+
+impl std::fmt::Debug for Profile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct(stringify!(Profile))
+            .field("region", &self.region)
+            .field("base_url", &self.base_url)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Profile {
+    /// Creates a profile authenticated with an access token.
+    pub fn access_token(value: impl Into<String>) -> Self {
+        Self {
+            access_token: Some(value.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Creates a profile authenticated with an authorization token.
+    pub fn authorization(value: impl Into<String>) -> Self {
+        Self {
+            auth: Some(value.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Sets the region of the account.
+    pub fn set_region(&mut self, region: Region) {
+        self.region = Some(region);
+    }
+
+    /// Sets the region of the account and returns the modified profile.
+    pub fn with_region(mut self, region: Region) -> Self {
+        self.set_region(region);
+        self
+    }
+
+    /// Sets a custom base URL.
+    pub fn set_base_url(&mut self, value: impl Into<String>) {
+        self.base_url = Some(value.into());
+    }
+
+    /// Sets a custom base URL and returns the modified profile.
+    pub fn with_base_url(mut self, value: impl Into<String>) -> Self {
+        self.set_base_url(value);
+        self
+    }
+
+    /// Returns the credentials of the profile, the access token being preferred.
+    pub fn credentials(&self) -> Credentials {
+        match (self.access_token.as_ref(), self.auth.as_ref()) {
+            (Some(token), _) => Credentials::access_token(token),
+            (None, Some(auth)) => Credentials::authorization(auth),
+            (None, None) => Credentials::anonymous(),
+        }
+    }
+
+    /// Returns the base URL of the profile, falling back to the EU region.
+    pub fn base_url(&self) -> Cow<'static, str> {
+        match (self.base_url.as_ref(), self.region) {
+            (Some(base_url), _) => Cow::Owned(base_url.clone()),
+            (None, Some(region)) => Cow::Borrowed(region.base_url()),
+            (None, None) => Cow::Borrowed(crate::EU_REGION),
+        }
+    }
+}
+
+/// A place where profiles are saved.
+pub trait ProfileStore {
+    /// Loads the profile with the given name, if any.
+    fn load(&self, name: &str) -> Result<Option<Profile>, ProfileError>;
+
+    /// Saves the profile under the given name, replacing any existing one.
+    fn save(&self, name: &str, profile: &Profile) -> Result<(), ProfileError>;
+
+    /// Removes the profile with the given name, returns `false` when there was none.
+    fn remove(&self, name: &str) -> Result<bool, ProfileError>;
+}
+
+/// Stores the profiles in a JSON file, only readable and writable by its owner.
+#[derive(Clone, Debug)]
+pub struct FileStore {
+    path: PathBuf,
+}
+
+impl FileStore {
+    /// Creates a store using the file at the given path.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Creates a store using the file given by `PCLOUD_PROFILES`, or `pcloud/profiles.json`
+    /// in the configuration folder of the user.
+    pub fn default_location() -> Option<Self> {
+        std::env::var_os(PROFILES_PATH_VAR)
+            .map(PathBuf::from)
+            .or_else(|| dirs::config_dir().map(|dir| dir.join("pcloud").join("profiles.json")))
+            .map(Self::new)
+    }
+
+    /// Returns the path of the profiles file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the names of the stored profiles.
+    pub fn names(&self) -> Result<Vec<String>, ProfileError> {
+        Ok(self.read()?.into_keys().collect())
+    }
+
+    fn read(&self) -> Result<BTreeMap<String, Profile>, ProfileError> {
+        let content = match std::fs::read(&self.path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+            Err(err) => return Err(ProfileError::Io(self.path.clone(), err)),
+        };
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            if let Ok(metadata) = std::fs::metadata(&self.path) {
+                if metadata.permissions().mode() & 0o077 != 0 {
+                    tracing::warn!(
+                        path = %self.path.display(),
+                        "profiles file is accessible by other users"
+                    );
+                }
+            }
+        }
+        serde_json::from_slice(&content).map_err(ProfileError::Format)
+    }
+
+    /// Writes the profiles in a temporary file, then moves it in place.
+    fn write(&self, profiles: &BTreeMap<String, Profile>) -> Result<(), ProfileError> {
+        use std::io::Write;
+
+        let io_error = |err| ProfileError::Io(self.path.clone(), err);
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            let mut builder = std::fs::DirBuilder::new();
+            builder.recursive(true);
+            #[cfg(unix)]
+            std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+            builder.create(parent).map_err(io_error)?;
+        }
+        let content = serde_json::to_vec_pretty(profiles).map_err(ProfileError::Format)?;
+        let tmp_path = self.path.with_extension("tmp");
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(&tmp_path).map_err(io_error)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            file.set_permissions(std::fs::Permissions::from_mode(0o600))
+                .map_err(io_error)?;
+        }
+        file.write_all(&content).map_err(io_error)?;
+        file.sync_all().map_err(io_error)?;
+        std::fs::rename(&tmp_path, &self.path).map_err(io_error)
+    }
+}
+
+impl ProfileStore for FileStore {
+    fn load(&self, name: &str) -> Result<Option<Profile>, ProfileError> {
+        Ok(self.read()?.remove(name))
+    }
+
+    fn save(&self, name: &str, profile: &Profile) -> Result<(), ProfileError> {
+        let mut profiles = self.read()?;
+        profiles.insert(name.to_owned(), profile.clone());
+        self.write(&profiles)
+    }
+
+    fn remove(&self, name: &str) -> Result<bool, ProfileError> {
+        let mut profiles = self.read()?;
+        if profiles.remove(name).is_none() {
+            return Ok(false);
+        }
+        self.write(&profiles)?;
+        Ok(true)
+    }
+}
+
+/// Stores each profile as a secret in the keyring of the operating system.
+#[cfg(feature = "keyring")]
+#[derive(Clone, Debug)]
+pub struct KeyringStore {
+    service: String,
+}
+
+#[cfg(feature = "keyring")]
+impl Default for KeyringStore {
+    /// Creates a store using the `pcloud` service.
+    fn default() -> Self {
+        Self::new("pcloud")
+    }
+}
+
+#[cfg(feature = "keyring")]
+impl KeyringStore {
+    /// Creates a store saving the profiles under the given service name.
+    pub fn new(service: impl Into<String>) -> Self {
+        Self {
+            service: service.into(),
+        }
+    }
+
+    fn entry(&self, name: &str) -> Result<keyring::Entry, ProfileError> {
+        Ok(keyring::Entry::new(&self.service, name)?)
+    }
+}
+
+#[cfg(feature = "keyring")]
+impl ProfileStore for KeyringStore {
+    fn load(&self, name: &str) -> Result<Option<Profile>, ProfileError> {
+        match self.entry(name)?.get_password() {
+            Ok(secret) => serde_json::from_str(&secret)
+                .map(Some)
+                .map_err(ProfileError::Format),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn save(&self, name: &str, profile: &Profile) -> Result<(), ProfileError> {
+        let secret = serde_json::to_string(profile).map_err(ProfileError::Format)?;
+        Ok(self.entry(name)?.set_password(&secret)?)
+    }
+
+    fn remove(&self, name: &str) -> Result<bool, ProfileError> {
+        match self.entry(name)?.delete_credential() {
+            Ok(()) => Ok(true),
+            Err(keyring::Error::NoEntry) => Ok(false),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+impl ClientBuilder {
+    /// Creates a builder configured with a stored profile.
+    ///
+    /// The profile is loaded from [`FileStore::default_location`] and, with the `keyring`
+    /// feature, from the keyring of the operating system when not found in the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProfileError::NotFound`] when no profile has that name, or a [`ProfileError`]
+    /// if the profiles couldn't be read.
+    pub fn from_profile(name: &str) -> Result<Self, ProfileError> {
+        let store = FileStore::default_location().ok_or(ProfileError::NoLocation)?;
+        #[cfg(feature = "keyring")]
+        if store.load(name)?.is_none() {
+            return Self::from_profile_in(&KeyringStore::default(), name);
+        }
+        Self::from_profile_in(&store, name)
+    }
+
+    /// Creates a builder configured with a profile of the given store.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProfileError::NotFound`] when no profile has that name, or a [`ProfileError`]
+    /// if the profiles couldn't be read.
+    pub fn from_profile_in(store: &impl ProfileStore, name: &str) -> Result<Self, ProfileError> {
+        let profile = store
+            .load(name)?
+            .ok_or_else(|| ProfileError::NotFound(name.to_owned()))?;
+        Ok(Self::default()
+            .with_base_url(profile.base_url())
+            .with_credentials(profile.credentials()))
+    }
+}
+
+impl crate::Client {
+    /// Creates a builder configured with a stored profile.
+    ///
+    /// See [`ClientBuilder::from_profile`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ProfileError`] if the profile couldn't be loaded.
+    pub fn builder_from_profile(name: &str) -> Result<ClientBuilder, ProfileError> {
+        ClientBuilder::from_profile(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FileStore, Profile, ProfileError, ProfileStore};
+    use crate::builder::ClientBuilder;
+    use crate::testing::TempDir;
+    use crate::Region;
+
+    /// Returns a store in a temporary directory, removed with the profiles when dropped.
+    fn temp_store(name: &str) -> (TempDir, FileStore) {
+        let dir = TempDir::new(&format!("profiles-{name}"));
+        let store = FileStore::new(dir.join("profiles.json"));
+        (dir, store)
+    }
+
+    #[test]
+    fn should_save_and_load_profiles() {
+        let (_dir, store) = temp_store("roundtrip");
+        assert!(store.load("work").unwrap().is_none());
+
+        let work = Profile::access_token("token").with_region(Region::Us);
+        store.save("work", &work).unwrap();
+        store
+            .save(
+                "home",
+                &Profile::authorization("auth").with_base_url("http://local"),
+            )
+            .unwrap();
+
+        assert_eq!(store.load("work").unwrap(), Some(work));
+        assert_eq!(store.names().unwrap(), vec!["home", "work"]);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let mode = std::fs::metadata(store.path())
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        assert!(store.remove("work").unwrap());
+        assert!(!store.remove("work").unwrap());
+        assert_eq!(store.names().unwrap(), vec!["home"]);
+    }
+
+    #[test]
+    fn should_build_client_from_profile() {
+        let (_dir, store) = temp_store("builder");
+        store
+            .save(
+                "home",
+                &Profile::authorization("auth").with_region(Region::Us),
+            )
+            .unwrap();
+
+        let client = ClientBuilder::from_profile_in(&store, "home")
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(client.base_url, crate::US_REGION);
+        assert!(matches!(
+            client.credentials,
+            crate::Credentials::Authorization { ref auth } if auth == "auth"
+        ));
+        assert!(matches!(
+            ClientBuilder::from_profile_in(&store, "missing"),
+            Err(ProfileError::NotFound(_))
+        ));
+    }
+}
@@ -4,12 +4,20 @@ use crate::{Credentials, Error};
 
 /// Reads the HTTP response and attempts to deserialize it into a type `T`.
 /// If the response is successful, it returns the payload, otherwise, it returns an error.
+///
+/// When the body can't be decoded and the HTTP status isn't a success (a proxy error page,
+/// a 5xx without a JSON body...), an [`Error::Http`] is returned instead of a decoding error.
 async fn read_response<T: serde::de::DeserializeOwned>(res: reqwest::Response) -> Result<T, Error> {
     let status = res.status();
     tracing::debug!("responded with status {status:?}");
     let body = res.bytes().await?;
     match serde_json::from_slice::<Response<T>>(&body) {
         Ok(parsed) => parsed.payload(),
+        Err(_) if !status.is_success() => {
+            let preview = String::from_utf8_lossy(&body);
+            tracing::error!("request failed with status {status} body={preview}");
+            Err(Error::Http(status, preview.into_owned()))
+        }
         Err(err) => {
             let preview = String::from_utf8_lossy(&body);
             tracing::error!("failed to decode response: {err} body={preview}");
@@ -32,6 +40,74 @@ impl crate::Client {
         format!("{}/{}", self.base_url, method)
     }
 
+    /// Waits for the concurrency and rate limits of the client, if any.
+    ///
+    /// The returned permit must be kept until the response has been read.
+    async fn throttle(&self, method: &str) -> Option<tokio::sync::SemaphorePermit<'_>> {
+        match self.throttle {
+            Some(ref throttle) => throttle.acquire(method).await,
+            None => None,
+        }
+    }
+
+    /// Adds the options of the client to the parameters of a request.
+    fn with_options<'a, P>(&self, params: &'a P) -> WithOptions<'a, P> {
+        WithOptions {
+            time_format: match self.time_format {
+                crate::TimeFormat::Rfc2822 => None,
+                crate::TimeFormat::Timestamp => Some("timestamp"),
+            },
+            inner: params,
+        }
+    }
+
+    /// Returns where the credentials must be placed in a request.
+    fn placement<'a>(&self, credentials: &'a Credentials) -> Placement<'a> {
+        match (self.credentials_mode, credentials) {
+            (_, Credentials::Anonymous) => Placement::None,
+            (crate::CredentialsMode::Query, _) => Placement::Query,
+            (crate::CredentialsMode::Hidden, Credentials::AccessToken { access_token }) => {
+                Placement::Bearer(access_token)
+            }
+            (crate::CredentialsMode::Hidden, _) => Placement::Body,
+        }
+    }
+
+    /// Sends a request and deserializes the response, calling the hooks of the client around it.
+    async fn execute<T: serde::de::DeserializeOwned>(
+        &self,
+        method: &str,
+        request: reqwest::RequestBuilder,
+    ) -> Result<T, Error> {
+        let mut request = request.build().map_err(redact_url)?;
+        if self.hooks.is_empty() {
+            let res = self.inner.execute(request).await.map_err(redact_url)?;
+            return read_response(res).await;
+        }
+        let params = redacted_params(&request);
+        // the hooks never see the access token, the header is put back once they're done
+        let authorization = request.headers_mut().remove(reqwest::header::AUTHORIZATION);
+        self.hooks
+            .before_request(method, &params, request.headers_mut());
+        if let Some(value) = authorization {
+            request
+                .headers_mut()
+                .insert(reqwest::header::AUTHORIZATION, value);
+        }
+        let start = std::time::Instant::now();
+        let (status, result) = match self.inner.execute(request).await {
+            Ok(res) => (Some(res.status()), read_response(res).await),
+            Err(err) => (None, Err(redact_url(err))),
+        };
+        let code = match result {
+            Ok(_) => Some(0),
+            Err(ref err) => err.api_code().map(u16::from),
+        };
+        self.hooks
+            .after_response(method, &params, status, start.elapsed(), code);
+        result
+    }
+
     /// Sends a GET request with query parameters and deserializes the response into type `T`.
     ///
     /// # Arguments
@@ -48,17 +124,47 @@ impl crate::Client {
         method: &str,
         params: P,
     ) -> Result<T, Error> {
-        let uri = self.build_url(method);
-        let res = self
-            .inner
-            .get(uri)
-            .query(&WithCredentials {
-                credentials: &self.credentials,
-                inner: params,
-            })
-            .send()
-            .await?;
-        read_response(res).await
+        let credentials = self.request_credentials().await?;
+        let result = self.send_get_request(method, &params, &credentials).await;
+        if let Err(ref err) = result {
+            if let Some(renewed) = self.renewed_credentials(&credentials, err).await {
+                return self.send_get_request(method, &params, &renewed?).await;
+            }
+        }
+        result
+    }
+
+    /// Sends a GET request with the given credentials, retrying it according to the retry policy.
+    async fn send_get_request<T: serde::de::DeserializeOwned, P: serde::Serialize>(
+        &self,
+        method: &str,
+        params: &P,
+        credentials: &Credentials,
+    ) -> Result<T, Error> {
+        let uri = &self.build_url(method);
+        let params = &self.with_options(params);
+        let send = move || async move {
+            let _permit = self.throttle(method).await;
+            let request = match self.placement(credentials) {
+                Placement::Query => self.inner.get(uri).query(&WithCredentials {
+                    credentials,
+                    inner: params,
+                }),
+                Placement::Bearer(token) => self.inner.get(uri).query(params).bearer_auth(token),
+                Placement::Body => self.inner.post(uri).form(&WithCredentials {
+                    credentials,
+                    inner: params,
+                }),
+                Placement::None => self.inner.get(uri).query(params),
+            };
+            self.execute(method, request).await
+        };
+        match self.retry_policy {
+            Some(ref policy) if crate::retry::is_idempotent(method) => {
+                policy.run(method, send(), || Some(send())).await
+            }
+            _ => send().await,
+        }
     }
 
     /// Sends a GET request without attaching the client's credentials.
@@ -76,54 +182,28 @@ impl crate::Client {
         params: P,
     ) -> Result<T, Error> {
         let uri = self.build_url(method);
-        let res = self.inner.get(uri).query(&params).send().await?;
-        read_response(res).await
-    }
-
-    /// Sends a PUT request with query parameters and a binary payload, and deserializes the response into type `T`.
-    ///
-    /// # Arguments
-    ///
-    /// * `method` - The method or endpoint to be used in the request.
-    /// * `params` - The parameters to be sent with the PUT request.
-    /// * `payload` - The binary payload to be included in the body of the PUT request.
-    ///
-    /// # Returns
-    ///
-    /// A result containing the deserialized response payload or an error.
-    #[tracing::instrument(name = "put", skip(self, params))]
-    pub(crate) async fn put_request_data<T: serde::de::DeserializeOwned, P: serde::Serialize>(
-        &self,
-        method: &str,
-        params: P,
-        payload: Vec<u8>,
-    ) -> Result<T, Error> {
-        let uri = self.build_url(method);
-        let res = self
-            .inner
-            .put(uri)
-            .query(&WithCredentials {
-                credentials: &self.credentials,
-                inner: params,
-            })
-            .body(payload)
-            .send()
-            .await?;
-        read_response(res).await
+        let params = self.with_options(&params);
+        let _permit = self.throttle(method).await;
+        let request = match self.credentials_mode {
+            crate::CredentialsMode::Hidden => self.inner.post(uri).form(&params),
+            crate::CredentialsMode::Query => self.inner.get(uri).query(&params),
+        };
+        self.execute(method, request).await
     }
 
-    /// Sends a POST request with multipart form data and query parameters, and deserializes the response into type `T`.
+    /// Sends a POST request with the files as multipart form data and query parameters,
+    /// and deserializes the response into type `T`.
     ///
     /// # Arguments
     ///
     /// * `method` - The method or endpoint to be used in the request.
     /// * `params` - The parameters to be sent with the POST request.
-    /// * `form` - The multipart form data to be included in the body of the POST request.
+    /// * `files` - The files to be included as multipart form data in the body of the POST request.
     ///
     /// # Returns
     ///
     /// A result containing the deserialized response payload or an error.
-    #[tracing::instrument(name = "post", skip(self, params))]
+    #[tracing::instrument(name = "post", skip(self, params, files))]
     pub(crate) async fn post_request_multipart<
         T: serde::de::DeserializeOwned,
         P: serde::Serialize,
@@ -131,23 +211,179 @@ impl crate::Client {
         &self,
         method: &str,
         params: P,
-        form: reqwest::multipart::Form,
+        files: crate::file::upload::MultiFileUpload,
     ) -> Result<T, Error> {
-        let uri = self.build_url(method);
-        let res = self
-            .inner
-            .post(uri)
-            .query(&WithCredentials {
-                credentials: &self.credentials,
-                inner: params,
+        let credentials = self.request_credentials().await?;
+        // only the files held in memory can be sent again after logging in
+        let replay = self.session.as_ref().and_then(|_| files.try_clone());
+        let result = self
+            .send_post_request_multipart(method, &params, &credentials, files)
+            .await;
+        if let (Err(ref err), Some(files)) = (&result, replay) {
+            if let Some(renewed) = self.renewed_credentials(&credentials, err).await {
+                return self
+                    .send_post_request_multipart(method, &params, &renewed?, files)
+                    .await;
+            }
+        }
+        result
+    }
+
+    /// Sends a multipart POST request with the given credentials, retrying it according to the retry policy.
+    async fn send_post_request_multipart<T: serde::de::DeserializeOwned, P: serde::Serialize>(
+        &self,
+        method: &str,
+        params: &P,
+        credentials: &Credentials,
+        files: crate::file::upload::MultiFileUpload,
+    ) -> Result<T, Error> {
+        let uri = &self.build_url(method);
+        let params = &self.with_options(params);
+        let send = move |files: crate::file::upload::MultiFileUpload| async move {
+            let _permit = self.throttle(method).await;
+            let post = self.inner.post(uri);
+            let (request, form) = match self.placement(credentials) {
+                Placement::Query => (
+                    post.query(&WithCredentials {
+                        credentials,
+                        inner: params,
+                    }),
+                    reqwest::multipart::Form::new(),
+                ),
+                Placement::Bearer(token) => (
+                    post.query(params).bearer_auth(token),
+                    reqwest::multipart::Form::new(),
+                ),
+                // the fields are placed before the files, the API reading them first
+                Placement::Body => (
+                    post.query(params),
+                    credential_fields(credentials)
+                        .into_iter()
+                        .fold(reqwest::multipart::Form::new(), |form, (name, value)| {
+                            form.text(name, value)
+                        }),
+                ),
+                Placement::None => (post.query(params), reqwest::multipart::Form::new()),
+            };
+            self.execute(method, request.multipart(files.into_form(form)))
+                .await
+        };
+        match self.retry_policy {
+            Some(ref policy) if policy.retries_uploads() => {
+                let mut replay = files.try_clone();
+                let first = send(files);
+                policy
+                    .run(method, first, || {
+                        let current = replay.take()?;
+                        replay = current.try_clone();
+                        Some(send(current))
+                    })
+                    .await
+            }
+            _ => send(files).await,
+        }
+    }
+}
+
+/// Removes the credentials from the URL attached to a request error.
+fn redact_url(mut err: reqwest::Error) -> Error {
+    if let Some(url) = err.url_mut() {
+        redact_query(url);
+    }
+    Error::from(err)
+}
+
+/// Replaces the value of the parameters holding credentials in the query string.
+fn redact_query(url: &mut reqwest::Url) {
+    if !url
+        .query_pairs()
+        .any(|(name, _)| crate::hook::is_secret(&name))
+    {
+        return;
+    }
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(name, value)| {
+            let value = if crate::hook::is_secret(&name) {
+                crate::hook::REDACTED.into()
+            } else {
+                value.into_owned()
+            };
+            (name.into_owned(), value)
+        })
+        .collect();
+    url.query_pairs_mut().clear().extend_pairs(pairs);
+}
+
+/// Where the credentials are placed in a request.
+enum Placement<'a> {
+    /// In the query string, with the other parameters.
+    Query,
+    /// In the `Authorization` header.
+    Bearer(&'a str),
+    /// In the body, as form fields.
+    Body,
+    /// Nowhere, the request isn't authenticated.
+    None,
+}
+
+/// Returns the credentials as a list of fields.
+fn credential_fields(credentials: &Credentials) -> Vec<(String, String)> {
+    match serde_json::to_value(credentials) {
+        Ok(serde_json::Value::Object(fields)) => fields
+            .into_iter()
+            .filter_map(|(name, value)| match value {
+                serde_json::Value::String(value) => Some((name, value)),
+                _ => None,
             })
-            .multipart(form)
-            .send()
-            .await?;
-        read_response(res).await
+            .collect(),
+        _ => Vec::new(),
     }
 }
 
+/// Returns `true` when the body of the request is an url encoded form.
+fn is_form(request: &reqwest::Request) -> bool {
+    request
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .is_some_and(|value| value == "application/x-www-form-urlencoded")
+}
+
+/// Returns the parameters of the request, from the query string and the form body,
+/// without the credentials.
+fn redacted_params(request: &reqwest::Request) -> Vec<(String, String)> {
+    let body = request
+        .body()
+        .filter(|_| is_form(request))
+        .and_then(reqwest::Body::as_bytes)
+        .unwrap_or_default();
+    request
+        .url()
+        .query_pairs()
+        .chain(url::form_urlencoded::parse(body))
+        .map(|(name, value)| {
+            let value = if crate::hook::is_secret(&name) {
+                crate::hook::REDACTED.into()
+            } else {
+                value.into_owned()
+            };
+            (name.into_owned(), value)
+        })
+        .collect()
+}
+
+/// Struct for serializing the options of the client along with the request parameters.
+#[derive(serde::Serialize)]
+struct WithOptions<'a, I> {
+    /// The format of the dates in the response, RFC 2822 when not specified.
+    #[serde(rename = "timeformat", skip_serializing_if = "Option::is_none")]
+    time_format: Option<&'static str>,
+
+    /// The parameters to be sent with the request.
+    #[serde(flatten)]
+    inner: &'a I,
+}
+
 /// Struct for serializing credentials along with the request parameters.
 ///
 /// # Type Parameters
@@ -218,8 +454,157 @@ impl<T> Response<T> {
     /// A result containing the payload if the response is a success, or an error if the response is an error.
     fn payload(self) -> Result<T, Error> {
         match self {
-            Self::Error { result, error } => Err(Error::Protocol(result, error)),
+            Self::Error { result, error } => Err(Error::Protocol(result.into(), error)),
             Self::Success { payload, .. } => Ok(payload),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{Client, Credentials};
+
+    #[tokio::test]
+    async fn should_return_http_error_on_html_page() {
+        let mut server = mockito::Server::new_async().await;
+        let m = server
+            .mock("GET", "/userinfo")
+            .match_query(mockito::Matcher::Any)
+            .with_status(502)
+            .with_header("content-type", "text/html")
+            .with_body("<html><body>Bad Gateway</body></html>")
+            .create_async()
+            .await;
+        let client = Client::new(server.url(), Credentials::access_token("access-token")).unwrap();
+        let error = client.user_info().await.unwrap_err();
+        assert!(matches!(error, crate::Error::Http(status, _) if status.as_u16() == 502));
+        assert!(error.is_retryable());
+        m.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn should_return_protocol_error_with_typed_code() {
+        let mut server = mockito::Server::new_async().await;
+        let m = server
+            .mock("GET", "/userinfo")
+            .match_query(mockito::Matcher::Any)
+            .with_status(200)
+            .with_body(r#"{ "result": 1000, "error": "Log in required." }"#)
+            .create_async()
+            .await;
+        let client = Client::new(server.url(), Credentials::anonymous()).unwrap();
+        let error = client.user_info().await.unwrap_err();
+        assert_eq!(
+            error.api_code(),
+            Some(crate::error::ApiErrorCode::LoginRequired)
+        );
+        assert!(error.is_auth_error());
+        assert!(!error.is_retryable());
+        m.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn should_send_credentials_in_form_body() {
+        let mut server = mockito::Server::new_async().await;
+        let m = server
+            .mock("POST", "/userinfo")
+            .match_query(mockito::Matcher::Missing)
+            .match_header("content-type", "application/x-www-form-urlencoded")
+            .match_body(mockito::Matcher::UrlEncoded(
+                "auth".into(),
+                "the-token".into(),
+            ))
+            .with_status(200)
+            .with_body(r#"{"result": 2000, "error": "Log in failed."}"#)
+            .create_async()
+            .await;
+        let client = Client::new(server.url(), Credentials::authorization("the-token")).unwrap();
+        client.user_info().await.unwrap_err();
+        m.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn should_send_credentials_in_query_when_requested() {
+        let mut server = mockito::Server::new_async().await;
+        let m = server
+            .mock("GET", "/userinfo")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "access_token".into(),
+                "access-token".into(),
+            ))
+            .match_header("authorization", mockito::Matcher::Missing)
+            .with_status(200)
+            .with_body(r#"{"result": 2000, "error": "Log in failed."}"#)
+            .create_async()
+            .await;
+        let client = Client::builder()
+            .with_base_url(server.url())
+            .with_credentials(Credentials::access_token("access-token"))
+            .with_credentials_mode(crate::CredentialsMode::Query)
+            .build()
+            .unwrap();
+        client.user_info().await.unwrap_err();
+        m.assert_async().await;
+    }
+
+    #[test]
+    fn should_redact_credentials_in_urls() {
+        let mut url =
+            reqwest::Url::parse("https://eapi.pcloud.com/listfolder?auth=secret&folderid=0")
+                .unwrap();
+        super::redact_query(&mut url);
+        assert_eq!(
+            url.as_str(),
+            "https://eapi.pcloud.com/listfolder?auth=%3Credacted%3E&folderid=0"
+        );
+
+        let mut url =
+            reqwest::Url::parse("https://eapi.pcloud.com/oauth2_token?client_id=app&code=secret")
+                .unwrap();
+        super::redact_query(&mut url);
+        assert_eq!(
+            url.as_str(),
+            "https://eapi.pcloud.com/oauth2_token?client_id=app&code=%3Credacted%3E"
+        );
+    }
+
+    #[tokio::test]
+    async fn should_request_timestamps() {
+        let mut server = mockito::Server::new_async().await;
+        let m = server
+            .mock("GET", "/listfolder")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("timeformat".into(), "timestamp".into()),
+                mockito::Matcher::UrlEncoded("folderid".into(), "0".into()),
+            ]))
+            .with_status(200)
+            .with_body(
+                r#"{
+    "result": 0,
+    "metadata": {
+        "name": "/",
+        "created": 1627067949,
+        "modified": 1627067949,
+        "ismine": true,
+        "thumb": false,
+        "id": "d0",
+        "isshared": false,
+        "icon": "folder",
+        "isfolder": true,
+        "folderid": 0
+    }
+}"#,
+            )
+            .create_async()
+            .await;
+        let client = Client::builder()
+            .with_base_url(server.url())
+            .with_credentials(Credentials::access_token("access-token"))
+            .with_time_format(crate::TimeFormat::Timestamp)
+            .build()
+            .unwrap();
+        let folder = client.list_folder(0).await.unwrap();
+        assert_eq!(folder.base.created.timestamp(), 1627067949);
+        m.assert_async().await;
+    }
+}
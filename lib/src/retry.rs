@@ -0,0 +1,372 @@
+//! Retry policy applied by the request layer on transient failures.
+
+use std::future::Future;
+use std::time::Duration;
+
+/// API methods that can safely be sent several times without changing the outcome.
+///
+/// Only those are retried automatically, the other methods (creating, renaming,
+/// deleting...) could be applied twice if the first attempt reached the server.
+const IDEMPOTENT_METHODS: &[&str] = &[
+    "checksumfile",
+    "createfolderifnotexists",
+    "getaudiolink",
+    "getdigest",
+    "getfilelink",
+    "getvideolink",
+    "listfolder",
+    "userinfo",
+];
+
+/// Returns `true` when the API method can be retried without side effects.
+pub(crate) fn is_idempotent(method: &str) -> bool {
+    IDEMPOTENT_METHODS.contains(&method)
+}
+
+/// Configuration of the retry mechanism used by the [`Client`](crate::Client).
+///
+/// Failed requests are retried with an exponential backoff, starting at
+/// `initial_backoff` and doubling on every attempt up to `max_backoff`.
+/// When jitter is enabled, the actual delay is picked randomly between
+/// zero and the computed backoff to avoid synchronized retries.
+///
+/// Only idempotent methods are retried. Uploads are only retried when enabled
+/// with [`RetryPolicy::with_uploads`] and when every file body can be replayed
+/// (in memory content, not streams).
+///
+/// ```rust
+/// use std::time::Duration;
+/// use pcloud::retry::RetryPolicy;
+///
+/// let policy = RetryPolicy::default()
+///     .with_max_attempts(5)
+///     .with_initial_backoff(Duration::from_millis(100))
+///     .with_rate_limited(false);
+/// let client = pcloud::Client::builder()
+///     .with_retry_policy(policy)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    jitter: bool,
+    network: bool,
+    server_error: bool,
+    rate_limited: bool,
+    uploads: bool,
+}
+
+impl Default for RetryPolicy {
+    /// Creates a policy with 3 attempts, a backoff between 200ms and 10s with jitter,
+    /// retrying network errors, server errors and rate limiting but not uploads.
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(10),
+            jitter: true,
+            network: true,
+            server_error: true,
+            rate_limited: true,
+            uploads: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Sets the maximum number of attempts, including the first one.
+    pub fn set_max_attempts(&mut self, value: u32) {
+        self.max_attempts = value.max(1);
+    }
+
+    /// Sets the maximum number of attempts and returns the updated policy.
+    pub fn with_max_attempts(mut self, value: u32) -> Self {
+        self.set_max_attempts(value);
+        self
+    }
+
+    /// Sets the delay before the first retry.
+    pub fn set_initial_backoff(&mut self, value: Duration) {
+        self.initial_backoff = value;
+    }
+
+    /// Sets the delay before the first retry and returns the updated policy.
+    pub fn with_initial_backoff(mut self, value: Duration) -> Self {
+        self.set_initial_backoff(value);
+        self
+    }
+
+    /// Sets the upper bound of the delay between two attempts.
+    pub fn set_max_backoff(&mut self, value: Duration) {
+        self.max_backoff = value;
+    }
+
+    /// Sets the upper bound of the delay between two attempts and returns the updated policy.
+    pub fn with_max_backoff(mut self, value: Duration) -> Self {
+        self.set_max_backoff(value);
+        self
+    }
+
+    /// Enables or disables the randomization of the delay between two attempts.
+    pub fn set_jitter(&mut self, value: bool) {
+        self.jitter = value;
+    }
+
+    /// Enables or disables the jitter and returns the updated policy.
+    pub fn with_jitter(mut self, value: bool) -> Self {
+        self.set_jitter(value);
+        self
+    }
+
+    /// Enables or disables retrying on network errors (connection reset, timeout...).
+    pub fn set_network(&mut self, value: bool) {
+        self.network = value;
+    }
+
+    /// Enables or disables retrying on network errors and returns the updated policy.
+    pub fn with_network(mut self, value: bool) -> Self {
+        self.set_network(value);
+        self
+    }
+
+    /// Enables or disables retrying on HTTP 5xx and API internal errors.
+    pub fn set_server_error(&mut self, value: bool) {
+        self.server_error = value;
+    }
+
+    /// Enables or disables retrying on server errors and returns the updated policy.
+    pub fn with_server_error(mut self, value: bool) -> Self {
+        self.set_server_error(value);
+        self
+    }
+
+    /// Enables or disables retrying when the API is throttling the client.
+    pub fn set_rate_limited(&mut self, value: bool) {
+        self.rate_limited = value;
+    }
+
+    /// Enables or disables retrying on rate limiting and returns the updated policy.
+    pub fn with_rate_limited(mut self, value: bool) -> Self {
+        self.set_rate_limited(value);
+        self
+    }
+
+    /// Enables or disables retrying uploads.
+    pub fn set_uploads(&mut self, value: bool) {
+        self.uploads = value;
+    }
+
+    /// Enables or disables retrying uploads and returns the updated policy.
+    pub fn with_uploads(mut self, value: bool) -> Self {
+        self.set_uploads(value);
+        self
+    }
+
+    /// Returns `true` when uploads are allowed to be retried.
+    pub(crate) fn retries_uploads(&self) -> bool {
+        self.uploads
+    }
+
+    /// Checks if the error belongs to one of the classes configured to be retried.
+    fn should_retry(&self, error: &crate::Error) -> bool {
+        if !error.is_retryable() {
+            return false;
+        }
+        match error {
+            crate::Error::Reqwest(_) => self.network,
+            _ if error.is_rate_limited() => self.rate_limited,
+            _ => self.server_error,
+        }
+    }
+
+    /// Computes the delay to wait before the next attempt.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let delay = self
+            .initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff);
+        if self.jitter && !delay.is_zero() {
+            Duration::from_millis(rand::random_range(0..=delay.as_millis() as u64))
+        } else {
+            delay
+        }
+    }
+
+    /// Awaits `first` and, on failure, retries with the requests produced by `next` until
+    /// one succeeds, fails with an error that shouldn't be retried or the maximum number
+    /// of attempts is reached.
+    ///
+    /// `next` returns `None` when the request can't be built again, in which case
+    /// the last error is returned.
+    pub(crate) async fn run<T, F, Fut>(
+        &self,
+        method: &str,
+        first: Fut,
+        mut next: F,
+    ) -> crate::Result<T>
+    where
+        F: FnMut() -> Option<Fut>,
+        Fut: Future<Output = crate::Result<T>>,
+    {
+        let mut count = 1;
+        let mut current = first;
+        loop {
+            let error = match current.await {
+                Ok(value) => return Ok(value),
+                Err(error) => error,
+            };
+            if count >= self.max_attempts || !self.should_retry(&error) {
+                return Err(error);
+            }
+            let Some(following) = next() else {
+                return Err(error);
+            };
+            let delay = self.backoff(count);
+            tracing::warn!(
+                method,
+                attempt = count,
+                delay_ms = delay.as_millis() as u64,
+                "request failed, retrying: {error}"
+            );
+            tokio::time::sleep(delay).await;
+            count += 1;
+            current = following;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::RetryPolicy;
+    use crate::file::upload::MultiFileUpload;
+    use crate::{Client, Credentials};
+    use mockito::Matcher;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy::default()
+            .with_initial_backoff(Duration::from_millis(1))
+            .with_jitter(false)
+    }
+
+    #[test]
+    fn should_compute_exponential_backoff() {
+        let policy = RetryPolicy::default()
+            .with_initial_backoff(Duration::from_millis(100))
+            .with_max_backoff(Duration::from_millis(300))
+            .with_jitter(false);
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(300));
+        assert_eq!(policy.backoff(30), Duration::from_millis(300));
+    }
+
+    #[tokio::test]
+    async fn should_retry_idempotent_method() {
+        let mut server = mockito::Server::new_async().await;
+        let m = server
+            .mock("GET", "/listfolder")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_body(r#"{ "result": 5000, "error": "Internal error. Try again later." }"#)
+            .expect(3)
+            .create_async()
+            .await;
+        let client = Client::builder()
+            .with_base_url(server.url())
+            .with_credentials(Credentials::access_token("access-token"))
+            .with_retry_policy(policy())
+            .build()
+            .unwrap();
+        let error = client.list_folder(0).await.unwrap_err();
+        assert!(error.is_retryable());
+        m.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn should_not_retry_when_disabled() {
+        let mut server = mockito::Server::new_async().await;
+        let m = server
+            .mock("GET", "/listfolder")
+            .match_query(Matcher::Any)
+            .with_status(503)
+            .with_body("<html>unavailable</html>")
+            .expect(1)
+            .create_async()
+            .await;
+        let client = Client::builder()
+            .with_base_url(server.url())
+            .with_credentials(Credentials::access_token("access-token"))
+            .with_retry_policy(policy().with_server_error(false))
+            .build()
+            .unwrap();
+        let _ = client.list_folder(0).await.unwrap_err();
+        m.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn should_not_retry_non_idempotent_method() {
+        let mut server = mockito::Server::new_async().await;
+        let m = server
+            .mock("GET", "/createfolder")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_body(r#"{ "result": 5000, "error": "Internal error. Try again later." }"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let client = Client::builder()
+            .with_base_url(server.url())
+            .with_credentials(Credentials::access_token("access-token"))
+            .with_retry_policy(policy())
+            .build()
+            .unwrap();
+        let _ = client.create_folder(0, "foo").await.unwrap_err();
+        m.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn should_retry_uploads_only_when_enabled() {
+        let mut server = mockito::Server::new_async().await;
+        let m = server
+            .mock("POST", "/uploadfile")
+            .match_query(Matcher::Any)
+            .with_status(502)
+            .with_body("bad gateway")
+            .expect(1)
+            .create_async()
+            .await;
+        let client = Client::builder()
+            .with_base_url(server.url())
+            .with_credentials(Credentials::access_token("access-token"))
+            .with_retry_policy(policy())
+            .build()
+            .unwrap();
+        let files = MultiFileUpload::default().with_body_entry("foo.txt", None, "hello");
+        let _ = client.upload_files(0, files).await.unwrap_err();
+        m.assert_async().await;
+
+        let m = server
+            .mock("POST", "/uploadfile")
+            .match_query(Matcher::Any)
+            .with_status(502)
+            .with_body("bad gateway")
+            .expect(3)
+            .create_async()
+            .await;
+        let client = Client::builder()
+            .with_base_url(server.url())
+            .with_credentials(Credentials::access_token("access-token"))
+            .with_retry_policy(policy().with_uploads(true))
+            .build()
+            .unwrap();
+        let files = MultiFileUpload::default().with_body_entry("foo.txt", None, "hello");
+        let _ = client.upload_files(0, files).await.unwrap_err();
+        m.assert_async().await;
+    }
+}
@@ -0,0 +1,392 @@
+//! Search for files and folders in a tree, on top of [`crate::Client::walk`].
+
+use std::ops::{Bound, RangeBounds};
+
+use chrono::{DateTime, Utc};
+use futures_core::Stream;
+use futures_util::TryStreamExt;
+
+use crate::entry::Entry;
+use crate::folder::walk::{RemotePath, WalkFilter, WalkOptions, WalkStrategy};
+use crate::folder::FolderIdentifier;
+
+/// Error returned when a pattern can't be compiled.
+#[derive(Debug, thiserror::Error)]
+#[error("invalid pattern {0:?}: {1}")]
+pub struct InvalidPattern(String, String);
+
+/// A glob or a regular expression matched against names or paths.
+#[derive(Clone, Debug)]
+pub enum Pattern {
+    /// A glob like `*.mov` or `photos/**/*.jpg`, where `*` doesn't match `/`.
+    Glob(globset::GlobMatcher),
+    /// A regular expression, matching when found anywhere in the value.
+    Regex(regex::Regex),
+}
+
+impl Pattern {
+    /// Compiles a glob pattern.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`InvalidPattern`] if the glob is malformed.
+    pub fn glob(pattern: &str) -> Result<Self, InvalidPattern> {
+        globset::GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()
+            .map(|glob| Self::Glob(glob.compile_matcher()))
+            .map_err(|err| InvalidPattern(pattern.to_string(), err.to_string()))
+    }
+
+    /// Compiles a regular expression, use `(?i)` for a case insensitive match.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`InvalidPattern`] if the regular expression is malformed.
+    pub fn regex(pattern: &str) -> Result<Self, InvalidPattern> {
+        regex::Regex::new(pattern)
+            .map(Self::Regex)
+            .map_err(|err| InvalidPattern(pattern.to_string(), err.to_string()))
+    }
+
+    /// Checks if the value matches the pattern.
+    pub fn is_match(&self, value: &str) -> bool {
+        match self {
+            Self::Glob(matcher) => matcher.is_match(value),
+            Self::Regex(regex) => regex.is_match(value),
+        }
+    }
+}
+
+/// An owned range, built from any [`RangeBounds`].
+type Range<T> = (Bound<T>, Bound<T>);
+
+fn bounds<T: Clone>(range: impl RangeBounds<T>) -> Range<T> {
+    (range.start_bound().cloned(), range.end_bound().cloned())
+}
+
+fn in_bounds<T: PartialOrd>(range: &Range<T>, value: &T) -> bool {
+    range.contains(value)
+}
+
+/// Criteria of a search, every criterion set must match.
+///
+/// The size and content type criteria only match files.
+///
+/// ```rust
+/// use chrono::{Duration, Utc};
+/// use pcloud::search::{Pattern, SearchQuery};
+///
+/// // all .mov files over 1 GB modified during the last month
+/// let query = SearchQuery::default()
+///     .with_name(Pattern::glob("*.mov").unwrap())
+///     .with_size(1024 * 1024 * 1024..)
+///     .with_modified(Utc::now() - Duration::days(30)..);
+/// ```
+#[derive(Clone, Debug)]
+pub struct SearchQuery {
+    strategy: WalkStrategy,
+    kind: WalkFilter,
+    name: Option<Pattern>,
+    path: Option<Pattern>,
+    size: Option<Range<u64>>,
+    modified: Option<Range<DateTime<Utc>>>,
+    created: Option<Range<DateTime<Utc>>>,
+    content_type: Option<String>,
+    is_mine: Option<bool>,
+}
+
+impl Default for SearchQuery {
+    /// Creates a query matching every entry, listing the folders as they're reached.
+    fn default() -> Self {
+        Self {
+            strategy: WalkStrategy::BreadthFirst,
+            kind: WalkFilter::All,
+            name: None,
+            path: None,
+            size: None,
+            modified: None,
+            created: None,
+            content_type: None,
+            is_mine: None,
+        }
+    }
+}
+
+impl SearchQuery {
+    /// Sets how the tree is listed, see [`WalkStrategy`].
+    ///
+    /// With [`WalkStrategy::Recursive`], the whole tree is fetched before the first match is
+    /// returned, which is faster when most of the tree is searched anyway.
+    pub fn set_strategy(&mut self, value: WalkStrategy) {
+        self.strategy = value;
+    }
+
+    /// Sets how the tree is listed and returns the updated query.
+    pub fn with_strategy(mut self, value: WalkStrategy) -> Self {
+        self.set_strategy(value);
+        self
+    }
+
+    /// Restricts the search to files or folders.
+    pub fn set_kind(&mut self, value: WalkFilter) {
+        self.kind = value;
+    }
+
+    /// Restricts the search to files or folders and returns the updated query.
+    pub fn with_kind(mut self, value: WalkFilter) -> Self {
+        self.set_kind(value);
+        self
+    }
+
+    /// Sets the pattern the name of the entries must match.
+    pub fn set_name(&mut self, value: Pattern) {
+        self.name = Some(value);
+    }
+
+    /// Sets the pattern the name must match and returns the updated query.
+    pub fn with_name(mut self, value: Pattern) -> Self {
+        self.set_name(value);
+        self
+    }
+
+    /// Sets the pattern the path of the entries, relative to the searched folder, must match.
+    pub fn set_path(&mut self, value: Pattern) {
+        self.path = Some(value);
+    }
+
+    /// Sets the pattern the path must match and returns the updated query.
+    pub fn with_path(mut self, value: Pattern) -> Self {
+        self.set_path(value);
+        self
+    }
+
+    /// Sets the range of sizes, in bytes, the files must be in.
+    pub fn set_size(&mut self, value: impl RangeBounds<u64>) {
+        self.size = Some(bounds(value));
+    }
+
+    /// Sets the range of sizes and returns the updated query.
+    pub fn with_size(mut self, value: impl RangeBounds<u64>) -> Self {
+        self.set_size(value);
+        self
+    }
+
+    /// Sets the range of dates the entries must have been modified in.
+    pub fn set_modified(&mut self, value: impl RangeBounds<DateTime<Utc>>) {
+        self.modified = Some(bounds(value));
+    }
+
+    /// Sets the range of modification dates and returns the updated query.
+    pub fn with_modified(mut self, value: impl RangeBounds<DateTime<Utc>>) -> Self {
+        self.set_modified(value);
+        self
+    }
+
+    /// Sets the range of dates the entries must have been created in.
+    pub fn set_created(&mut self, value: impl RangeBounds<DateTime<Utc>>) {
+        self.created = Some(bounds(value));
+    }
+
+    /// Sets the range of creation dates and returns the updated query.
+    pub fn with_created(mut self, value: impl RangeBounds<DateTime<Utc>>) -> Self {
+        self.set_created(value);
+        self
+    }
+
+    /// Sets the prefix the content type of the files must start with (e.g. `video/`).
+    pub fn set_content_type(&mut self, value: impl Into<String>) {
+        self.content_type = Some(value.into());
+    }
+
+    /// Sets the prefix of the content type and returns the updated query.
+    pub fn with_content_type(mut self, value: impl Into<String>) -> Self {
+        self.set_content_type(value);
+        self
+    }
+
+    /// Restricts the search to the entries owned by the user (`true`) or shared with them (`false`).
+    pub fn set_is_mine(&mut self, value: bool) {
+        self.is_mine = Some(value);
+    }
+
+    /// Restricts the search to owned or shared entries and returns the updated query.
+    pub fn with_is_mine(mut self, value: bool) -> Self {
+        self.set_is_mine(value);
+        self
+    }
+
+    /// Checks if an entry matches every criterion of the query.
+    pub fn matches(&self, path: &RemotePath, entry: &Entry) -> bool {
+        let base = entry.base();
+        let file = entry.as_file();
+        if let Some(ref pattern) = self.name {
+            if !pattern.is_match(&base.name) {
+                return false;
+            }
+        }
+        if let Some(ref pattern) = self.path {
+            if !pattern.is_match(path.as_str()) {
+                return false;
+            }
+        }
+        if let Some(ref range) = self.size {
+            match file.and_then(|file| file.size) {
+                Some(size) if in_bounds(range, &size) => {}
+                _ => return false,
+            }
+        }
+        if let Some(ref range) = self.modified {
+            if !in_bounds(range, &base.modified) {
+                return false;
+            }
+        }
+        if let Some(ref range) = self.created {
+            if !in_bounds(range, &base.created) {
+                return false;
+            }
+        }
+        if let Some(ref prefix) = self.content_type {
+            match file.and_then(|file| file.content_type.as_deref()) {
+                Some(content_type) if content_type.starts_with(prefix.as_str()) => {}
+                _ => return false,
+            }
+        }
+        self.is_mine.is_none_or(|is_mine| base.is_mine == is_mine)
+    }
+}
+
+impl crate::Client {
+    /// Searches the tree under a folder for the entries matching a query.
+    ///
+    /// The tree is walked with [`crate::Client::walk`] and the matching entries are
+    /// returned as soon as they are listed, with their path relative to `root`.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - A value convertible into a [`FolderIdentifier`] representing the folder to search in.
+    /// * `query` - The [`SearchQuery`] the entries must match.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use futures_util::StreamExt;
+    /// use pcloud::search::{Pattern, SearchQuery};
+    ///
+    /// # async fn example(client: &pcloud::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let query = SearchQuery::default()
+    ///     .with_name(Pattern::regex(r"(?i)\.mov$")?)
+    ///     .with_size(1024 * 1024 * 1024..);
+    /// let mut results = std::pin::pin!(client.search("/Videos", query));
+    /// while let Some(item) = results.next().await {
+    ///     let (path, _entry) = item?;
+    ///     println!("{path}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn search<'a>(
+        &'a self,
+        root: impl Into<FolderIdentifier<'a>>,
+        query: SearchQuery,
+    ) -> impl Stream<Item = crate::Result<(RemotePath, Entry)>> + 'a {
+        let options = WalkOptions::default()
+            .with_strategy(query.strategy)
+            .with_filter(query.kind);
+        self.walk(root, options).try_filter(move |(path, entry)| {
+            futures_util::future::ready(query.matches(path, entry))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use futures_util::StreamExt;
+    use mockito::Matcher;
+
+    use super::{Pattern, SearchQuery};
+    use crate::entry::Entry;
+    use crate::folder::walk::{RemotePath, WalkStrategy};
+    use crate::testing::{file, folder, response, with_fields};
+    use crate::{Client, Credentials};
+
+    fn entry(name: &str, size: u64, content_type: &str, modified: &str) -> Entry {
+        let metadata = with_fields(
+            file(1, name),
+            serde_json::json!({ "size": size, "contenttype": content_type, "modified": modified }),
+        );
+        serde_json::from_str(&metadata).unwrap()
+    }
+
+    #[test]
+    fn should_match_patterns() {
+        let glob = Pattern::glob("*.mov").unwrap();
+        assert!(glob.is_match("holidays.mov"));
+        assert!(!glob.is_match("videos/holidays.mov"));
+        assert!(Pattern::glob("videos/**/*.mov")
+            .unwrap()
+            .is_match("videos/2024/holidays.mov"));
+        assert!(Pattern::regex(r"(?i)\.MOV$").unwrap().is_match("a.mov"));
+        assert!(Pattern::regex("(").is_err());
+    }
+
+    #[test]
+    fn should_match_criteria() {
+        let entry = entry(
+            "holidays.mov",
+            2_000_000_000,
+            "video/quicktime",
+            "Sat, 10 Aug 2024 10:00:00 +0000",
+        );
+        let path = RemotePath::default().join("videos").join("holidays.mov");
+        let query = SearchQuery::default()
+            .with_name(Pattern::glob("*.mov").unwrap())
+            .with_size(1_000_000_000..)
+            .with_content_type("video/")
+            .with_modified(Utc.with_ymd_and_hms(2024, 8, 1, 0, 0, 0).unwrap()..)
+            .with_is_mine(true);
+        assert!(query.matches(&path, &entry));
+        assert!(!query.clone().with_size(..1_000).matches(&path, &entry));
+        assert!(!query
+            .clone()
+            .with_content_type("image/")
+            .matches(&path, &entry));
+        assert!(!query
+            .clone()
+            .with_path(Pattern::glob("photos/**").unwrap())
+            .matches(&path, &entry));
+        assert!(!query.with_is_mine(false).matches(&path, &entry));
+    }
+
+    #[tokio::test]
+    async fn should_stream_matching_entries() {
+        let mut server = mockito::Server::new_async().await;
+        let m = server
+            .mock("GET", "/listfolder")
+            .match_query(Matcher::UrlEncoded("recursive".into(), "1".into()))
+            .with_status(200)
+            .with_body(response(folder(
+                0,
+                "/",
+                Some(&folder(
+                    1,
+                    "videos",
+                    Some(&[file(2, "a.mov"), file(3, "b.txt")].join(",")),
+                )),
+            )))
+            .create_async()
+            .await;
+        let client = Client::new(server.url(), Credentials::anonymous()).unwrap();
+        let query = SearchQuery::default()
+            .with_strategy(WalkStrategy::Recursive)
+            .with_name(Pattern::glob("*.mov").unwrap());
+        let paths = client
+            .search(0, query)
+            .map(|item| item.unwrap().0.to_string())
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(paths, vec!["videos/a.mov"]);
+        m.assert_async().await;
+    }
+}
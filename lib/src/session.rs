@@ -0,0 +1,240 @@
+//! Automatic login with a username and a password.
+//!
+//! The password is never sent to the API: a digest is requested, the password is hashed
+//! with it and exchanged for an authorization token. The token is then used by every
+//! request and a new one is requested when the API rejects it.
+
+use std::borrow::Cow;
+
+use tokio::sync::Mutex;
+
+use crate::Credentials;
+
+/// The login of a client, shared with its clones.
+pub(crate) struct Session {
+    username: String,
+    password: String,
+    token: Mutex<Option<String>>,
+}
+
+impl std::fmt::Debug for Session {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct(stringify!(Session))
+            .field("username", &self.username)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Session {
+    pub(crate) fn new(username: String, password: String) -> Self {
+        Self {
+            username,
+            password,
+            token: Mutex::new(None),
+        }
+    }
+
+    /// Exchanges the username and the password for an authorization token.
+    async fn login(&self, client: &crate::Client) -> crate::Result<String> {
+        tracing::debug!(username = self.username, "logging in");
+        let mut client = client.clone();
+        client.session = None;
+        client.credentials = Credentials::Anonymous;
+        let digest = client.get_digest().await?;
+        client.credentials =
+            Credentials::username_password_digest(&self.username, digest.value, &self.password);
+        client.get_token().await
+    }
+
+    /// Returns the current token, logging in when there is none yet.
+    async fn token(&self, client: &crate::Client) -> crate::Result<String> {
+        let mut token = self.token.lock().await;
+        match *token {
+            Some(ref value) => Ok(value.clone()),
+            None => {
+                let value = Box::pin(self.login(client)).await?;
+                *token = Some(value.clone());
+                Ok(value)
+            }
+        }
+    }
+
+    /// Replaces a token rejected by the API.
+    ///
+    /// When another request already replaced it, the new token is returned without logging in.
+    async fn renew(&self, client: &crate::Client, rejected: &str) -> crate::Result<String> {
+        let mut token = self.token.lock().await;
+        match *token {
+            Some(ref value) if value != rejected => Ok(value.clone()),
+            _ => {
+                *token = None;
+                let value = Box::pin(self.login(client)).await?;
+                *token = Some(value.clone());
+                Ok(value)
+            }
+        }
+    }
+}
+
+impl crate::Client {
+    /// Logs in with the username and password given to the builder, when not done already.
+    ///
+    /// Logging in happens automatically on the first request, this allows to do it upfront.
+    ///
+    /// # Returns
+    ///
+    /// The authorization token used by the client, or `None` when the client wasn't
+    /// built with [`crate::builder::ClientBuilder::with_login`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`crate::Error`] if the digest or the token can't be fetched, including when
+    /// the username or the password is wrong.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = pcloud::Client::builder()
+    ///     .with_login("someone@example.com", "password")
+    ///     .build()?;
+    /// client.login().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn login(&self) -> crate::Result<Option<String>> {
+        match self.session {
+            Some(ref session) => session.token(self).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the credentials to attach to a request.
+    pub(crate) async fn request_credentials(&self) -> crate::Result<Cow<'_, Credentials>> {
+        match self.session {
+            Some(ref session) => session
+                .token(self)
+                .await
+                .map(|token| Cow::Owned(Credentials::authorization(token))),
+            None => Ok(Cow::Borrowed(&self.credentials)),
+        }
+    }
+
+    /// Returns new credentials when the error was caused by a token the session should replace.
+    pub(crate) async fn renewed_credentials(
+        &self,
+        credentials: &Credentials,
+        error: &crate::Error,
+    ) -> Option<crate::Result<Credentials>> {
+        let session = self.session.as_ref()?;
+        let Credentials::Authorization { auth } = credentials else {
+            return None;
+        };
+        if !error.api_code().is_some_and(|code| code.is_auth_error()) {
+            return None;
+        }
+        tracing::debug!("authorization token rejected, logging in again");
+        Some(
+            session
+                .renew(self, auth)
+                .await
+                .map(Credentials::authorization),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use crate::testing::{folder, response};
+    use crate::Client;
+    use mockito::Matcher;
+
+    async fn mock_login(server: &mut mockito::ServerGuard, expected: usize) -> mockito::Mock {
+        server
+            .mock("GET", "/getdigest")
+            .match_query(Matcher::Missing)
+            .with_status(200)
+            .with_body(r#"{"result": 0, "digest": "the-digest", "expires": "Fri, 27 Sep 2013 10:15:46 +0000"}"#)
+            .create_async()
+            .await;
+        let logins = Arc::new(AtomicUsize::new(0));
+        server
+            .mock("POST", "/userinfo")
+            .match_body(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("getauth".into(), "1".into()),
+                Matcher::UrlEncoded("username".into(), "someone@example.com".into()),
+                Matcher::UrlEncoded("digest".into(), "the-digest".into()),
+                Matcher::Regex("passworddigest=[0-9a-f]{40}".into()),
+            ]))
+            .with_status(200)
+            .with_body_from_request(move |_| {
+                let count = logins.fetch_add(1, Ordering::SeqCst) + 1;
+                format!(r#"{{"result": 0, "auth": "token-{count}"}}"#).into()
+            })
+            .expect(expected)
+            .create_async()
+            .await
+    }
+
+    #[tokio::test]
+    async fn should_login_once() {
+        let mut server = mockito::Server::new_async().await;
+        let login = mock_login(&mut server, 1).await;
+        let m = server
+            .mock("POST", "/listfolder")
+            .match_body(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("auth".into(), "token-1".into()),
+                Matcher::UrlEncoded("folderid".into(), "0".into()),
+            ]))
+            .with_status(200)
+            .with_body(response(folder(0, "/", None)))
+            .expect(2)
+            .create_async()
+            .await;
+        let client = Client::builder()
+            .with_base_url(server.url())
+            .with_login("someone@example.com", "password")
+            .build()
+            .unwrap();
+        client.list_folder(0).await.unwrap();
+        client.list_folder(0).await.unwrap();
+        assert_eq!(client.login().await.unwrap().as_deref(), Some("token-1"));
+        login.assert_async().await;
+        m.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn should_login_again_when_token_is_rejected() {
+        let mut server = mockito::Server::new_async().await;
+        let login = mock_login(&mut server, 2).await;
+        let rejected = server
+            .mock("POST", "/listfolder")
+            .match_body(Matcher::UrlEncoded("auth".into(), "token-1".into()))
+            .with_status(200)
+            .with_body(r#"{"result": 1000, "error": "Log in required."}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let accepted = server
+            .mock("POST", "/listfolder")
+            .match_body(Matcher::UrlEncoded("auth".into(), "token-2".into()))
+            .with_status(200)
+            .with_body(response(folder(0, "/", None)))
+            .expect(1)
+            .create_async()
+            .await;
+        let client = Client::builder()
+            .with_base_url(server.url())
+            .with_login("someone@example.com", "password")
+            .build()
+            .unwrap();
+        let folder = client.list_folder(0).await.unwrap();
+        assert_eq!(folder.folder_id, 0);
+        login.assert_async().await;
+        rejected.assert_async().await;
+        accepted.assert_async().await;
+    }
+}
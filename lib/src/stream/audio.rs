@@ -122,8 +122,8 @@ mod tests {
     async fn success() {
         let mut server = mockito::Server::new_async().await;
         let m = server.mock("GET", "/getaudiolink")
+            .match_header("authorization", "Bearer access-token")
             .match_query(Matcher::AllOf(vec![
-                Matcher::UrlEncoded("access_token".into(), "access-token".into()),
                 Matcher::UrlEncoded("fileid".into(), "42".into()),
             ]))
             .with_status(200)
@@ -2,6 +2,7 @@ use chrono::{DateTime, Utc};
 
 pub mod audio;
 pub mod file;
+pub mod provider;
 pub mod video;
 
 /// A struct that represents a list of streaming links with metadata such as expiration date and hosts.
@@ -16,6 +17,14 @@ pub struct StreamingLinkList {
 
     /// The path to the resource to be streamed.
     pub path: String,
+
+    /// The hash of the file content, changing when the file is modified.
+    #[serde(default)]
+    pub hash: Option<u64>,
+
+    /// The size of the file in bytes.
+    #[serde(default)]
+    pub size: Option<u64>,
 }
 
 /// A struct representing an individual streaming link, built using a host and a path.
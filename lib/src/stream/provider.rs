@@ -0,0 +1,495 @@
+//! A cache of streaming links, refreshed before they expire.
+//!
+//! Every call to `getfilelink`, `getaudiolink` or `getvideolink` creates a new link on the
+//! API side. Long running applications serving the same files over and over, like players or
+//! the http server, can reuse a link until it expires with a [`LinkProvider`].
+//!
+//! A path can point to another file after an overwrite, a deletion or a move. The links cached
+//! for a path are evicted when a download with them fails, and [`LinkProvider::evict_stale`]
+//! drops them when the content hash of the file is known to have changed.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use bytes::Bytes;
+use chrono::Utc;
+use futures_util::Stream;
+use tokio::io::AsyncWrite;
+
+use super::audio::GetAudioLinkParams;
+use super::file::GetFileLinkParams;
+use super::video::GetVideoLinkParams;
+use super::StreamingLinkList;
+use crate::cache::CacheKey;
+use crate::file::FileIdentifier;
+
+/// The delay before expiration after which a link is refreshed, by default.
+const DEFAULT_REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+/// Identifies a link by the endpoint, the file and the serialized parameters.
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct LinkKey {
+    method: &'static str,
+    file: CacheKey,
+    params: String,
+}
+
+/// Struct representing the parameters used when requesting a link.
+#[derive(serde::Serialize)]
+struct Params<'a, P> {
+    #[serde(flatten)]
+    identifier: FileIdentifier<'a>,
+    #[serde(flatten)]
+    params: P,
+}
+
+/// Provides streaming links, reusing the previous ones until they're about to expire.
+///
+/// Cloning the provider is cheap, the clones share the same links.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use pcloud::stream::provider::LinkProvider;
+///
+/// # async fn example(client: pcloud::Client) -> Result<(), pcloud::Error> {
+/// let provider = LinkProvider::new(client);
+/// let first = provider.file_link(42).await?;
+/// // the same link is returned until it's about to expire
+/// let second = provider.file_link(42).await?;
+/// assert_eq!(first.path, second.path);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct LinkProvider {
+    client: crate::Client,
+    refresh_margin: Duration,
+    links: Arc<Mutex<HashMap<LinkKey, Arc<StreamingLinkList>>>>,
+}
+
+impl LinkProvider {
+    /// Creates a provider requesting the links with the given client.
+    pub fn new(client: crate::Client) -> Self {
+        Self {
+            client,
+            refresh_margin: DEFAULT_REFRESH_MARGIN,
+            links: Default::default(),
+        }
+    }
+
+    /// Sets how long before their expiration the links are refreshed, one minute by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The delay before expiration.
+    pub fn set_refresh_margin(&mut self, value: Duration) {
+        self.refresh_margin = value;
+    }
+
+    /// Sets the refresh margin and returns the updated `LinkProvider`.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The delay before expiration.
+    pub fn with_refresh_margin(mut self, value: Duration) -> Self {
+        self.set_refresh_margin(value);
+        self
+    }
+
+    /// Returns the client used to request the links.
+    pub fn client(&self) -> &crate::Client {
+        &self.client
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<LinkKey, Arc<StreamingLinkList>>> {
+        self.links.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns `true` while the link can be used without being refreshed.
+    fn is_fresh(&self, links: &StreamingLinkList) -> bool {
+        let margin =
+            chrono::Duration::from_std(self.refresh_margin).unwrap_or(chrono::Duration::MAX);
+        Utc::now()
+            .checked_add_signed(margin)
+            .is_some_and(|limit| limit < links.expires)
+    }
+
+    /// Returns the cached link, or requests a new one.
+    ///
+    /// The returned flag is `true` when the link comes from the cache.
+    async fn link<P: serde::Serialize>(
+        &self,
+        method: &'static str,
+        identifier: FileIdentifier<'_>,
+        params: P,
+    ) -> crate::Result<(Arc<StreamingLinkList>, bool)> {
+        let key = LinkKey {
+            method,
+            file: CacheKey::from(&identifier),
+            params: serde_json::to_string(&params).unwrap_or_default(),
+        };
+        if let Some(links) = self.lock().get(&key).filter(|links| self.is_fresh(links)) {
+            return Ok((links.clone(), true));
+        }
+        let links = self
+            .client
+            .get_request::<StreamingLinkList, _>(method, Params { identifier, params })
+            .await
+            .map(Arc::new)?;
+        let mut cache = self.lock();
+        cache.retain(|_, item| self.is_fresh(item));
+        cache.insert(key, links.clone());
+        Ok((links, false))
+    }
+
+    /// Gets a file link, see [`crate::Client::get_file_link`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`crate::Error`] if a new link is needed and the request fails.
+    pub async fn file_link(
+        &self,
+        identifier: impl Into<FileIdentifier<'_>>,
+    ) -> crate::Result<Arc<StreamingLinkList>> {
+        self.file_link_with_params(identifier, GetFileLinkParams::default())
+            .await
+    }
+
+    /// Gets a file link with parameters, see [`crate::Client::get_file_link_with_params`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`crate::Error`] if a new link is needed and the request fails.
+    pub async fn file_link_with_params(
+        &self,
+        identifier: impl Into<FileIdentifier<'_>>,
+        params: GetFileLinkParams<'_>,
+    ) -> crate::Result<Arc<StreamingLinkList>> {
+        self.link("getfilelink", identifier.into(), params)
+            .await
+            .map(|(links, _)| links)
+    }
+
+    /// Gets an audio link, see [`crate::Client::get_audio_link`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`crate::Error`] if a new link is needed and the request fails.
+    pub async fn audio_link(
+        &self,
+        identifier: impl Into<FileIdentifier<'_>>,
+    ) -> crate::Result<Arc<StreamingLinkList>> {
+        self.audio_link_with_params(identifier, GetAudioLinkParams::default())
+            .await
+    }
+
+    /// Gets an audio link with parameters, see [`crate::Client::get_audio_link_with_params`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`crate::Error`] if a new link is needed and the request fails.
+    pub async fn audio_link_with_params(
+        &self,
+        identifier: impl Into<FileIdentifier<'_>>,
+        params: GetAudioLinkParams,
+    ) -> crate::Result<Arc<StreamingLinkList>> {
+        self.link("getaudiolink", identifier.into(), params)
+            .await
+            .map(|(links, _)| links)
+    }
+
+    /// Gets a video link, see [`crate::Client::get_video_link`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`crate::Error`] if a new link is needed and the request fails.
+    pub async fn video_link(
+        &self,
+        identifier: impl Into<FileIdentifier<'_>>,
+    ) -> crate::Result<Arc<StreamingLinkList>> {
+        self.video_link_with_params(identifier, GetVideoLinkParams::default())
+            .await
+    }
+
+    /// Gets a video link with parameters, see [`crate::Client::get_video_link_with_params`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`crate::Error`] if a new link is needed and the request fails.
+    pub async fn video_link_with_params(
+        &self,
+        identifier: impl Into<FileIdentifier<'_>>,
+        params: GetVideoLinkParams<'_>,
+    ) -> crate::Result<Arc<StreamingLinkList>> {
+        self.link("getvideolink", identifier.into(), params)
+            .await
+            .map(|(links, _)| links)
+    }
+
+    /// Removes a link from the cache, the next call requesting a new one.
+    ///
+    /// This should be called when a download against the link failed.
+    ///
+    /// # Returns
+    ///
+    /// `true` when the link was cached.
+    pub fn evict(&self, links: &StreamingLinkList) -> bool {
+        let mut cache = self.lock();
+        let before = cache.len();
+        cache.retain(|_, item| item.path != links.path);
+        cache.len() != before
+    }
+
+    /// Removes the links cached for a file whose content hash isn't `hash` anymore.
+    ///
+    /// This should be called with the hash of fresh metadata when the file is identified by
+    /// its path, the path possibly pointing to another file since the link was cached.
+    ///
+    /// # Arguments
+    ///
+    /// * `identifier` - A value convertible into a [`FileIdentifier`] representing the file.
+    /// * `hash` - The current content hash of the file.
+    ///
+    /// # Returns
+    ///
+    /// `true` when at least one link was removed.
+    pub fn evict_stale<'a>(&self, identifier: impl Into<FileIdentifier<'a>>, hash: u64) -> bool {
+        let file = CacheKey::from(&identifier.into());
+        let mut cache = self.lock();
+        let before = cache.len();
+        cache.retain(|key, item| key.file != file || item.hash.is_none_or(|value| value == hash));
+        cache.len() != before
+    }
+
+    /// Removes all the cached links.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Downloads a file with a cached link and writes it in `writer`.
+    ///
+    /// When the download fails, the link is evicted. If it came from the cache and nothing
+    /// was written yet, the download is attempted once more with a new link.
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes written.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`crate::Error`] if the link cannot be resolved, or a
+    /// [`crate::Error::Download`] if the download itself failed.
+    pub async fn download<'a, W>(
+        &self,
+        identifier: impl Into<FileIdentifier<'a>>,
+        writer: &mut W,
+    ) -> crate::Result<u64>
+    where
+        W: AsyncWrite + Unpin + ?Sized,
+    {
+        let identifier = identifier.into();
+        let (links, cached) = self
+            .link(
+                "getfilelink",
+                identifier.clone(),
+                GetFileLinkParams::default(),
+            )
+            .await?;
+        let mut counter = CountingWriter {
+            inner: writer,
+            written: 0,
+        };
+        match self.client.download_links(&links, &mut counter).await {
+            Ok(written) => Ok(written),
+            Err(err) => {
+                self.evict(&links);
+                if !cached || counter.written > 0 {
+                    return Err(err);
+                }
+                tracing::debug!("download failed with a cached link, requesting a new one: {err}");
+                let links = self.file_link(identifier).await?;
+                let result = self.client.download_links(&links, counter.inner).await;
+                if result.is_err() {
+                    self.evict(&links);
+                }
+                result
+            }
+        }
+    }
+
+    /// Downloads a file with a cached link, as a stream of bytes.
+    ///
+    /// When no host can be reached, the link is evicted and, if it came from the cache,
+    /// a new one is requested.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`crate::Error`] if the link cannot be resolved, or a
+    /// [`crate::Error::Download`] if none of the hosts could be reached.
+    pub async fn download_stream<'a>(
+        &self,
+        identifier: impl Into<FileIdentifier<'a>>,
+    ) -> crate::Result<impl Stream<Item = crate::Result<Bytes>> + Send + 'static> {
+        let identifier = identifier.into();
+        let (links, cached) = self
+            .link(
+                "getfilelink",
+                identifier.clone(),
+                GetFileLinkParams::default(),
+            )
+            .await?;
+        match self.client.download_links_stream(&links).await {
+            Ok(stream) => Ok(stream),
+            Err(err) => {
+                self.evict(&links);
+                if !cached {
+                    return Err(err);
+                }
+                let links = self.file_link(identifier).await?;
+                let result = self.client.download_links_stream(&links).await;
+                if result.is_err() {
+                    self.evict(&links);
+                }
+                result
+            }
+        }
+    }
+}
+
+/// Counts the bytes written, to know if a failed download can be restarted from scratch.
+struct CountingWriter<'a, W: ?Sized> {
+    inner: &'a mut W,
+    written: u64,
+}
+
+impl<W: AsyncWrite + Unpin + ?Sized> AsyncWrite for CountingWriter<'_, W> {
+    fn poll_write(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        let this = &mut *self;
+        let result = std::pin::Pin::new(&mut *this.inner).poll_write(cx, buf);
+        if let std::task::Poll::Ready(Ok(size)) = result {
+            this.written += size as u64;
+        }
+        result
+    }
+
+    fn poll_flush(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        std::pin::Pin::new(&mut *self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        std::pin::Pin::new(&mut *self.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::LinkProvider;
+    use crate::stream::file::GetFileLinkParams;
+    use crate::{Client, Credentials};
+    use mockito::Matcher;
+
+    fn link_body(expires: &str) -> String {
+        format!(
+            r#"{{
+    "result": 0,
+    "expires": "{expires}",
+    "path": "/DLZCAt2vXZejNfL5ZruLVZZTk2ev7Z2ZZNR5ZZdoz6ZXZQZZErw4bH0PfzBQt3LlgXMliXVtietX/file.bin",
+    "hosts": ["edef2.pcloud.com", "eu3.pcloud.com"]
+}}"#
+        )
+    }
+
+    #[tokio::test]
+    async fn should_reuse_links_until_evicted() {
+        let mut server = mockito::Server::new_async().await;
+        let m = server
+            .mock("GET", "/getfilelink")
+            .match_header("authorization", "Bearer access-token")
+            .match_query(Matcher::UrlEncoded("fileid".into(), "42".into()))
+            .with_status(200)
+            .with_body(link_body("Sat, 24 Jul 2100 03:18:31 +0000"))
+            .expect(3)
+            .create_async()
+            .await;
+        let client = Client::new(server.url(), Credentials::access_token("access-token")).unwrap();
+        let provider = LinkProvider::new(client);
+        let links = provider.file_link(42).await.unwrap();
+        provider.file_link(42).await.unwrap();
+        // other parameters give another link
+        provider
+            .file_link_with_params(42, GetFileLinkParams::default().with_force_download(true))
+            .await
+            .unwrap();
+        assert!(provider.evict(&links));
+        assert!(!provider.evict(&links));
+        provider.file_link(42).await.unwrap();
+        m.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn should_evict_stale_links_by_path() {
+        let mut server = mockito::Server::new_async().await;
+        let m = server
+            .mock("GET", "/getfilelink")
+            .match_query(Matcher::UrlEncoded("path".into(), "/file.bin".into()))
+            .with_status(200)
+            .with_body(
+                r#"{
+    "result": 0,
+    "expires": "Sat, 24 Jul 2100 03:18:31 +0000",
+    "path": "/DLZCAt2vXZejNfL5ZruLVZZTk2ev7Z2ZZNR5ZZdoz6ZXZQZZErw4bH0PfzBQt3LlgXMliXVtietX/file.bin",
+    "hosts": ["edef2.pcloud.com"],
+    "hash": 42
+}"#,
+            )
+            .expect(2)
+            .create_async()
+            .await;
+        let client = Client::new(server.url(), Credentials::access_token("access-token")).unwrap();
+        let provider = LinkProvider::new(client);
+        provider.file_link("/file.bin").await.unwrap();
+        provider.file_link("/file.bin").await.unwrap();
+        // same content, the link is kept
+        assert!(!provider.evict_stale("/file.bin", 42));
+        // the path now points to another content
+        assert!(provider.evict_stale("/file.bin", 43));
+        provider.file_link("/file.bin").await.unwrap();
+        m.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn should_refresh_links_about_to_expire() {
+        let mut server = mockito::Server::new_async().await;
+        let expires = (chrono::Utc::now() + chrono::Duration::minutes(5)).to_rfc2822();
+        let m = server
+            .mock("GET", "/getfilelink")
+            .match_query(Matcher::UrlEncoded("fileid".into(), "42".into()))
+            .with_status(200)
+            .with_body(link_body(&expires))
+            .expect(3)
+            .create_async()
+            .await;
+        let client = Client::new(server.url(), Credentials::access_token("access-token")).unwrap();
+        let provider = LinkProvider::new(client.clone());
+        provider.file_link(42).await.unwrap();
+        provider.file_link(42).await.unwrap();
+        let provider = LinkProvider::new(client).with_refresh_margin(Duration::from_secs(600));
+        provider.file_link(42).await.unwrap();
+        provider.file_link(42).await.unwrap();
+        m.assert_async().await;
+    }
+}
@@ -0,0 +1,753 @@
+//! Planning the synchronization of a local directory with a folder on pCloud.
+//!
+//! [`crate::Client::plan_sync`] compares both trees by path, size and content and returns a
+//! [`SyncPlan`] describing what should be uploaded, downloaded or deleted on each side, and the
+//! conflicts that can't be solved automatically. Nothing is transferred, the caller runs the
+//! actions it accepts and records them in the [`SyncState`].
+//!
+//! The [`SyncState`] keeps what both sides looked like after the last synchronization. It's
+//! what tells a file deleted on one side apart from a file created on the other one, and it
+//! avoids hashing the local files that didn't change since.
+//!
+//! Only files are compared, empty directories and symbolic links are ignored.
+//!
+//! ```rust,no_run
+//! use pcloud::sync::{SyncAction, SyncDirection, SyncOptions, SyncState};
+//!
+//! # async fn example(client: &pcloud::Client) -> Result<(), pcloud::sync::SyncError> {
+//! let mut state = SyncState::load("./photos.sync.json")?;
+//! let options = SyncOptions::default().with_direction(SyncDirection::TwoWay);
+//! let plan = client
+//!     .plan_sync("./photos", "/Photos", &mut state, &options)
+//!     .await?;
+//! for item in plan.items.iter() {
+//!     if let SyncAction::Conflict(kind) = item.action {
+//!         eprintln!("{}: {kind:?}", item.path);
+//!     }
+//! }
+//! state.save("./photos.sync.json")?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use futures_util::{StreamExt, TryStreamExt};
+use tokio::io::AsyncReadExt;
+
+use crate::file::checksum::ChecksumHasher;
+use crate::file::File;
+use crate::folder::list::ListFolderOptions;
+use crate::folder::walk::RemotePath;
+use crate::folder::{Folder, FolderIdentifier};
+
+/// Size of the chunks read when hashing the local files.
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Errors that can occur when planning a synchronization.
+#[derive(Debug, thiserror::Error)]
+pub enum SyncError {
+    /// A local file or directory couldn't be read, or the state couldn't be written.
+    #[error("unable to access {0}")]
+    Io(PathBuf, #[source] std::io::Error),
+    /// The stored state couldn't be decoded.
+    #[error("invalid sync state format")]
+    Format(#[source] serde_json::Error),
+    /// The remote folder couldn't be listed or a checksum couldn't be fetched.
+    #[error(transparent)]
+    Api(#[from] crate::Error),
+}
+
+/// Which side is the reference when both trees differ.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SyncDirection {
+    /// The remote folder is made identical to the local directory.
+    Push,
+    /// The local directory is made identical to the remote folder.
+    Pull,
+    /// The changes made on each side since the last synchronization are applied to the other.
+    #[default]
+    TwoWay,
+}
+
+/// Options for [`crate::Client::plan_sync`].
+#[derive(Clone, Debug)]
+pub struct SyncOptions {
+    concurrency: usize,
+    direction: SyncDirection,
+    verify_checksums: bool,
+}
+
+impl Default for SyncOptions {
+    /// Two-way synchronization, fetching up to 4 remote checksums at a time when needed.
+    fn default() -> Self {
+        Self {
+            concurrency: 4,
+            direction: SyncDirection::default(),
+            verify_checksums: true,
+        }
+    }
+}
+
+impl SyncOptions {
+    /// Sets the maximum number of remote checksums fetched at the same time.
+    pub fn set_concurrency(&mut self, value: usize) {
+        self.concurrency = value.max(1);
+    }
+
+    /// Sets the maximum number of checksums fetched at the same time and returns the updated options.
+    pub fn with_concurrency(mut self, value: usize) -> Self {
+        self.set_concurrency(value);
+        self
+    }
+
+    /// Sets which side is the reference.
+    pub fn set_direction(&mut self, value: SyncDirection) {
+        self.direction = value;
+    }
+
+    /// Sets which side is the reference and returns the updated options.
+    pub fn with_direction(mut self, value: SyncDirection) -> Self {
+        self.set_direction(value);
+        self
+    }
+
+    /// When enabled, the SHA-1 of a remote file having the same size as the local one is
+    /// fetched when the state can't tell whether they're identical. Otherwise files with the
+    /// same size are considered identical.
+    ///
+    /// Every checksum is a request, sent with the concurrency of [`SyncOptions::set_concurrency`].
+    pub fn set_verify_checksums(&mut self, value: bool) {
+        self.verify_checksums = value;
+    }
+
+    /// Enables or disables the verification of the checksums and returns the updated options.
+    pub fn with_verify_checksums(mut self, value: bool) -> Self {
+        self.set_verify_checksums(value);
+        self
+    }
+}
+
+/// A file of the local directory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LocalFile {
+    /// The path of the file on the local file system.
+    pub path: PathBuf,
+    /// The size of the file, in bytes.
+    pub size: u64,
+    /// The last modification of the file, when provided by the file system.
+    pub modified: Option<DateTime<Utc>>,
+    /// The SHA-1 of the content, in lowercase hexadecimal.
+    pub sha1: String,
+}
+
+/// What a file looked like on both sides after its last synchronization.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SyncRecord {
+    /// The size of the file, in bytes.
+    pub size: u64,
+    /// The last modification of the local file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified: Option<DateTime<Utc>>,
+    /// The SHA-1 of the content.
+    pub sha1: String,
+    /// The ID of the remote file.
+    pub file_id: u64,
+    /// The hash of the remote file, as returned by pCloud, changing with its content.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_hash: Option<u64>,
+}
+
+impl SyncRecord {
+    /// Returns `true` when the local file changed since the synchronization.
+    fn local_changed(&self, local: &LocalFile) -> bool {
+        self.size != local.size || !self.sha1.eq_ignore_ascii_case(&local.sha1)
+    }
+
+    /// Returns `true` when the remote file changed since the synchronization.
+    fn remote_changed(&self, remote: &File) -> bool {
+        self.file_id != remote.file_id
+            || self.remote_hash.is_none()
+            || self.remote_hash != remote.hash
+    }
+}
+
+/// The files of a synchronized pair of trees, as they were after the last synchronization.
+///
+/// The state is keyed by the path of the files relative to the roots, like `2024/beach.jpg`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SyncState {
+    files: BTreeMap<String, SyncRecord>,
+}
+
+impl SyncState {
+    /// Loads the state from a JSON file, an empty state is returned when the file doesn't exist.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SyncError> {
+        let path = path.as_ref();
+        match std::fs::read(path) {
+            Ok(content) => serde_json::from_slice(&content).map_err(SyncError::Format),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(SyncError::Io(path.to_path_buf(), err)),
+        }
+    }
+
+    /// Saves the state in a JSON file, through a temporary file moved in place.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SyncError> {
+        let path = path.as_ref();
+        let io_error = |err| SyncError::Io(path.to_path_buf(), err);
+        let content = serde_json::to_vec_pretty(self).map_err(SyncError::Format)?;
+        let tmp_path = path.with_extension("tmp");
+        std::fs::write(&tmp_path, content).map_err(io_error)?;
+        std::fs::rename(&tmp_path, path).map_err(io_error)
+    }
+
+    /// Returns the record of a file.
+    pub fn get(&self, path: &RemotePath) -> Option<&SyncRecord> {
+        self.files.get(path.as_str())
+    }
+
+    /// Iterates over the paths and records of the synchronized files.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &SyncRecord)> {
+        self.files
+            .iter()
+            .map(|(path, record)| (path.as_str(), record))
+    }
+
+    /// Returns the number of synchronized files.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Returns `true` when no file has been synchronized.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Records a file identical on both sides, after an upload or a download.
+    pub fn record(&mut self, path: &RemotePath, local: &LocalFile, remote: &File) {
+        self.files.insert(
+            path.to_string(),
+            SyncRecord {
+                size: local.size,
+                modified: local.modified,
+                sha1: local.sha1.clone(),
+                file_id: remote.file_id,
+                remote_hash: remote.hash,
+            },
+        );
+    }
+
+    /// Forgets a file, after it has been deleted on both sides.
+    pub fn remove(&mut self, path: &RemotePath) -> Option<SyncRecord> {
+        self.files.remove(path.as_str())
+    }
+}
+
+/// Why a file can't be synchronized automatically.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConflictKind {
+    /// The file has been created on both sides with different contents.
+    BothCreated,
+    /// The file has been modified on both sides.
+    BothModified,
+    /// The local file has been modified while the remote one has been deleted.
+    ModifiedLocallyDeletedRemotely,
+    /// The local file has been deleted while the remote one has been modified.
+    DeletedLocallyModifiedRemotely,
+}
+
+/// What should be done with a file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyncAction {
+    /// The local file should be uploaded, replacing the remote one if any.
+    Upload,
+    /// The remote file should be downloaded, replacing the local one if any.
+    Download,
+    /// The local file should be deleted.
+    DeleteLocal,
+    /// The remote file should be deleted.
+    DeleteRemote,
+    /// The file changed on both sides, the user has to choose.
+    Conflict(ConflictKind),
+}
+
+/// A file to synchronize, with what's known about it on each side.
+#[derive(Debug)]
+pub struct SyncItem {
+    /// The path of the file relative to the roots.
+    pub path: RemotePath,
+    /// What should be done.
+    pub action: SyncAction,
+    /// The local file, when it exists.
+    pub local: Option<LocalFile>,
+    /// The remote file, when it exists.
+    pub remote: Option<File>,
+}
+
+/// Plan returned by [`crate::Client::plan_sync`], the files already in sync are not listed.
+#[derive(Debug)]
+pub struct SyncPlan {
+    /// The local directory.
+    pub local_root: PathBuf,
+    /// The ID of the remote folder.
+    pub remote_folder_id: u64,
+    /// The files to synchronize, sorted by path.
+    pub items: Vec<SyncItem>,
+}
+
+impl SyncPlan {
+    /// Returns `true` when both trees are already in sync.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Iterates over the files planned for the given action.
+    pub fn with_action(&self, action: SyncAction) -> impl Iterator<Item = &SyncItem> {
+        self.items.iter().filter(move |item| item.action == action)
+    }
+
+    /// Iterates over the conflicting files.
+    pub fn conflicts(&self) -> impl Iterator<Item = &SyncItem> {
+        self.items
+            .iter()
+            .filter(|item| matches!(item.action, SyncAction::Conflict(_)))
+    }
+}
+
+/// Decides what to do with a file, `content_changed` telling whether both sides differ when
+/// the file exists on both sides.
+fn decide(
+    direction: SyncDirection,
+    local: Option<&LocalFile>,
+    remote: Option<&File>,
+    record: Option<&SyncRecord>,
+    content_changed: bool,
+) -> Option<SyncAction> {
+    match (local, remote) {
+        (Some(_), Some(_)) if !content_changed => None,
+        (Some(local), Some(remote)) => Some(match direction {
+            SyncDirection::Push => SyncAction::Upload,
+            SyncDirection::Pull => SyncAction::Download,
+            SyncDirection::TwoWay => match record {
+                None => SyncAction::Conflict(ConflictKind::BothCreated),
+                Some(record) => {
+                    match (record.local_changed(local), record.remote_changed(remote)) {
+                        (true, false) => SyncAction::Upload,
+                        (false, true) => SyncAction::Download,
+                        _ => SyncAction::Conflict(ConflictKind::BothModified),
+                    }
+                }
+            },
+        }),
+        (Some(local), None) => Some(match (direction, record) {
+            (SyncDirection::Push, _) | (SyncDirection::TwoWay, None) => SyncAction::Upload,
+            (SyncDirection::Pull, _) => SyncAction::DeleteLocal,
+            (SyncDirection::TwoWay, Some(record)) if record.local_changed(local) => {
+                SyncAction::Conflict(ConflictKind::ModifiedLocallyDeletedRemotely)
+            }
+            (SyncDirection::TwoWay, Some(_)) => SyncAction::DeleteLocal,
+        }),
+        (None, Some(remote)) => Some(match (direction, record) {
+            (SyncDirection::Pull, _) | (SyncDirection::TwoWay, None) => SyncAction::Download,
+            (SyncDirection::Push, _) => SyncAction::DeleteRemote,
+            (SyncDirection::TwoWay, Some(record)) if record.remote_changed(remote) => {
+                SyncAction::Conflict(ConflictKind::DeletedLocallyModifiedRemotely)
+            }
+            (SyncDirection::TwoWay, Some(_)) => SyncAction::DeleteRemote,
+        }),
+        (None, None) => None,
+    }
+}
+
+/// Flattens a recursive listing into the files it contains, by path.
+fn remote_files(folder: Folder) -> BTreeMap<RemotePath, File> {
+    let mut files = BTreeMap::new();
+    let mut queue = VecDeque::from([(RemotePath::default(), folder)]);
+    while let Some((path, folder)) = queue.pop_front() {
+        for entry in folder.contents.unwrap_or_default() {
+            match entry {
+                crate::entry::Entry::File(file) => {
+                    files.insert(path.join(&file.base.name), file);
+                }
+                crate::entry::Entry::Folder(child) => {
+                    queue.push_back((path.join(&child.base.name), child));
+                }
+            }
+        }
+    }
+    files
+}
+
+/// Computes the SHA-1 of a local file.
+async fn hash_file(path: &Path) -> std::io::Result<String> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut hasher = ChecksumHasher::matching(&Default::default());
+    let mut buffer = vec![0; READ_CHUNK_SIZE];
+    loop {
+        let read = file.read(&mut buffer).await?;
+        if read == 0 {
+            return Ok(hasher.finalize().sha1);
+        }
+        hasher.update(&buffer[..read]);
+    }
+}
+
+/// Lists the files of the local directory, reusing the SHA-1 of the recorded files that
+/// kept the same size and modification date.
+async fn local_files(
+    root: &Path,
+    state: &SyncState,
+) -> Result<BTreeMap<RemotePath, LocalFile>, SyncError> {
+    let io_error = |path: &Path| {
+        let path = path.to_path_buf();
+        move |err| SyncError::Io(path, err)
+    };
+    let mut files = BTreeMap::new();
+    let mut queue = VecDeque::from([(root.to_path_buf(), RemotePath::default())]);
+    while let Some((dir, remote_path)) = queue.pop_front() {
+        let mut entries = tokio::fs::read_dir(&dir).await.map_err(io_error(&dir))?;
+        while let Some(entry) = entries.next_entry().await.map_err(io_error(&dir))? {
+            let path = entry.path();
+            let file_type = entry.file_type().await.map_err(io_error(&path))?;
+            let Some(name) = entry.file_name().to_str().map(String::from) else {
+                tracing::warn!(path = %path.display(), "ignoring file with an invalid name");
+                continue;
+            };
+            if file_type.is_dir() {
+                queue.push_back((path, remote_path.join(&name)));
+            } else if file_type.is_file() {
+                let metadata = entry.metadata().await.map_err(io_error(&path))?;
+                let size = metadata.len();
+                let modified = metadata.modified().ok().map(DateTime::<Utc>::from);
+                let file_path = remote_path.join(&name);
+                let sha1 = match state.get(&file_path) {
+                    Some(record)
+                        if record.size == size
+                            && modified.is_some()
+                            && record.modified == modified =>
+                    {
+                        record.sha1.clone()
+                    }
+                    _ => hash_file(&path).await.map_err(io_error(&path))?,
+                };
+                files.insert(
+                    file_path,
+                    LocalFile {
+                        path,
+                        size,
+                        modified,
+                        sha1,
+                    },
+                );
+            }
+        }
+    }
+    Ok(files)
+}
+
+impl crate::Client {
+    /// Tells whether a file existing on both sides has different contents.
+    async fn sync_content_changed(
+        &self,
+        local: &LocalFile,
+        remote: &File,
+        record: Option<&SyncRecord>,
+        options: &SyncOptions,
+    ) -> crate::Result<bool> {
+        if remote.size.is_some_and(|size| size != local.size) {
+            return Ok(true);
+        }
+        if let Some(record) = record {
+            if !record.local_changed(local) && !record.remote_changed(remote) {
+                return Ok(false);
+            }
+        }
+        if !options.verify_checksums {
+            return Ok(false);
+        }
+        let checksum = self.get_file_checksum(remote.file_id).await?;
+        Ok(!checksum.sha1.eq_ignore_ascii_case(&local.sha1))
+    }
+
+    /// Compares a local directory with a folder on pCloud and plans their synchronization.
+    ///
+    /// The files identical on both sides are recorded in `state`, and the files deleted on
+    /// both sides are removed from it. The other files are listed in the plan, and should be
+    /// recorded with [`SyncState::record`] or removed with [`SyncState::remove`] once synced.
+    ///
+    /// # Arguments
+    ///
+    /// * `local_path` - The local directory.
+    /// * `remote` - A value convertible into a [`FolderIdentifier`] representing the remote folder.
+    /// * `state` - The state of the last synchronization, empty for the first one.
+    /// * `options` - A [`SyncOptions`] to configure the direction and the checksum verification.
+    ///
+    /// # Returns
+    ///
+    /// A [`SyncPlan`] listing the files to synchronize.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SyncError`] if the local directory can't be read, or if the remote folder
+    /// can't be listed.
+    pub async fn plan_sync(
+        &self,
+        local_path: impl AsRef<Path>,
+        remote: impl Into<FolderIdentifier<'_>>,
+        state: &mut SyncState,
+        options: &SyncOptions,
+    ) -> Result<SyncPlan, SyncError> {
+        let local_root = local_path.as_ref().to_path_buf();
+        let mut local = local_files(&local_root, state).await?;
+        let folder = self
+            .list_folder_with_options(remote, ListFolderOptions::default().with_recursive())
+            .await?;
+        let remote_folder_id = folder.folder_id;
+        let mut remote = remote_files(folder);
+
+        let paths: BTreeSet<RemotePath> = local
+            .keys()
+            .chain(remote.keys())
+            .cloned()
+            .chain(
+                state
+                    .files
+                    .keys()
+                    .map(|path| RemotePath::default().join(path)),
+            )
+            .collect();
+        let entries: Vec<_> = paths
+            .into_iter()
+            .map(|path| {
+                let local_file = local.remove(&path);
+                let remote_file = remote.remove(&path);
+                (path, local_file, remote_file)
+            })
+            .collect();
+        // the checksums are fetched concurrently, before the state gets updated
+        let records = &*state;
+        let changes: Vec<bool> = futures_util::stream::iter(entries.iter())
+            .map(|(path, local_file, remote_file)| async move {
+                match (local_file, remote_file) {
+                    (Some(local), Some(remote)) => {
+                        self.sync_content_changed(local, remote, records.get(path), options)
+                            .await
+                    }
+                    _ => Ok(true),
+                }
+            })
+            .buffered(options.concurrency)
+            .try_collect()
+            .await?;
+
+        let mut items = Vec::new();
+        for ((path, local_file, remote_file), content_changed) in entries.into_iter().zip(changes) {
+            let record = state.get(&path);
+            match decide(
+                options.direction,
+                local_file.as_ref(),
+                remote_file.as_ref(),
+                record,
+                content_changed,
+            ) {
+                Some(action) => items.push(SyncItem {
+                    path,
+                    action,
+                    local: local_file,
+                    remote: remote_file,
+                }),
+                None => match (local_file, remote_file) {
+                    (Some(local), Some(remote)) => state.record(&path, &local, &remote),
+                    _ => {
+                        state.remove(&path);
+                    }
+                },
+            }
+        }
+        Ok(SyncPlan {
+            local_root,
+            remote_folder_id,
+            items,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        decide, ConflictKind, LocalFile, SyncAction, SyncDirection, SyncOptions, SyncRecord,
+        SyncState,
+    };
+    use crate::testing::{file, folder, response, with_fields, TempDir};
+    use crate::{Client, Credentials};
+    use mockito::Matcher;
+
+    fn local(sha1: &str) -> LocalFile {
+        LocalFile {
+            path: "file.txt".into(),
+            size: 5,
+            modified: None,
+            sha1: sha1.into(),
+        }
+    }
+
+    fn remote(hash: u64) -> crate::file::File {
+        let metadata = with_fields(
+            file(1, "file.txt"),
+            serde_json::json!({ "size": 5, "hash": hash }),
+        );
+        serde_json::from_str(&metadata).unwrap()
+    }
+
+    fn record() -> SyncRecord {
+        SyncRecord {
+            size: 5,
+            modified: None,
+            sha1: "old".into(),
+            file_id: 1,
+            remote_hash: Some(1),
+        }
+    }
+
+    #[test]
+    fn should_decide_two_way_actions() {
+        let direction = SyncDirection::TwoWay;
+        let record = record();
+        let cases = [
+            (
+                Some(local("new")),
+                Some(remote(1)),
+                Some(SyncAction::Upload),
+            ),
+            (
+                Some(local("old")),
+                Some(remote(2)),
+                Some(SyncAction::Download),
+            ),
+            (
+                Some(local("new")),
+                Some(remote(2)),
+                Some(SyncAction::Conflict(ConflictKind::BothModified)),
+            ),
+            (Some(local("old")), None, Some(SyncAction::DeleteLocal)),
+            (
+                Some(local("new")),
+                None,
+                Some(SyncAction::Conflict(
+                    ConflictKind::ModifiedLocallyDeletedRemotely,
+                )),
+            ),
+            (None, Some(remote(1)), Some(SyncAction::DeleteRemote)),
+            (
+                None,
+                Some(remote(2)),
+                Some(SyncAction::Conflict(
+                    ConflictKind::DeletedLocallyModifiedRemotely,
+                )),
+            ),
+        ];
+        for (local, remote, expected) in cases {
+            let action = decide(
+                direction,
+                local.as_ref(),
+                remote.as_ref(),
+                Some(&record),
+                true,
+            );
+            assert_eq!(action, expected, "{local:?} {:?}", remote.map(|f| f.hash));
+        }
+        // without state, nothing is deleted
+        assert_eq!(
+            decide(direction, Some(&local("new")), None, None, true),
+            Some(SyncAction::Upload)
+        );
+        assert_eq!(
+            decide(direction, None, Some(&remote(1)), None, true),
+            Some(SyncAction::Download)
+        );
+        assert_eq!(
+            decide(SyncDirection::Push, None, Some(&remote(1)), None, true),
+            Some(SyncAction::DeleteRemote)
+        );
+    }
+
+    #[tokio::test]
+    async fn should_plan_first_sync() {
+        let dir = TempDir::new("sync");
+        tokio::fs::create_dir_all(dir.join("sub")).await.unwrap();
+        tokio::fs::write(dir.join("same.txt"), b"hello")
+            .await
+            .unwrap();
+        tokio::fs::write(dir.join("sub/local.txt"), b"local")
+            .await
+            .unwrap();
+
+        let mut server = mockito::Server::new_async().await;
+        let m_list = server
+            .mock("GET", "/listfolder")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("folderid".into(), "12".into()),
+                Matcher::UrlEncoded("recursive".into(), "1".into()),
+            ]))
+            .with_status(200)
+            .with_body(response(folder(
+                12,
+                "root",
+                Some(
+                    &[
+                        with_fields(file(1, "same.txt"), serde_json::json!({ "size": 5 })),
+                        folder(13, "sub", Some(&file(2, "remote.txt"))),
+                    ]
+                    .join(","),
+                ),
+            )))
+            .create_async()
+            .await;
+        let m_checksum = server
+            .mock("GET", "/checksumfile")
+            .match_query(Matcher::UrlEncoded("fileid".into(), "1".into()))
+            .with_status(200)
+            .with_body(format!(
+                r#"{{ "result": 0, "sha1": "aaf4c61ddcc5e8a2dabede0f3b482cd9aea9434d", "metadata": {} }}"#,
+                with_fields(file(1, "same.txt"), serde_json::json!({ "size": 5 })),
+            ))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = Client::new(server.url(), Credentials::access_token("access-token")).unwrap();
+        let mut state = SyncState::default();
+        let plan = client
+            .plan_sync(&dir, 12, &mut state, &SyncOptions::default())
+            .await
+            .unwrap();
+        let actions: Vec<_> = plan
+            .items
+            .iter()
+            .map(|item| (item.path.as_str(), item.action))
+            .collect();
+        assert_eq!(
+            actions,
+            vec![
+                ("sub/local.txt", SyncAction::Upload),
+                ("sub/remote.txt", SyncAction::Download),
+            ]
+        );
+        assert_eq!(state.len(), 1);
+        let (path, record) = state.iter().next().unwrap();
+        assert_eq!(path, "same.txt");
+        assert_eq!(record.remote_hash, Some(42));
+        m_list.assert_async().await;
+        m_checksum.assert_async().await;
+
+        // the recorded file is neither hashed remotely nor planned again
+        let plan = client
+            .plan_sync(&dir, 12, &mut state, &SyncOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(plan.items.len(), 2);
+        m_checksum.assert_async().await;
+    }
+}
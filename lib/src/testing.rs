@@ -0,0 +1,88 @@
+//! Fixtures shared by the unit tests.
+
+use std::path::{Path, PathBuf};
+
+/// A temporary directory with a random name, removed with its content when dropped.
+pub(crate) struct TempDir(PathBuf);
+
+impl TempDir {
+    /// Creates a directory named after `prefix`, like `pcloud-upload-3f2a...`.
+    pub(crate) fn new(prefix: &str) -> Self {
+        let path =
+            std::env::temp_dir().join(format!("pcloud-{prefix}-{:016x}", rand::random::<u64>()));
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    pub(crate) fn join(&self, path: impl AsRef<Path>) -> PathBuf {
+        self.0.join(path)
+    }
+}
+
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Builds the JSON metadata of a folder, with its contents when provided.
+pub(crate) fn folder(id: u64, name: &str, contents: Option<&str>) -> String {
+    let contents = contents
+        .map(|value| format!(r#", "contents": [{value}]"#))
+        .unwrap_or_default();
+    format!(
+        r#"{{
+    "name": "{name}",
+    "created": "Fri, 23 Jul 2021 19:39:09 +0000",
+    "modified": "Fri, 23 Jul 2021 19:39:09 +0000",
+    "ismine": true,
+    "thumb": false,
+    "id": "d{id}",
+    "isshared": false,
+    "icon": "folder",
+    "isfolder": true,
+    "folderid": {id}{contents}
+}}"#
+    )
+}
+
+/// Builds the JSON metadata of a text file of 10 bytes.
+pub(crate) fn file(id: u64, name: &str) -> String {
+    format!(
+        r#"{{
+    "name": "{name}",
+    "created": "Fri, 23 Jul 2021 19:39:09 +0000",
+    "modified": "Fri, 23 Jul 2021 19:39:09 +0000",
+    "ismine": true,
+    "thumb": false,
+    "id": "f{id}",
+    "isshared": false,
+    "icon": "file",
+    "isfolder": false,
+    "fileid": {id},
+    "hash": 42,
+    "size": 10,
+    "contenttype": "text/plain"
+}}"#
+    )
+}
+
+/// Adds or replaces fields of the JSON metadata built by [`file`] or [`folder`].
+pub(crate) fn with_fields(metadata: String, fields: serde_json::Value) -> String {
+    let mut value: serde_json::Value = serde_json::from_str(&metadata).unwrap();
+    if let (Some(target), serde_json::Value::Object(fields)) = (value.as_object_mut(), fields) {
+        target.extend(fields);
+    }
+    value.to_string()
+}
+
+/// Wraps the JSON metadata in a successful response of the API.
+pub(crate) fn response(metadata: String) -> String {
+    format!(r#"{{ "result": 0, "metadata": {metadata} }}"#)
+}
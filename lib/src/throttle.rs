@@ -0,0 +1,184 @@
+//! Client-wide limits on the number of requests sent to the API.
+
+use std::time::Duration;
+
+use tokio::sync::{Mutex, Semaphore, SemaphorePermit};
+use tokio::time::Instant;
+
+/// A token bucket refilled at a constant rate.
+#[derive(Debug)]
+struct TokenBucket {
+    capacity: f64,
+    rate: f64,
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(requests_per_second: u32) -> Self {
+        let rate = f64::from(requests_per_second.max(1));
+        Self {
+            capacity: rate,
+            rate,
+            tokens: rate,
+            updated_at: Instant::now(),
+        }
+    }
+
+    /// Takes a token if available, otherwise returns how long to wait for the next one.
+    fn try_take(&mut self) -> Result<(), Duration> {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.updated_at = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / self.rate))
+        }
+    }
+}
+
+/// Limits shared by all the clones of a [`Client`](crate::Client).
+#[derive(Debug)]
+pub(crate) struct Throttle {
+    max_concurrent: usize,
+    semaphore: Option<Semaphore>,
+    bucket: Option<Mutex<TokenBucket>>,
+}
+
+impl Throttle {
+    /// Creates the limits, returns `None` when none is configured.
+    pub(crate) fn new(
+        max_concurrent: Option<usize>,
+        requests_per_second: Option<u32>,
+    ) -> Option<Self> {
+        if max_concurrent.is_none() && requests_per_second.is_none() {
+            return None;
+        }
+        let max_concurrent = max_concurrent.map(|value| value.max(1));
+        Some(Self {
+            max_concurrent: max_concurrent.unwrap_or_default(),
+            semaphore: max_concurrent.map(Semaphore::new),
+            bucket: requests_per_second.map(|rate| Mutex::new(TokenBucket::new(rate))),
+        })
+    }
+
+    /// Waits until a request can be sent.
+    ///
+    /// The returned permit must be held until the response has been read.
+    pub(crate) async fn acquire(&self, method: &str) -> Option<SemaphorePermit<'_>> {
+        let permit = match self.semaphore {
+            Some(ref semaphore) => Some(self.acquire_slot(semaphore, method).await),
+            None => None,
+        };
+        if let Some(ref bucket) = self.bucket {
+            self.acquire_token(bucket, method).await;
+        }
+        permit
+    }
+
+    async fn acquire_slot<'a>(
+        &self,
+        semaphore: &'a Semaphore,
+        method: &str,
+    ) -> SemaphorePermit<'a> {
+        if let Ok(permit) = semaphore.try_acquire() {
+            return permit;
+        }
+        let started = Instant::now();
+        tracing::debug!(
+            method,
+            max_concurrent = self.max_concurrent,
+            "waiting for a request slot"
+        );
+        let permit = semaphore
+            .acquire()
+            .await
+            .expect("the semaphore is never closed");
+        tracing::debug!(
+            method,
+            waited_ms = started.elapsed().as_millis() as u64,
+            "request slot acquired"
+        );
+        permit
+    }
+
+    async fn acquire_token(&self, bucket: &Mutex<TokenBucket>, method: &str) {
+        let started = Instant::now();
+        loop {
+            let wait = match bucket.lock().await.try_take() {
+                Ok(()) => break,
+                Err(wait) => wait,
+            };
+            tracing::debug!(
+                method,
+                wait_ms = wait.as_millis() as u64,
+                "rate limit reached, delaying request"
+            );
+            tokio::time::sleep(wait).await;
+        }
+        let waited = started.elapsed();
+        if !waited.is_zero() {
+            tracing::debug!(
+                method,
+                waited_ms = waited.as_millis() as u64,
+                "request released by rate limiter"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use tokio::time::Instant;
+
+    use super::Throttle;
+
+    #[test]
+    fn should_not_create_without_limits() {
+        assert!(Throttle::new(None, None).is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_delay_requests_over_the_rate() {
+        let throttle = Throttle::new(None, Some(2)).unwrap();
+        let started = Instant::now();
+        for _ in 0..2 {
+            throttle.acquire("listfolder").await;
+        }
+        assert!(started.elapsed() < Duration::from_millis(10));
+        throttle.acquire("listfolder").await;
+        assert!(started.elapsed() >= Duration::from_millis(500));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_limit_concurrent_requests() {
+        let throttle = Arc::new(Throttle::new(Some(2), None).unwrap());
+        let current = Arc::new(AtomicUsize::new(0));
+        let highest = Arc::new(AtomicUsize::new(0));
+        let tasks = (0..8)
+            .map(|_| {
+                let throttle = throttle.clone();
+                let current = current.clone();
+                let highest = highest.clone();
+                tokio::spawn(async move {
+                    let _permit = throttle.acquire("checksumfile").await;
+                    let count = current.fetch_add(1, Ordering::SeqCst) + 1;
+                    highest.fetch_max(count, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    current.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect::<Vec<_>>();
+        for task in tasks {
+            task.await.unwrap();
+        }
+        assert_eq!(highest.load(Ordering::SeqCst), 2);
+    }
+}
@@ -2,7 +2,6 @@
 
 use std::panic;
 
-use pcloud::builder::ClientBuilder;
 use pcloud::file::upload::MultiFileUpload;
 use pcloud::folder::ROOT;
 use pcloud::Credentials;
@@ -67,7 +66,8 @@ async fn complete() {
     // create file in folder
     let filename = create_filename("bin");
     let filecontent = create_file(1024 * 1024 * 10); // 10Mo
-    let files = MultiFileUpload::default().with_body_entry(filename.as_str(), None, filecontent);
+    let files =
+        MultiFileUpload::default().with_body_entry(filename.as_str(), None, filecontent.clone());
     let mut files = client.upload_files(folder.folder_id, files).await.unwrap();
     let file = files.pop().unwrap();
     // get file info
@@ -76,13 +76,10 @@ async fn complete() {
     // get file link
     let _link = client.get_file_link(file.file_id).await.unwrap();
     // download file
-    // let mut buffer: Vec<u8> = Vec::with_capacity(1024 * 1024 * 10);
-    // let cursor = Cursor::new(&mut buffer);
-    // let _size = FileDownloadCommand::new(file.file_id.into(), cursor)
-    //     .execute(&client)
-    //     .await
-    //     .unwrap();
-    // assert_eq!(buffer, filecontent);
+    let mut buffer: Vec<u8> = Vec::with_capacity(1024 * 1024 * 10);
+    let size = client.download(file.file_id, &mut buffer).await.unwrap();
+    assert_eq!(size as usize, filecontent.len());
+    assert_eq!(buffer, filecontent);
     // rename file
     let renamed_file = client
         .rename_file(file.file_id, "hello.world")